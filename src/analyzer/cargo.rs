@@ -0,0 +1,1243 @@
+// Running cargo subcommands and turning their output into something an
+// agent can act on.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+/// How long `cargo_run` lets a program run unless told otherwise.
+pub const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Bytes of stdout/stderr kept per stream unless told otherwise.
+pub const DEFAULT_OUTPUT_LIMIT: usize = 64 * 1024;
+
+/// How long a `cargo check` may take unless told otherwise.
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long a `cargo test` run may take unless told otherwise.
+pub const DEFAULT_TEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How long a `cargo bench` run may take unless told otherwise.
+pub const DEFAULT_BENCH_TIMEOUT: Duration = Duration::from_secs(1800);
+
+/// Bytes kept per stream of output that is parsed rather than shown; only
+/// a runaway build or test gets near it.
+pub const PARSED_OUTPUT_LIMIT: usize = 32 * 1024 * 1024;
+
+/// How long output is still read after the process exits or is killed,
+/// before whatever it left behind is killed too.
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// Relative change below which `run_cargo_bench` reports no difference.
+pub const DEFAULT_BENCH_THRESHOLD: f64 = 2.0;
+
+/// Filters for a `cargo test` run.
+#[derive(Debug, Clone, Default)]
+pub struct TestOptions {
+    pub package: Option<String>,
+    /// Integration test target, passed as `--test <name>`.
+    pub test_target: Option<String>,
+    /// Substring filter on test names.
+    pub filter: Option<String>,
+    pub ignored: bool,
+    pub features: Vec<String>,
+    pub no_default_features: bool,
+}
+
+impl TestOptions {
+    /// Arguments for `cargo test`, with libtest's JSON output when `json`.
+    pub fn args(&self, json: bool) -> Vec<String> {
+        let mut args = vec!["test".to_string(), "--no-fail-fast".to_string()];
+        if let Some(package) = &self.package {
+            args.extend(["--package".to_string(), package.clone()]);
+        }
+        if let Some(test_target) = &self.test_target {
+            args.extend(["--test".to_string(), test_target.clone()]);
+        }
+        if !self.features.is_empty() {
+            args.extend(["--features".to_string(), self.features.join(",")]);
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        args.push("--".to_string());
+        if let Some(filter) = &self.filter {
+            args.push(filter.clone());
+        }
+        if self.ignored {
+            args.push("--ignored".to_string());
+        }
+        if json {
+            args.extend(
+                [
+                    "-Z",
+                    "unstable-options",
+                    "--format",
+                    "json",
+                    "--report-time",
+                ]
+                .map(String::from),
+            );
+        }
+        args
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+impl TestStatus {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Passed => "ok",
+            Self::Failed => "FAILED",
+            Self::Ignored => "ignored",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub suite: String,
+    pub name: String,
+    pub status: TestStatus,
+    /// Seconds, only reported by libtest's JSON format.
+    pub duration: Option<f64>,
+    /// Captured output (including the panic message) for failures, or the
+    /// ignore reason.
+    pub output: Option<String>,
+}
+
+/// Names of the test binaries cargo ran, in order, taken from its
+/// `Running ...` and `Doc-tests ...` status lines.
+pub fn test_suites(stderr: &str) -> Vec<String> {
+    stderr
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| {
+            line.strip_prefix("Running ")
+                .map(|suite| {
+                    // Drop the `(target/debug/deps/name-hash)` suffix.
+                    suite
+                        .rsplit_once(" (")
+                        .map_or(suite, |(name, _)| name)
+                        .to_string()
+                })
+                .or_else(|| line.starts_with("Doc-tests ").then(|| line.to_string()))
+        })
+        .collect()
+}
+
+/// Parse libtest's plain output. Each `running N tests` block belongs to
+/// the next entry of `suites`.
+pub fn parse_libtest_output(stdout: &str, suites: &[String]) -> Vec<TestCase> {
+    let mut cases: Vec<TestCase> = Vec::new();
+    let mut suite_index = 0;
+    let mut suite = String::new();
+    let mut lines = stdout.lines().peekable();
+    let mut block_start = 0;
+
+    while let Some(line) = lines.next() {
+        if line.starts_with("running ") && (line.ends_with(" tests") || line.ends_with(" test")) {
+            suite = suites
+                .get(suite_index)
+                .cloned()
+                .unwrap_or_else(|| format!("suite {}", suite_index + 1));
+            suite_index += 1;
+            block_start = cases.len();
+        } else if let Some(rest) = line.strip_prefix("test ")
+            && let Some((name, outcome)) = rest.rsplit_once(" ... ")
+        {
+            let (status, output) = match outcome {
+                "ok" => (TestStatus::Passed, None),
+                "FAILED" => (TestStatus::Failed, None),
+                other => match other.strip_prefix("ignored") {
+                    Some(reason) => {
+                        let reason = reason.trim_start_matches(", ").trim();
+                        (
+                            TestStatus::Ignored,
+                            (!reason.is_empty()).then(|| reason.to_string()),
+                        )
+                    }
+                    None => continue,
+                },
+            };
+            cases.push(TestCase {
+                suite: suite.clone(),
+                name: name.to_string(),
+                status,
+                duration: None,
+                output,
+            });
+        } else if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            let mut output = String::new();
+            while let Some(next) = lines.peek() {
+                if next.starts_with("---- ") || *next == "failures:" {
+                    break;
+                }
+                output.push_str(next);
+                output.push('\n');
+                lines.next();
+            }
+            if let Some(case) = cases[block_start..]
+                .iter_mut()
+                .find(|case| case.name == name)
+            {
+                case.output = Some(output.trim().to_string());
+            }
+        }
+    }
+    cases
+}
+
+/// Parse libtest's `--format json` event stream.
+pub fn parse_libtest_json(stdout: &str, suites: &[String]) -> Vec<TestCase> {
+    let mut cases = Vec::new();
+    let mut suite_index = 0;
+    let mut suite = String::new();
+    for line in stdout.lines() {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let kind = event["type"].as_str().unwrap_or_default();
+        let outcome = event["event"].as_str().unwrap_or_default();
+        if kind == "suite" && outcome == "started" {
+            suite = suites
+                .get(suite_index)
+                .cloned()
+                .unwrap_or_else(|| format!("suite {}", suite_index + 1));
+            suite_index += 1;
+            continue;
+        }
+        if kind != "test" {
+            continue;
+        }
+        let status = match outcome {
+            "ok" => TestStatus::Passed,
+            "failed" | "timeout" => TestStatus::Failed,
+            "ignored" => TestStatus::Ignored,
+            _ => continue,
+        };
+        let output = event
+            .get("stdout")
+            .or_else(|| event.get("message"))
+            .and_then(|v| v.as_str())
+            .map(|text| text.trim().to_string());
+        cases.push(TestCase {
+            suite: suite.clone(),
+            name: event["name"].as_str().unwrap_or_default().to_string(),
+            status,
+            duration: event["exec_time"].as_f64(),
+            output,
+        });
+    }
+    cases
+}
+
+/// `error...` lines from cargo's human readable output, each with the
+/// `-->` location that follows it.
+pub fn compiler_errors(stderr: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut lines = stderr.lines().peekable();
+    while let Some(line) = lines.next() {
+        if !line.starts_with("error") {
+            continue;
+        }
+        let mut error = line.to_string();
+        if let Some(location) = lines.peek().map(|next| next.trim_start())
+            && let Some(location) = location.strip_prefix("--> ")
+        {
+            error.push_str(&format!(" ({location})"));
+            lines.next();
+        }
+        errors.push(error);
+    }
+    errors
+}
+
+pub fn format_test_results(cases: &[TestCase]) -> String {
+    let count = |status| cases.iter().filter(|case| case.status == status).count();
+    let mut result = format!(
+        "cargo test: {} passed, {} failed, {} ignored\n",
+        count(TestStatus::Passed),
+        count(TestStatus::Failed),
+        count(TestStatus::Ignored)
+    );
+
+    let mut suite = None;
+    for case in cases {
+        if suite != Some(&case.suite) {
+            result.push_str(&format!("\n{}:\n", case.suite));
+            suite = Some(&case.suite);
+        }
+        result.push_str(&format!("  {:<7} {}", case.status.label(), case.name));
+        if let Some(duration) = case.duration {
+            result.push_str(&format!(" ({duration:.3}s)"));
+        }
+        if case.status == TestStatus::Ignored
+            && let Some(reason) = &case.output
+        {
+            result.push_str(&format!(" - {reason}"));
+        }
+        result.push('\n');
+    }
+
+    let failures: Vec<&TestCase> = cases
+        .iter()
+        .filter(|case| case.status == TestStatus::Failed)
+        .collect();
+    if !failures.is_empty() {
+        result.push_str("\nFailures:\n");
+        for case in failures {
+            result.push_str(&format!(
+                "\n---- {} ({}) ----\n{}\n",
+                case.name,
+                case.suite,
+                case.output.as_deref().unwrap_or("(no output captured)")
+            ));
+        }
+    }
+    result
+}
+
+/// Target selection shared by `cargo build`, `cargo check` and `cargo run`.
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    pub package: Option<String>,
+    pub bin: Option<String>,
+    pub example: Option<String>,
+    /// Cargo profile, e.g. `release`.
+    pub profile: Option<String>,
+    /// Target triple to build for.
+    pub target: Option<String>,
+    pub features: Vec<String>,
+    pub no_default_features: bool,
+}
+
+impl BuildOptions {
+    /// Arguments for `cargo <subcommand>` with JSON messages.
+    pub fn args(&self, subcommand: &str) -> Vec<String> {
+        let mut args = vec![
+            subcommand.to_string(),
+            "--message-format".to_string(),
+            "json".to_string(),
+        ];
+        let flags = [
+            ("--package", &self.package),
+            ("--bin", &self.bin),
+            ("--example", &self.example),
+            ("--profile", &self.profile),
+            ("--target", &self.target),
+        ];
+        for (flag, value) in flags {
+            if let Some(value) = value {
+                args.extend([flag.to_string(), value.clone()]);
+            }
+        }
+        if !self.features.is_empty() {
+            args.extend(["--features".to_string(), self.features.join(",")]);
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        args
+    }
+}
+
+/// A `compiler-message` from `--message-format json`.
+#[derive(Debug, Clone)]
+pub struct CompilerMessage {
+    pub level: String,
+    pub code: Option<String>,
+    pub message: String,
+    /// `file:line:column` of the primary span.
+    pub location: Option<String>,
+    pub rendered: Option<String>,
+}
+
+/// A `compiler-artifact` produced by a workspace member.
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    pub name: String,
+    pub kinds: Vec<String>,
+    pub executable: Option<PathBuf>,
+    pub filenames: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default)]
+pub struct BuildReport {
+    pub success: bool,
+    pub messages: Vec<CompilerMessage>,
+    pub artifacts: Vec<Artifact>,
+}
+
+impl BuildReport {
+    /// Parse cargo's JSON message stream.
+    pub fn parse(stdout: &str) -> Self {
+        let mut report = Self::default();
+        for line in stdout.lines() {
+            let Ok(message) = serde_json::from_str::<Value>(line) else {
+                continue;
+            };
+            match message["reason"].as_str() {
+                Some("compiler-message") => {
+                    let diagnostic = &message["message"];
+                    let location = diagnostic["spans"]
+                        .as_array()
+                        .and_then(|spans| {
+                            spans
+                                .iter()
+                                .find(|span| span["is_primary"].as_bool() == Some(true))
+                        })
+                        .map(|span| {
+                            format!(
+                                "{}:{}:{}",
+                                span["file_name"].as_str().unwrap_or_default(),
+                                span["line_start"],
+                                span["column_start"]
+                            )
+                        });
+                    report.messages.push(CompilerMessage {
+                        level: diagnostic["level"].as_str().unwrap_or_default().to_string(),
+                        code: diagnostic["code"]["code"].as_str().map(String::from),
+                        message: diagnostic["message"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        location,
+                        rendered: diagnostic["rendered"].as_str().map(String::from),
+                    });
+                }
+                Some("compiler-artifact") => {
+                    let package_id = message["package_id"].as_str().unwrap_or_default();
+                    if !package_id.contains("path+file://") {
+                        continue;
+                    }
+                    let strings = |value: &Value| -> Vec<String> {
+                        value
+                            .as_array()
+                            .map(|arr| {
+                                arr.iter()
+                                    .filter_map(|v| v.as_str().map(String::from))
+                                    .collect()
+                            })
+                            .unwrap_or_default()
+                    };
+                    report.artifacts.push(Artifact {
+                        name: message["target"]["name"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        kinds: strings(&message["target"]["kind"]),
+                        executable: message["executable"].as_str().map(PathBuf::from),
+                        filenames: strings(&message["filenames"])
+                            .into_iter()
+                            .map(PathBuf::from)
+                            .collect(),
+                    });
+                }
+                Some("build-finished") => {
+                    report.success = message["success"].as_bool().unwrap_or(false);
+                }
+                _ => {}
+            }
+        }
+        report
+    }
+
+    /// Compiler errors as `error[code]: message (location)`, without the
+    /// closing "aborting due to" summary.
+    pub fn errors(&self) -> Vec<String> {
+        self.messages
+            .iter()
+            .filter(|message| {
+                message.level == "error" && !message.message.starts_with("aborting due to")
+            })
+            .map(|message| {
+                let mut error = match &message.code {
+                    Some(code) => format!("error[{code}]: {}", message.message),
+                    None => format!("error: {}", message.message),
+                };
+                if let Some(location) = &message.location {
+                    error.push_str(&format!(" ({location})"));
+                }
+                error
+            })
+            .collect()
+    }
+
+    pub fn executables(&self) -> Vec<&Artifact> {
+        self.artifacts
+            .iter()
+            .filter(|artifact| artifact.executable.is_some())
+            .collect()
+    }
+
+    /// Summary of a `cargo <subcommand>` run.
+    pub fn format(&self, subcommand: &str) -> String {
+        let count = |level: &str| {
+            self.messages
+                .iter()
+                .filter(|message| message.level == level)
+                .count()
+        };
+        let mut result = format!(
+            "cargo {subcommand} {}: {} errors, {} warnings\n",
+            if self.success { "succeeded" } else { "failed" },
+            count("error"),
+            count("warning")
+        );
+
+        if !self.artifacts.is_empty() {
+            result.push_str("\nArtifacts:\n");
+            for artifact in &self.artifacts {
+                let files: Vec<String> = match &artifact.executable {
+                    Some(executable) => vec![executable.display().to_string()],
+                    None => artifact
+                        .filenames
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect(),
+                };
+                result.push_str(&format!(
+                    "  {} {}: {}\n",
+                    artifact.kinds.join(","),
+                    artifact.name,
+                    files.join(", ")
+                ));
+            }
+        }
+
+        let diagnostics: Vec<&CompilerMessage> = self
+            .messages
+            .iter()
+            .filter(|message| message.level == "error" || message.level == "warning")
+            .collect();
+        if !diagnostics.is_empty() {
+            result.push_str("\nDiagnostics:\n");
+            for message in diagnostics {
+                let code = message
+                    .code
+                    .as_ref()
+                    .map(|code| format!("[{code}]"))
+                    .unwrap_or_default();
+                result.push_str(&format!("  {}{code}: {}", message.level, message.message));
+                if let Some(location) = &message.location {
+                    result.push_str(&format!(" at {location}"));
+                }
+                result.push('\n');
+                if message.level == "error"
+                    && let Some(rendered) = &message.rendered
+                {
+                    for line in rendered.trim_end().lines() {
+                        result.push_str(&format!("    {line}\n"));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Run `cargo build` with JSON messages in `workspace`.
+pub async fn build(workspace: &Path, options: &BuildOptions) -> Result<BuildReport> {
+    let output = Command::new("cargo")
+        .args(options.args("build"))
+        .current_dir(workspace)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+    build_report(
+        "build",
+        output.status.success(),
+        &String::from_utf8_lossy(&output.stdout),
+        &String::from_utf8_lossy(&output.stderr),
+    )
+}
+
+/// Run `cargo check` with JSON messages in `workspace`, killing it after
+/// `timeout`.
+pub async fn check(
+    workspace: &Path,
+    options: &BuildOptions,
+    timeout: Duration,
+) -> Result<BuildReport> {
+    let mut command = Command::new("cargo");
+    command.args(options.args("check")).current_dir(workspace);
+    let output = run_with_limits(command, timeout, PARSED_OUTPUT_LIMIT).await?;
+    let Some(status) = output.status else {
+        return Err(anyhow::anyhow!(
+            "cargo check timed out after {}s",
+            timeout.as_secs()
+        ));
+    };
+    build_report("check", status.success(), &output.stdout, &output.stderr)
+}
+
+/// The report of a `cargo <subcommand>` run, or cargo's own error if it
+/// failed without compiler errors, e.g. for an unknown package or target.
+fn build_report(
+    subcommand: &str,
+    success: bool,
+    stdout: &str,
+    stderr: &str,
+) -> Result<BuildReport> {
+    let report = BuildReport::parse(stdout);
+    if !success && !report.messages.iter().any(|m| m.level == "error") {
+        let errors = compiler_errors(stderr);
+        return Err(anyhow::anyhow!(
+            "cargo {subcommand} failed:\n{}",
+            if errors.is_empty() {
+                stderr.trim().to_string()
+            } else {
+                errors.join("\n")
+            }
+        ));
+    }
+    Ok(report)
+}
+
+/// Output of a process run under a time and size limit.
+#[derive(Debug)]
+pub struct LimitedOutput {
+    /// `None` if the process was killed after the timeout.
+    pub status: Option<ExitStatus>,
+    pub stdout: String,
+    pub stderr: String,
+    pub truncated: bool,
+    pub elapsed: Duration,
+}
+
+/// Run `command`, killing it after `timeout` and keeping at most `limit`
+/// bytes of each output stream. On Unix the command runs in its own process
+/// group, so that processes it starts (e.g. the test binary under `cargo
+/// test`) are killed with it.
+pub async fn run_with_limits(
+    mut command: Command,
+    timeout: Duration,
+    limit: usize,
+) -> Result<LimitedOutput> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+    let started = Instant::now();
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("Failed to capture stdout"))?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("Failed to capture stderr"))?;
+    let stdout = tokio::spawn(read_limited(stdout, limit));
+    let stderr = tokio::spawn(read_limited(stderr, limit));
+
+    let status = match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(status) => Some(status?),
+        Err(_) => {
+            kill_process_group(pid);
+            child.kill().await?;
+            None
+        }
+    };
+    let elapsed = started.elapsed();
+
+    // Processes left behind keep the pipes open; kill them if reading
+    // doesn't finish promptly.
+    let readers = async { tokio::join!(stdout, stderr) };
+    tokio::pin!(readers);
+    let (stdout, stderr) = match tokio::time::timeout(OUTPUT_GRACE, &mut readers).await {
+        Ok(output) => output,
+        Err(_) => {
+            kill_process_group(pid);
+            tokio::time::timeout(OUTPUT_GRACE, &mut readers)
+                .await
+                .map_err(|_| {
+                    anyhow::anyhow!("Output is held open by a process outside the process group")
+                })?
+        }
+    };
+    let (stdout, stdout_truncated) = stdout??;
+    let (stderr, stderr_truncated) = stderr??;
+    Ok(LimitedOutput {
+        status,
+        stdout,
+        stderr,
+        truncated: stdout_truncated || stderr_truncated,
+        elapsed,
+    })
+}
+
+/// Kill every process in the group led by `pid`.
+fn kill_process_group(pid: Option<u32>) {
+    #[cfg(unix)]
+    if let Some(pid) = pid.and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+        // SAFETY: kill(2) takes no pointers; a negative pid addresses the
+        // process group the child was spawned into.
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// Read a stream to the end, keeping only the first `limit` bytes.
+async fn read_limited(
+    mut reader: impl AsyncRead + Unpin,
+    limit: usize,
+) -> std::io::Result<(String, bool)> {
+    let mut kept = Vec::new();
+    let mut buffer = [0u8; 8192];
+    let mut truncated = false;
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        let room = limit.saturating_sub(kept.len());
+        truncated |= read > room;
+        kept.extend_from_slice(&buffer[..read.min(room)]);
+    }
+    Ok((String::from_utf8_lossy(&kept).into_owned(), truncated))
+}
+
+impl LimitedOutput {
+    pub fn format(&self, limit: usize) -> String {
+        let status = match self.status {
+            Some(status) => match status.code() {
+                Some(code) => format!("exited with status {code}"),
+                None => format!("terminated by {status}"),
+            },
+            None => "timed out and was killed".to_string(),
+        };
+        let mut result = format!(
+            "Process {status} after {:.2}s\n",
+            self.elapsed.as_secs_f64()
+        );
+        if self.truncated {
+            result.push_str(&format!("(output truncated to {limit} bytes per stream)\n"));
+        }
+        result.push_str(&format!("\nstdout:\n{}\n", self.stdout.trim_end()));
+        result.push_str(&format!("\nstderr:\n{}\n", self.stderr.trim_end()));
+        result
+    }
+}
+
+/// Selection for a `cargo bench` run.
+#[derive(Debug, Clone, Default)]
+pub struct BenchOptions {
+    pub package: Option<String>,
+    /// Bench target, passed as `--bench <name>`.
+    pub bench: Option<String>,
+    /// Substring filter on benchmark names.
+    pub filter: Option<String>,
+    pub features: Vec<String>,
+    pub no_default_features: bool,
+}
+
+impl BenchOptions {
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!["bench".to_string()];
+        if let Some(package) = &self.package {
+            args.extend(["--package".to_string(), package.clone()]);
+        }
+        if let Some(bench) = &self.bench {
+            args.extend(["--bench".to_string(), bench.clone()]);
+        }
+        if !self.features.is_empty() {
+            args.extend(["--features".to_string(), self.features.join(",")]);
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        if let Some(filter) = &self.filter {
+            args.extend(["--".to_string(), filter.clone()]);
+        }
+        args
+    }
+}
+
+/// Benchmark name to its time per iteration in nanoseconds.
+pub type BenchTimings = BTreeMap<String, f64>;
+
+/// Parse libtest `#[bench]` lines and criterion's `time: [low estimate
+/// high]` reports.
+pub fn parse_bench_output(stdout: &str) -> BenchTimings {
+    let mut timings = BenchTimings::new();
+    let mut previous = "";
+    for line in stdout.lines() {
+        if let Some(rest) = line.strip_prefix("test ")
+            && let Some((name, result)) = rest.split_once(" ... bench:")
+            && let Some(value) = result.split_whitespace().next()
+            && let Ok(value) = value.replace(',', "").parse::<f64>()
+        {
+            timings.insert(name.trim().to_string(), value);
+        } else if let Some((name, rest)) = line.split_once("time:")
+            && let Some(values) = rest
+                .trim()
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .map(|(values, _)| values)
+        {
+            // Long names are printed on a line of their own.
+            let name = if name.trim().is_empty() {
+                previous.trim()
+            } else {
+                name.trim()
+            };
+            let parts: Vec<&str> = values.split_whitespace().collect();
+            if let [_, _, estimate, unit, ..] = parts.as_slice()
+                && let Ok(estimate) = estimate.parse::<f64>()
+                && let Some(scale) = nanoseconds_per(unit)
+            {
+                timings.insert(name.to_string(), estimate * scale);
+            }
+        }
+        if !line.trim().is_empty() {
+            previous = line;
+        }
+    }
+    timings
+}
+
+fn nanoseconds_per(unit: &str) -> Option<f64> {
+    match unit {
+        "ps" => Some(0.001),
+        "ns" => Some(1.0),
+        "µs" | "us" => Some(1_000.0),
+        "ms" => Some(1_000_000.0),
+        "s" => Some(1_000_000_000.0),
+        _ => None,
+    }
+}
+
+fn format_duration(nanoseconds: f64) -> String {
+    let (value, unit) = if nanoseconds >= 1_000_000_000.0 {
+        (nanoseconds / 1_000_000_000.0, "s")
+    } else if nanoseconds >= 1_000_000.0 {
+        (nanoseconds / 1_000_000.0, "ms")
+    } else if nanoseconds >= 1_000.0 {
+        (nanoseconds / 1_000.0, "µs")
+    } else {
+        (nanoseconds, "ns")
+    };
+    format!("{value:.3} {unit}")
+}
+
+/// Where a named baseline lives under the target directory.
+pub fn baseline_path(target_directory: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        || name.starts_with('.')
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid baseline name '{name}', use letters, digits, '-', '_' and '.'"
+        ));
+    }
+    Ok(target_directory
+        .join("rust-mcp")
+        .join("bench-baselines")
+        .join(format!("{name}.json")))
+}
+
+pub fn load_baseline(path: &Path) -> Result<BenchTimings> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read baseline {}: {e}", path.display()))?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+pub fn save_baseline(path: &Path, timings: &BenchTimings) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(timings)?)?;
+    Ok(())
+}
+
+/// List timings, and when a baseline is given, the change of each
+/// benchmark relative to it. Changes within `threshold` percent count as
+/// noise.
+pub fn format_bench_results(
+    timings: &BenchTimings,
+    baseline: Option<(&str, &BenchTimings)>,
+    threshold: f64,
+) -> String {
+    let mut result = format!("cargo bench: {} benchmarks\n", timings.len());
+    let Some((name, baseline)) = baseline else {
+        for (bench, time) in timings {
+            result.push_str(&format!("  {bench}: {}\n", format_duration(*time)));
+        }
+        return result;
+    };
+
+    let (mut regressed, mut improved) = (0, 0);
+    let mut lines = String::new();
+    for (bench, time) in timings {
+        let Some(before) = baseline.get(bench) else {
+            lines.push_str(&format!(
+                "  new        {bench}: {}\n",
+                format_duration(*time)
+            ));
+            continue;
+        };
+        let delta = if *before > 0.0 {
+            (time - before) / before * 100.0
+        } else {
+            0.0
+        };
+        let label = if delta > threshold {
+            regressed += 1;
+            "REGRESSED"
+        } else if delta < -threshold {
+            improved += 1;
+            "improved"
+        } else {
+            "unchanged"
+        };
+        lines.push_str(&format!(
+            "  {label:<10} {bench}: {} -> {} ({delta:+.2}%)\n",
+            format_duration(*before),
+            format_duration(*time)
+        ));
+    }
+    for bench in baseline
+        .keys()
+        .filter(|bench| !timings.contains_key(*bench))
+    {
+        lines.push_str(&format!("  missing    {bench}\n"));
+    }
+    result.push_str(&format!(
+        "Compared with baseline '{name}' (noise threshold {threshold}%): {regressed} regressed, {improved} improved\n{lines}"
+    ));
+    result
+}
+
+/// `cargo metadata --no-deps` for `workspace`.
+async fn workspace_metadata(workspace: &Path) -> Result<Value> {
+    let output = Command::new("cargo")
+        .args([
+            "metadata",
+            "--no-deps",
+            "--offline",
+            "--format-version",
+            "1",
+        ])
+        .current_dir(workspace)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// The target directory cargo uses for `workspace`.
+pub async fn target_directory(workspace: &Path) -> Result<PathBuf> {
+    let metadata = workspace_metadata(workspace).await?;
+    metadata["target_directory"]
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow::anyhow!("cargo metadata did not report a target directory"))
+}
+
+/// Crate root files of the workspace members, library targets first.
+pub async fn member_crate_roots(workspace: &Path) -> Result<Vec<PathBuf>> {
+    let metadata = workspace_metadata(workspace).await?;
+    let mut libraries = Vec::new();
+    let mut others = Vec::new();
+    for package in metadata["packages"].as_array().into_iter().flatten() {
+        for target in package["targets"].as_array().into_iter().flatten() {
+            let Some(src_path) = target["src_path"].as_str() else {
+                continue;
+            };
+            let kinds: Vec<&str> = target["kind"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            if kinds
+                .iter()
+                .any(|kind| matches!(*kind, "lib" | "rlib" | "dylib" | "proc-macro"))
+            {
+                libraries.push(PathBuf::from(src_path));
+            } else if kinds.contains(&"bin") {
+                others.push(PathBuf::from(src_path));
+            }
+        }
+    }
+    libraries.extend(others);
+    Ok(libraries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_STDERR: &str = "   Compiling tt v0.1.0 (/tmp/tt)
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.38s
+     Running unittests src/lib.rs (target/debug/deps/tt-2ffcd961b67f0851)
+     Running tests/it.rs (target/debug/deps/it-0d1c3d52a5f8e6f1)
+   Doc-tests tt
+error: 2 targets failed:
+    `--lib`
+    `--test it`
+";
+
+    const TEST_STDOUT: &str = "
+running 4 tests
+test tests::bad ... FAILED
+test tests::ok ... ok
+test tests::slow ... ignored, slow
+test tests::slow2 ... ignored
+
+failures:
+
+---- tests::bad stdout ----
+some output
+
+thread 'tests::bad' panicked at src/lib.rs:8:49:
+assertion `left == right` failed: math is broken
+  left: 3
+ right: 4
+
+
+failures:
+    tests::bad
+
+test result: FAILED. 1 passed; 1 failed; 2 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+
+running 1 test
+test integration ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+
+running 1 test
+test src/lib.rs - add (line 1) ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.12s
+";
+
+    const TEST_JSON: &str = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::bad" }
+{ "type": "test", "name": "tests::bad", "event": "failed", "exec_time": 0.000050971, "stdout": "some output\n\nthread 'tests::bad' panicked at src/lib.rs:8:49:\nmath is broken\n" }
+{ "type": "test", "event": "started", "name": "tests::ok" }
+{ "type": "test", "name": "tests::ok", "event": "ok", "exec_time": 0.000000469 }
+{ "type": "test", "name": "tests::slow", "event": "ignored", "message": "slow" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.000330584 }
+{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "name": "integration", "event": "ok", "exec_time": 0.001 }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.002 }
+"#;
+
+    const BUILD_JSON: &str = r#"{"reason":"compiler-artifact","package_id":"registry+https://github.com/rust-lang/crates.io-index#itoa@1.0.15","target":{"kind":["lib"],"name":"itoa"},"filenames":["/tmp/demo/target/debug/deps/libitoa-1.rlib"],"executable":null,"fresh":true}
+{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","target":{"kind":["bin"],"name":"demo"},"message":{"rendered":"warning: unused variable: `a`\n","$message_type":"diagnostic","children":[],"level":"warning","message":"unused variable: `a`","spans":[{"file_name":"src/main.rs","line_start":2,"column_start":9,"is_primary":true}],"code":{"code":"unused_variables","explanation":null}}}
+{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","target":{"kind":["bin"],"name":"demo"},"message":{"rendered":"error[E0308]: mismatched types\n --> src/main.rs:3:18\n","$message_type":"diagnostic","children":[],"level":"error","message":"mismatched types","spans":[{"file_name":"src/main.rs","line_start":3,"column_start":13,"is_primary":false},{"file_name":"src/main.rs","line_start":3,"column_start":18,"is_primary":true}],"code":{"code":"E0308","explanation":"..."}}}
+{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","target":{"kind":["bin"],"name":"demo"},"message":{"rendered":"error: aborting due to 1 previous error\n","$message_type":"diagnostic","children":[],"level":"error","message":"aborting due to 1 previous error","spans":[],"code":null}}
+{"reason":"compiler-artifact","package_id":"path+file:///tmp/demo#0.1.0","target":{"kind":["lib"],"name":"demo"},"filenames":["/tmp/demo/target/debug/libdemo.rlib"],"executable":null,"fresh":false}
+{"reason":"compiler-artifact","package_id":"path+file:///tmp/demo#0.1.0","target":{"kind":["bin"],"name":"demo"},"filenames":["/tmp/demo/target/debug/demo"],"executable":"/tmp/demo/target/debug/demo","fresh":false}
+   Compiling demo v0.1.0 (/tmp/demo)
+{"reason":"build-finished","success":false}
+"#;
+
+    fn summary(cases: &[TestCase]) -> Vec<(&str, &str, TestStatus)> {
+        cases
+            .iter()
+            .map(|case| (case.suite.as_str(), case.name.as_str(), case.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_suites_come_from_running_and_doc_test_lines() {
+        assert_eq!(
+            test_suites(TEST_STDERR),
+            ["unittests src/lib.rs", "tests/it.rs", "Doc-tests tt"]
+        );
+    }
+
+    #[test]
+    fn parses_plain_libtest_output() {
+        let cases = parse_libtest_output(TEST_STDOUT, &test_suites(TEST_STDERR));
+        assert_eq!(
+            summary(&cases),
+            [
+                ("unittests src/lib.rs", "tests::bad", TestStatus::Failed),
+                ("unittests src/lib.rs", "tests::ok", TestStatus::Passed),
+                ("unittests src/lib.rs", "tests::slow", TestStatus::Ignored),
+                ("unittests src/lib.rs", "tests::slow2", TestStatus::Ignored),
+                ("tests/it.rs", "integration", TestStatus::Passed),
+                (
+                    "Doc-tests tt",
+                    "src/lib.rs - add (line 1)",
+                    TestStatus::Passed
+                ),
+            ]
+        );
+        let failure = cases[0].output.as_deref().unwrap();
+        assert!(failure.starts_with("some output"));
+        assert!(failure.ends_with("right: 4"));
+        assert_eq!(cases[2].output.as_deref(), Some("slow"));
+        assert_eq!(cases[3].output, None);
+    }
+
+    #[test]
+    fn parses_libtest_json_with_timings() {
+        let suites = ["unittests src/lib.rs".to_string()];
+        let cases = parse_libtest_json(TEST_JSON, &suites);
+        assert_eq!(
+            summary(&cases),
+            [
+                ("unittests src/lib.rs", "tests::bad", TestStatus::Failed),
+                ("unittests src/lib.rs", "tests::ok", TestStatus::Passed),
+                ("unittests src/lib.rs", "tests::slow", TestStatus::Ignored),
+                ("suite 2", "integration", TestStatus::Passed),
+            ]
+        );
+        assert_eq!(cases[1].duration, Some(0.000000469));
+        assert!(
+            cases[0]
+                .output
+                .as_deref()
+                .unwrap()
+                .ends_with("math is broken")
+        );
+        assert_eq!(cases[2].output.as_deref(), Some("slow"));
+    }
+
+    #[test]
+    fn compiler_errors_take_the_location_line() {
+        let stderr = "warning: unused variable: `error`
+ --> src/lib.rs:1:5
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:3:13
+  |
+3 |     let y = x;
+  |             ^ not found in this scope
+error: could not compile `demo` (bin \"demo\") due to 1 previous error
+";
+        assert_eq!(
+            compiler_errors(stderr),
+            [
+                "error[E0425]: cannot find value `x` in this scope (src/main.rs:3:13)",
+                "error: could not compile `demo` (bin \"demo\") due to 1 previous error",
+            ]
+        );
+    }
+
+    #[test]
+    fn build_report_keeps_workspace_artifacts_and_messages() {
+        let report = BuildReport::parse(BUILD_JSON);
+        assert!(!report.success);
+        let names: Vec<&str> = report.artifacts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["demo", "demo"]);
+        let executables = report.executables();
+        assert_eq!(executables.len(), 1);
+        assert_eq!(
+            executables[0].executable.as_deref(),
+            Some(Path::new("/tmp/demo/target/debug/demo"))
+        );
+
+        assert_eq!(report.messages.len(), 3);
+        let warning = &report.messages[0];
+        assert_eq!(warning.level, "warning");
+        assert_eq!(warning.code.as_deref(), Some("unused_variables"));
+        assert_eq!(warning.location.as_deref(), Some("src/main.rs:2:9"));
+        assert_eq!(
+            report.errors(),
+            ["error[E0308]: mismatched types (src/main.rs:3:18)"]
+        );
+        assert!(
+            report
+                .format("build")
+                .starts_with("cargo build failed: 2 errors, 1 warnings")
+        );
+    }
+
+    #[test]
+    fn parses_libtest_bench_output() {
+        let stdout = "
+running 3 tests
+test parse_small ... bench:         512.30 ns/iter (+/- 12.40)
+test parse_large ... bench:   1,234,567 ns/iter (+/- 8,910)
+test tests::unit ... ignored
+
+test result: ok. 0 passed; 0 failed; 1 ignored; 2 measured; 0 filtered out; finished in 3.21s
+";
+        let timings = parse_bench_output(stdout);
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings["parse_small"], 512.3);
+        assert_eq!(timings["parse_large"], 1_234_567.0);
+    }
+
+    #[test]
+    fn parses_criterion_output_with_wrapped_names() {
+        let stdout = "Benchmarking fib 20: Warming up for 3.0000 s
+fib 20                  time:   [12.345 µs 12.400 µs 12.460 µs]
+                        change: [-1.0% +0.5% +2.0%] (p = 0.50 > 0.05)
+                        No change in performance detected.
+a very long benchmark name that wraps/onto its own line
+                        time:   [1.2000 ms 1.3000 ms 1.4000 ms]
+Found 2 outliers among 100 measurements (2.00%)
+";
+        let timings = parse_bench_output(stdout);
+        assert_eq!(timings.len(), 2);
+        assert!((timings["fib 20"] - 12_400.0).abs() < 1e-6);
+        assert!(
+            (timings["a very long benchmark name that wraps/onto its own line"] - 1_300_000.0)
+                .abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn bench_results_classify_changes_against_the_threshold() {
+        let baseline: BenchTimings = [("a", 100.0), ("b", 100.0), ("c", 100.0), ("gone", 5.0)]
+            .into_iter()
+            .map(|(name, time)| (name.to_string(), time))
+            .collect();
+        let timings: BenchTimings = [("a", 110.0), ("b", 101.0), ("c", 80.0), ("new", 2_500.0)]
+            .into_iter()
+            .map(|(name, time)| (name.to_string(), time))
+            .collect();
+        let result = format_bench_results(&timings, Some(("main", &baseline)), 2.0);
+        assert!(result.contains(
+            "Compared with baseline 'main' (noise threshold 2%): 1 regressed, 1 improved"
+        ));
+        assert!(result.contains("  REGRESSED  a: 100.000 ns -> 110.000 ns (+10.00%)"));
+        assert!(result.contains("  unchanged  b: 100.000 ns -> 101.000 ns (+1.00%)"));
+        assert!(result.contains("  improved   c: 100.000 ns -> 80.000 ns (-20.00%)"));
+        assert!(result.contains("  new        new: 2.500 µs"));
+        assert!(result.contains("  missing    gone"));
+
+        let plain = format_bench_results(&timings, None, 2.0);
+        assert!(plain.contains("  c: 80.000 ns"));
+        assert!(!plain.contains("baseline"));
+    }
+}
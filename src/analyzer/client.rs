@@ -1,10 +1,38 @@
 use anyhow::Result;
 use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
-use tokio::process::Child;
+use tokio::process::{Child, ChildStdout};
+use tokio::sync::mpsc;
 
+use crate::analyzer::cargo;
+use crate::analyzer::code_actions::{self, CodeAction};
+use crate::analyzer::completion::{self, Completion};
+use crate::analyzer::crate_index::{self, CrateIndex, CrateInfo};
+use crate::analyzer::diagnostics::{self, Diagnostic, Severity};
+use crate::analyzer::edit::{self, FileChange};
+use crate::analyzer::expand;
+use crate::analyzer::features::{self, FeatureGraph};
+use crate::analyzer::imports::{self, Pending, Resolution};
+use crate::analyzer::inlay;
+use crate::analyzer::lsp::parse_lsp_message;
+use crate::analyzer::manifest;
+use crate::analyzer::match_arms::{self, FillOptions, Filled, NestedFields};
+use crate::analyzer::move_items;
+use crate::analyzer::outline;
 use crate::analyzer::protocol::*;
+use crate::analyzer::quick_fixes::{self, Batch, Choice, Round};
+use crate::analyzer::runnables::{self, Runnable};
+use crate::analyzer::rustdoc;
+use crate::analyzer::semantic::{self, Legend, SemanticToken};
+use crate::analyzer::signature;
+use crate::analyzer::sources;
+use crate::analyzer::syntax;
+use crate::analyzer::unsafe_audit;
+use crate::analyzer::unused;
 
 fn get_rust_analyzer_path() -> String {
     std::env::var("RUST_ANALYZER_PATH").unwrap_or_else(|_| {
@@ -14,10 +42,34 @@ fn get_rust_analyzer_path() -> String {
     })
 }
 
+/// Upper bound on `cargo check` runs for one feature combination check.
+const MAX_FEATURE_COMBINATIONS: usize = 256;
+
+// How long to wait for rust-analyzer to finish loading the workspace
+// before answering requests against a partially indexed project.
+const READY_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Diagnostics are published some time after indexing finishes; consider
+// them complete once rust-analyzer has been silent this long.
+const DIAGNOSTICS_QUIET: Duration = Duration::from_secs(1);
+
 pub struct RustAnalyzerClient {
     process: Option<Child>,
+    messages: Option<mpsc::UnboundedReceiver<Value>>,
     request_id: u64,
     initialized: bool,
+    quiescent: bool,
+    /// Version and last text sent for each document opened with the server.
+    open_documents: HashMap<PathBuf, (i32, String)>,
+    diagnostics: HashMap<PathBuf, Vec<Value>>,
+    /// Runnables from the last `list_runnables` call, indexed by id.
+    runnables: Vec<Runnable>,
+    /// Actions from the last `list_code_actions` call, indexed by id.
+    code_actions: Vec<CodeAction>,
+    /// Semantic token legend announced by the server in `initialize`.
+    semantic_legend: Option<Legend>,
+    /// Result of the last `workspace_diagnostics` call, to diff against.
+    diagnostics_snapshot: Option<Vec<Diagnostic>>,
 }
 
 impl Default for RustAnalyzerClient {
@@ -30,20 +82,37 @@ impl RustAnalyzerClient {
     pub fn new() -> Self {
         Self {
             process: None,
+            messages: None,
             request_id: 0,
             initialized: false,
+            quiescent: false,
+            open_documents: HashMap::new(),
+            diagnostics: HashMap::new(),
+            runnables: Vec::new(),
+            code_actions: Vec::new(),
+            semantic_legend: None,
+            diagnostics_snapshot: None,
         }
     }
 
     pub async fn start(&mut self) -> Result<()> {
         let rust_analyzer_path = get_rust_analyzer_path();
-        let child = tokio::process::Command::new(&rust_analyzer_path)
+        let mut child = tokio::process::Command::new(&rust_analyzer_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
+            // rust-analyzer logs to stderr; an undrained pipe would block it
+            .stderr(Stdio::null())
             .spawn()?;
 
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("rust-analyzer stdout unavailable"))?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(read_messages(stdout, sender));
+
         self.process = Some(child);
+        self.messages = Some(receiver);
         self.initialize().await?;
         Ok(())
     }
@@ -61,6 +130,14 @@ impl RustAnalyzerClient {
                 "version": "0.1.0"
             },
             "rootUri": root_uri,
+            "initializationOptions": {
+                "inlayHints": {
+                    "lifetimeElisionHints": {
+                        "enable": "skip_trivial",
+                        "useParameterNames": true
+                    }
+                }
+            },
             "capabilities": {
                 "textDocument": {
                     "definition": {
@@ -69,21 +146,88 @@ impl RustAnalyzerClient {
                     "references": {
                         "dynamicRegistration": false
                     },
+                    "implementation": {
+                        "linkSupport": true
+                    },
+                    "typeDefinition": {
+                        "linkSupport": true
+                    },
+                    "declaration": {
+                        "linkSupport": true
+                    },
+                    "documentSymbol": {
+                        "hierarchicalDocumentSymbolSupport": true
+                    },
                     "publishDiagnostics": {
                         "relatedInformation": true
+                    },
+                    "completion": {
+                        "completionItem": {
+                            "documentationFormat": ["plaintext", "markdown"],
+                            "labelDetailsSupport": true
+                        }
+                    },
+                    "semanticTokens": {
+                        "requests": {
+                            "range": true,
+                            "full": true
+                        },
+                        "tokenTypes": [],
+                        "tokenModifiers": [],
+                        "formats": ["relative"]
+                    },
+                    "inlayHint": {
+                        "dynamicRegistration": false
+                    },
+                    "signatureHelp": {
+                        "signatureInformation": {
+                            "documentationFormat": ["plaintext", "markdown"],
+                            "parameterInformation": {
+                                "labelOffsetSupport": true
+                            },
+                            "activeParameterSupport": true
+                        }
+                    },
+                    "codeAction": {
+                        "codeActionLiteralSupport": {
+                            "codeActionKind": {
+                                "valueSet": [
+                                    "",
+                                    "quickfix",
+                                    "refactor",
+                                    "refactor.extract",
+                                    "refactor.inline",
+                                    "refactor.rewrite",
+                                    "source",
+                                    "source.organizeImports"
+                                ]
+                            }
+                        },
+                        "resolveSupport": {
+                            "properties": ["edit"]
+                        },
+                        "dataSupport": true
                     }
                 },
                 "workspace": {
                     "symbol": {
                         "dynamicRegistration": false
+                    },
+                    "workspaceEdit": {
+                        "documentChanges": true,
+                        "resourceOperations": ["create", "rename", "delete"]
                     }
+                },
+                "experimental": {
+                    "serverStatusNotification": true
                 }
             }
         });
 
-        let _response = self
+        let response = self
             .send_request_internal("initialize", init_params)
             .await?;
+        self.semantic_legend = Legend::from_capabilities(&response["result"]["capabilities"]);
 
         // Send initialized notification
         self.send_notification("initialized", json!({})).await?;
@@ -111,166 +255,2392 @@ impl RustAnalyzerClient {
             "params": params
         });
 
-        self.send_message(&request).await?;
-        self.read_response(self.request_id).await
+        self.send_message(&request).await?;
+        self.read_response(self.request_id).await
+    }
+
+    /// Send a request and return its `result`, turning LSP errors into `Err`.
+    async fn send_request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let response = self.send_request_internal(method, params).await?;
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown error");
+            return Err(anyhow::anyhow!("{method} failed: {message}"));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn send_message(&mut self, message: &Value) -> Result<()> {
+        let content = message.to_string();
+        let header = format!("Content-Length: {}\r\n\r\n", content.len());
+
+        if let Some(child) = &mut self.process
+            && let Some(stdin) = child.stdin.as_mut()
+        {
+            stdin.write_all(header.as_bytes()).await?;
+            stdin.write_all(content.as_bytes()).await?;
+            stdin.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn read_response(&mut self, expected_id: u64) -> Result<Value> {
+        loop {
+            let message = self.next_message().await?;
+            let is_response = message.get("method").is_none();
+            if is_response
+                && let Some(id) = message.get("id")
+                && id.as_u64() == Some(expected_id)
+            {
+                return Ok(message);
+            }
+            self.handle_server_message(message).await?;
+        }
+    }
+
+    async fn next_message(&mut self) -> Result<Value> {
+        let receiver = self
+            .messages
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Failed to read response"))?;
+        receiver
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("rust-analyzer closed its output stream"))
+    }
+
+    /// Process a notification or request initiated by rust-analyzer.
+    async fn handle_server_message(&mut self, message: Value) -> Result<()> {
+        let Some(method) = message.get("method").and_then(|v| v.as_str()) else {
+            // A response to a request nobody is waiting for any more.
+            return Ok(());
+        };
+
+        if let Some(id) = message.get("id") {
+            // We do not advertise any server-to-client requests we act on,
+            // so acknowledge them to keep rust-analyzer from waiting.
+            let reply = json!({ "jsonrpc": "2.0", "id": id, "result": null });
+            return self.send_message(&reply).await;
+        }
+
+        let params = &message["params"];
+        match method {
+            "textDocument/publishDiagnostics" => {
+                let uri = params["uri"].as_str().unwrap_or_default();
+                let diagnostics = params["diagnostics"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default();
+                self.diagnostics.insert(edit::uri_to_path(uri), diagnostics);
+            }
+            "experimental/serverStatus" => {
+                self.quiescent = params["quiescent"].as_bool().unwrap_or(false);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Wait until rust-analyzer reports that it has finished loading and
+    /// indexing the workspace, so that cross-file queries are complete.
+    async fn wait_until_ready(&mut self) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+        while !self.quiescent {
+            let Some(receiver) = self.messages.as_mut() else {
+                break;
+            };
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(message)) => self.handle_server_message(message).await?,
+                Ok(None) => {
+                    return Err(anyhow::anyhow!("rust-analyzer closed its output stream"));
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Process messages until rust-analyzer has been silent for
+    /// `DIAGNOSTICS_QUIET`, so diagnostics published after indexing are in.
+    async fn settle_diagnostics(&mut self) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+        while tokio::time::Instant::now() < deadline {
+            let Some(receiver) = self.messages.as_mut() else {
+                break;
+            };
+            match tokio::time::timeout(DIAGNOSTICS_QUIET, receiver.recv()).await {
+                Ok(Some(message)) => self.handle_server_message(message).await?,
+                Ok(None) => {
+                    return Err(anyhow::anyhow!("rust-analyzer closed its output stream"));
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Make sure rust-analyzer has `path` open with its current contents,
+    /// resending the file if it changed on disk since it was last sent.
+    async fn open_document(&mut self, path: &Path) -> Result<()> {
+        let text = std::fs::read_to_string(path)?;
+        if let Some((_, sent)) = self.open_documents.get(path) {
+            if *sent != text {
+                self.send_document_text(path, &text).await?;
+            }
+            return Ok(());
+        }
+        self.send_notification(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": edit::path_to_uri(path),
+                    "languageId": "rust",
+                    "version": 1,
+                    "text": text
+                }
+            }),
+        )
+        .await?;
+        self.open_documents
+            .insert(path.to_path_buf(), (1, text.clone()));
+        Ok(())
+    }
+
+    /// Replace the in-memory contents of an open document without touching
+    /// the file on disk.
+    async fn set_document_text(&mut self, path: &Path, text: &str) -> Result<()> {
+        if !self.open_documents.contains_key(path) {
+            self.open_document(path).await?;
+        }
+        self.send_document_text(path, text).await
+    }
+
+    /// Send the full text of an open document as a new version.
+    async fn send_document_text(&mut self, path: &Path, text: &str) -> Result<()> {
+        let Some((version, sent)) = self.open_documents.get_mut(path) else {
+            return Err(anyhow::anyhow!("{} is not open", path.display()));
+        };
+        *version += 1;
+        let version = *version;
+        *sent = text.to_string();
+        self.send_notification(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": edit::path_to_uri(path), "version": version },
+                "contentChanges": [{ "text": text }]
+            }),
+        )
+        .await
+    }
+
+    /// Send the given contents again for documents whose in-memory text was
+    /// changed from them.
+    async fn restore_documents(&mut self, originals: &BTreeMap<PathBuf, String>) -> Result<()> {
+        for (path, original) in originals {
+            let changed = self
+                .open_documents
+                .get(path)
+                .is_some_and(|(_, sent)| sent != original);
+            if changed {
+                self.send_document_text(path, original).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Close the documents opened since `already_open` was taken, so calls
+    /// that open every workspace file don't leave them all open.
+    async fn close_documents(&mut self, already_open: &BTreeSet<PathBuf>) -> Result<()> {
+        let opened: Vec<PathBuf> = self
+            .open_documents
+            .keys()
+            .filter(|path| !already_open.contains(*path))
+            .cloned()
+            .collect();
+        for path in opened {
+            self.open_documents.remove(&path);
+            self.send_notification(
+                "textDocument/didClose",
+                json!({ "textDocument": { "uri": edit::path_to_uri(&path) } }),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Tell rust-analyzer about files we changed on disk.
+    async fn sync_changed_files(&mut self, changes: &[FileChange]) -> Result<()> {
+        let mut watched = Vec::new();
+        for change in changes {
+            let uri = edit::path_to_uri(&change.path);
+            let kind = match (&change.old_text, &change.new_text) {
+                (None, Some(_)) => 1,
+                (Some(_), None) => 3,
+                _ => 2,
+            };
+            watched.push(json!({ "uri": uri, "type": kind }));
+
+            if self.open_documents.contains_key(&change.path) {
+                match &change.new_text {
+                    Some(text) => self.send_document_text(&change.path, text).await?,
+                    None => {
+                        self.open_documents.remove(&change.path);
+                        self.send_notification(
+                            "textDocument/didClose",
+                            json!({ "textDocument": { "uri": uri } }),
+                        )
+                        .await?;
+                    }
+                }
+            }
+        }
+
+        if !watched.is_empty() {
+            self.quiescent = false;
+            self.send_notification(
+                "workspace/didChangeWatchedFiles",
+                json!({ "changes": watched }),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Write file changes to disk and keep rust-analyzer in sync.
+    async fn apply_file_changes(&mut self, changes: &[FileChange]) -> Result<()> {
+        edit::write_file_changes(changes)?;
+        self.sync_changed_files(changes).await
+    }
+
+    async fn document_symbols(&mut self, path: &Path) -> Result<Vec<Value>> {
+        self.open_document(path).await?;
+        let params = create_text_document_params(&path.to_string_lossy());
+        let result = self
+            .send_request("textDocument/documentSymbol", params)
+            .await?;
+        Ok(result.as_array().cloned().unwrap_or_default())
+    }
+
+    async fn reference_locations(&mut self, path: &Path, position: &Value) -> Result<Vec<Value>> {
+        let line = position["line"].as_u64().unwrap_or(0) as u32;
+        let character = position["character"].as_u64().unwrap_or(0) as u32;
+        let params = create_references_params(&path.to_string_lossy(), line, character);
+        let result = self.send_request("textDocument/references", params).await?;
+        Ok(result.as_array().cloned().unwrap_or_default())
+    }
+
+    /// Run a go-to request at a position and render the targets with source
+    /// snippets.
+    async fn goto_locations(
+        &mut self,
+        method: &str,
+        title: &str,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let path = edit::absolute_path(file_path)?;
+        self.wait_until_ready().await?;
+        self.open_document(&path).await?;
+        let params = create_text_document_position_params(&path.to_string_lossy(), line, character);
+        let result = self.send_request(method, params).await?;
+        Ok(sources::format_locations(
+            title,
+            &sources::locations_from_lsp(&result),
+        ))
+    }
+
+    // Tool implementation methods
+    pub async fn find_definition(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let params = create_text_document_position_params(file_path, line, character);
+        let response = self
+            .send_request_internal("textDocument/definition", params)
+            .await?;
+
+        Ok(format!("Definition response: {response}"))
+    }
+
+    pub async fn find_references(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let params = create_references_params(file_path, line, character);
+        let response = self
+            .send_request_internal("textDocument/references", params)
+            .await?;
+
+        Ok(format!("References response: {response}"))
+    }
+
+    /// Impls of the trait, or implementations of the trait method, at a
+    /// position.
+    pub async fn find_implementations(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<String> {
+        self.goto_locations(
+            "textDocument/implementation",
+            "implementations",
+            file_path,
+            line,
+            character,
+        )
+        .await
+    }
+
+    /// Definition of the type of the expression or binding at a position.
+    pub async fn find_type_definition(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<String> {
+        self.goto_locations(
+            "textDocument/typeDefinition",
+            "type definitions",
+            file_path,
+            line,
+            character,
+        )
+        .await
+    }
+
+    /// Declaration of the symbol at a position, e.g. the trait method an
+    /// impl method implements or the `mod` item of a module file.
+    pub async fn find_declaration(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<String> {
+        self.goto_locations(
+            "textDocument/declaration",
+            "declarations",
+            file_path,
+            line,
+            character,
+        )
+        .await
+    }
+
+    pub async fn get_diagnostics(&mut self, file_path: &str) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let path = edit::absolute_path(file_path)?;
+        self.open_document(&path).await?;
+        self.wait_until_ready().await?;
+        self.settle_diagnostics().await?;
+        let found = diagnostics::normalize(
+            self.diagnostics
+                .get(&path)
+                .into_iter()
+                .flatten()
+                .map(|diagnostic| Diagnostic::from_lsp(&path, diagnostic))
+                .collect(),
+        );
+        if found.is_empty() {
+            return Ok(format!("No diagnostics in {}", path.display()));
+        }
+        let root = path.parent().unwrap_or(&path);
+        let mut result = format!("{} diagnostics in {}:\n", found.len(), path.display());
+        for diagnostic in &found {
+            result.push_str(&format!("  {}\n", diagnostic.render(root, false)));
+        }
+        Ok(result)
+    }
+
+    /// Import the names rust-analyzer reports as unresolved in a file. The
+    /// candidates of its `auto_import` fix are ranked by `prefer` (crate
+    /// names or path prefixes, most preferred first) followed by the
+    /// defaults; ties are reported back instead of guessed.
+    pub async fn add_missing_imports(
+        &mut self,
+        file_path: &str,
+        prefer: &[String],
+        dry_run: bool,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let path = edit::absolute_path(file_path)?;
+        let text = std::fs::read_to_string(&path)?;
+        self.open_document(&path).await?;
+        self.wait_until_ready().await?;
+        self.settle_diagnostics().await?;
+        let workspace = match syntax::find_manifest_dir(&path) {
+            Some(dir) => dir,
+            None => std::env::current_dir()?,
+        };
+        let mut preferences = prefer.to_vec();
+        preferences.extend(imports::default_preferences(&workspace, &path));
+
+        let mut pending = Vec::new();
+        let mut seen = BTreeSet::new();
+        let published = self.diagnostics.get(&path).cloned().unwrap_or_default();
+        for diagnostic in published.iter().filter(|d| imports::is_unresolved(d)) {
+            let range = edit::lsp_range_to_offsets(&text, &diagnostic["range"]);
+            let name = text[range.clone()].to_string();
+            if name.is_empty() || !seen.insert(name.clone()) {
+                continue;
+            }
+            pending.push(Pending {
+                name,
+                range,
+                diagnostic: diagnostic.clone(),
+                import: None,
+            });
+        }
+
+        let originals = BTreeMap::from([(path.clone(), text.clone())]);
+        let rounds = self
+            .import_rounds(&path, &text, pending, &preferences)
+            .await;
+        // Rounds only edit rust-analyzer's copy; put the disk contents back
+        // whether they succeeded or not.
+        let restored = self.restore_documents(&originals).await;
+        let (new_text, outcome) = rounds?;
+        restored?;
+
+        let diff = if new_text != text {
+            let change = FileChange {
+                path: path.clone(),
+                old_text: Some(text),
+                new_text: Some(new_text),
+            };
+            if !dry_run {
+                self.apply_file_changes(std::slice::from_ref(&change))
+                    .await?;
+            }
+            Some(edit::unified_diff(&change))
+        } else {
+            None
+        };
+        Ok(outcome.format(&path, diff, dry_run))
+    }
+
+    /// Apply the chosen `auto_import` quick fix for each of `pending` to
+    /// rust-analyzer's copy of `path`, in rounds of non-overlapping edits.
+    /// Returns the imported text and the outcome per name.
+    async fn import_rounds(
+        &mut self,
+        path: &Path,
+        text: &str,
+        mut pending: Vec<Pending>,
+        preferences: &[String],
+    ) -> Result<(String, imports::Outcome)> {
+        let mut outcome = imports::Outcome::default();
+        let mut text = text.to_string();
+        while !pending.is_empty() {
+            let mut batch = Batch::default();
+            let mut deferred = Vec::new();
+            for mut unresolved in pending {
+                let mut diagnostic = unresolved.diagnostic.clone();
+                diagnostic["range"] = edit::offsets_to_lsp_range(&text, &unresolved.range);
+                let params = create_code_action_params(
+                    &path.to_string_lossy(),
+                    diagnostic["range"].clone(),
+                    vec![diagnostic],
+                    Some("quickfix"),
+                );
+                let actions = self.send_request("textDocument/codeAction", params).await?;
+                let import = match unresolved.import.take() {
+                    Some(import) => import,
+                    None => match imports::resolve(imports::candidates(&actions), preferences) {
+                        Resolution::Import(import) => import,
+                        Resolution::Ambiguous(candidates) => {
+                            outcome.ambiguous.push((unresolved.name, candidates));
+                            continue;
+                        }
+                        Resolution::Unavailable => {
+                            let line =
+                                edit::offset_to_position(&text, unresolved.range.start)["line"]
+                                    .as_u64()
+                                    .unwrap_or(0)
+                                    + 1;
+                            outcome.unavailable.push((unresolved.name, line));
+                            continue;
+                        }
+                    },
+                };
+
+                let title = format!("Import `{import}`");
+                let Some(action) = actions
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(CodeAction::from_lsp)
+                    .find(|action| action.title == title)
+                else {
+                    outcome
+                        .not_applied
+                        .push((unresolved.name, format!("`{import}` is no longer offered")));
+                    continue;
+                };
+                let resolved = if action.needs_resolve() {
+                    self.send_request("codeAction/resolve", action.action.clone())
+                        .await?
+                } else {
+                    action.action
+                };
+                let Some(lsp_edits) = resolved
+                    .get("edit")
+                    .and_then(edit::workspace_text_edits)
+                    .filter(|edits| edits.keys().all(|file| file == path))
+                    .and_then(|mut edits| edits.remove(path))
+                else {
+                    outcome.not_applied.push((
+                        unresolved.name,
+                        format!("importing `{import}` changes other files"),
+                    ));
+                    continue;
+                };
+
+                let fix =
+                    BTreeMap::from([(path.to_path_buf(), edit::lsp_text_edits(&text, &lsp_edits))]);
+                if quick_fixes::is_empty_fix(&fix) {
+                    outcome.not_applied.push((
+                        unresolved.name,
+                        format!("importing `{import}` produced no edits"),
+                    ));
+                    continue;
+                }
+                if batch.try_add(fix) {
+                    outcome.imported.push((unresolved.name, import));
+                } else {
+                    unresolved.import = Some(import);
+                    deferred.push(unresolved);
+                }
+            }
+
+            // Conflicting imports, e.g. two into the same `use` group, are
+            // retried against the text with this round's imports.
+            for unresolved in &mut deferred {
+                let start = batch.map_offset(path, unresolved.range.start);
+                unresolved.range = start..start + unresolved.name.len();
+            }
+            let texts = BTreeMap::from([(path.to_path_buf(), text.clone())]);
+            if let Some(updated) = batch.apply(&texts)?.remove(path) {
+                self.set_document_text(path, &updated).await?;
+                text = updated;
+            }
+            pending = deferred;
+        }
+        Ok((text, outcome))
+    }
+
+    /// One pass of `fix_all_diagnostics`: the quick fix for every current
+    /// diagnostic in `files`, batched if it doesn't conflict. `texts` holds
+    /// the in-memory contents the fixes are computed against.
+    async fn collect_quick_fixes(
+        &mut self,
+        files: &[PathBuf],
+        texts: &mut BTreeMap<PathBuf, String>,
+        originals: &mut BTreeMap<PathBuf, String>,
+    ) -> Result<Round> {
+        let mut round = Round::default();
+        for path in files {
+            let published = self.diagnostics.get(path).cloned().unwrap_or_default();
+            let modified = texts.get(path) != originals.get(path);
+            for diagnostic in published {
+                let summary = Diagnostic::from_lsp(path, &diagnostic);
+                // `cargo check` results describe the file on disk, not the
+                // edited document.
+                if modified && diagnostic["source"].as_str() != Some("rust-analyzer") {
+                    round.remaining.push((
+                        summary,
+                        "reported for the unfixed file; re-check after writing".to_string(),
+                    ));
+                    continue;
+                }
+                let params = create_code_action_params(
+                    &path.to_string_lossy(),
+                    diagnostic["range"].clone(),
+                    vec![diagnostic.clone()],
+                    Some("quickfix"),
+                );
+                let actions: Vec<CodeAction> = self
+                    .send_request("textDocument/codeAction", params)
+                    .await?
+                    .as_array()
+                    .map(|actions| actions.iter().filter_map(CodeAction::from_lsp).collect())
+                    .unwrap_or_default();
+
+                let action = match quick_fixes::choose(actions) {
+                    Choice::Apply(action) => action,
+                    Choice::Ambiguous(titles) => {
+                        round
+                            .remaining
+                            .push((summary, format!("ambiguous: {}", titles.join(" | "))));
+                        continue;
+                    }
+                    Choice::Unavailable => {
+                        round.remaining.push((summary, "no quick fix".to_string()));
+                        continue;
+                    }
+                };
+                let resolved = if action.needs_resolve() {
+                    self.send_request("codeAction/resolve", action.action.clone())
+                        .await?
+                } else {
+                    action.action.clone()
+                };
+                let Some(file_edits) = resolved.get("edit").and_then(edit::workspace_text_edits)
+                else {
+                    round.remaining.push((
+                        summary,
+                        format!("'{}' changes files; use apply_code_action", action.title),
+                    ));
+                    continue;
+                };
+
+                let mut fix = BTreeMap::new();
+                for (file, lsp_edits) in file_edits {
+                    if !texts.contains_key(&file) {
+                        let text = std::fs::read_to_string(&file)?;
+                        originals.insert(file.clone(), text.clone());
+                        texts.insert(file.clone(), text);
+                    }
+                    let edits = edit::lsp_text_edits(&texts[&file], &lsp_edits);
+                    fix.insert(file, edits);
+                }
+                if quick_fixes::is_empty_fix(&fix) {
+                    round
+                        .remaining
+                        .push((summary, format!("'{}' produced no edits", action.title)));
+                    continue;
+                }
+                if round.batch.try_add(fix) {
+                    round.fixed.push((summary, action.title));
+                } else {
+                    round.remaining.push((
+                        summary,
+                        format!("'{}' conflicts with another fix", action.title),
+                    ));
+                }
+            }
+        }
+        Ok(round)
+    }
+
+    /// Apply unambiguous quick fixes to the diagnostics of a file, or of the
+    /// whole workspace, round after round until none apply or `max_rounds`
+    /// is reached, and report what remains. Rounds run on rust-analyzer's
+    /// in-memory documents; files are written at the end unless `dry_run`.
+    pub async fn fix_all_diagnostics(
+        &mut self,
+        file_path: Option<&str>,
+        workspace_path: Option<&str>,
+        max_rounds: usize,
+        dry_run: bool,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+        let (root, files) = match file_path {
+            Some(file_path) => {
+                let path = edit::absolute_path(file_path)?;
+                let root = syntax::find_manifest_dir(&path)
+                    .unwrap_or_else(|| path.parent().unwrap_or(&path).to_path_buf());
+                (root, vec![path])
+            }
+            None => {
+                let workspace = match workspace_path {
+                    Some(path) => edit::absolute_path(path)?,
+                    None => std::env::current_dir()?,
+                };
+                let files = crate_index::workspace_rust_files(&workspace);
+                (workspace, files)
+            }
+        };
+
+        let already_open: BTreeSet<PathBuf> = self.open_documents.keys().cloned().collect();
+        let mut originals = BTreeMap::new();
+        for path in &files {
+            originals.insert(path.clone(), std::fs::read_to_string(path)?);
+            self.open_document(path).await?;
+        }
+        let mut texts = originals.clone();
+        let rounds = self
+            .fix_rounds(&files, &mut texts, &mut originals, max_rounds)
+            .await;
+        // Rounds only edit rust-analyzer's copies; put the disk contents
+        // back whether they succeeded or not.
+        let restored = self.restore_documents(&originals).await;
+        let closed = self.close_documents(&already_open).await;
+        let (applied, remaining) = rounds?;
+        restored?;
+        closed?;
+
+        let changes: Vec<FileChange> = texts
+            .into_iter()
+            .filter(|(path, text)| originals.get(path) != Some(text))
+            .map(|(path, text)| FileChange {
+                old_text: originals.get(&path).cloned(),
+                new_text: Some(text),
+                path,
+            })
+            .collect();
+        if !dry_run {
+            self.apply_file_changes(&changes).await?;
+        }
+        Ok(quick_fixes::format_report(
+            &root, &applied, &remaining, &changes, dry_run,
+        ))
+    }
+
+    /// The rounds of `fix_all_diagnostics`, leaving the fixed contents in
+    /// `texts`. Returns the applied fixes with their round and what remains.
+    async fn fix_rounds(
+        &mut self,
+        files: &[PathBuf],
+        texts: &mut BTreeMap<PathBuf, String>,
+        originals: &mut BTreeMap<PathBuf, String>,
+        max_rounds: usize,
+    ) -> Result<(Vec<(usize, Diagnostic, String)>, Vec<(Diagnostic, String)>)> {
+        let mut applied = Vec::new();
+        let mut number = 0;
+        let remaining = loop {
+            self.wait_until_ready().await?;
+            self.settle_diagnostics().await?;
+            let mut round = self.collect_quick_fixes(files, texts, originals).await?;
+            if round.batch.is_empty() || number == max_rounds {
+                round
+                    .remaining
+                    .extend(round.fixed.into_iter().map(|(diagnostic, title)| {
+                        (
+                            diagnostic,
+                            format!("'{title}' not applied: round limit reached"),
+                        )
+                    }));
+                break round.remaining;
+            }
+            number += 1;
+            for (path, text) in round.batch.apply(texts)? {
+                self.set_document_text(&path, &text).await?;
+                texts.insert(path, text);
+            }
+            applied.extend(
+                round
+                    .fixed
+                    .into_iter()
+                    .map(|(diagnostic, title)| (number, diagnostic, title)),
+            );
+        };
+        Ok((applied, remaining))
+    }
+
+    /// Run `add_missing_match_arms` on the match whose keyword is at
+    /// `keyword` in the in-memory `text` of `path`, returning the edited
+    /// text, or None if rust-analyzer doesn't offer the assist there.
+    async fn missing_arms_text(
+        &mut self,
+        path: &Path,
+        text: &str,
+        keyword: usize,
+    ) -> Result<Option<String>> {
+        self.set_document_text(path, text).await?;
+        let position = edit::offset_to_position(text, keyword);
+        let params = create_code_action_params(
+            &path.to_string_lossy(),
+            json!({ "start": position, "end": position }),
+            Vec::new(),
+            None,
+        );
+        let Some(action) = self
+            .send_request("textDocument/codeAction", params)
+            .await?
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(CodeAction::from_lsp)
+            .find(|action| {
+                action.title == "Fill match arms"
+                    || action.action["data"]["id"]
+                        .as_str()
+                        .is_some_and(|id| id.starts_with("add_missing_match_arms"))
+            })
+        else {
+            return Ok(None);
+        };
+        let resolved = if action.needs_resolve() {
+            self.send_request("codeAction/resolve", action.action.clone())
+                .await?
+        } else {
+            action.action
+        };
+        let Some(lsp_edits) = resolved
+            .get("edit")
+            .and_then(edit::workspace_text_edits)
+            .and_then(|mut edits| edits.remove(path))
+        else {
+            return Ok(None);
+        };
+        let edits = edit::lsp_text_edits(text, &lsp_edits);
+        Ok(Some(edit::apply_text_edits(text, &edits)?))
+    }
+
+    /// Add the missing arms of the match whose keyword is at `keyword`.
+    /// Returns the new text and the added patterns.
+    async fn fill_match(
+        &mut self,
+        path: &Path,
+        text: &str,
+        keyword: usize,
+        options: &FillOptions,
+    ) -> Result<Option<(String, Vec<String>)>> {
+        let Some(expr) = match_arms::match_at(text, keyword) else {
+            return Ok(None);
+        };
+        let before = match_arms::arms(text, &expr);
+        let Some(mut filled) = self.missing_arms_text(path, text, keyword).await? else {
+            return Ok(None);
+        };
+        let Some(expr) = match_arms::match_at(&filled, keyword) else {
+            return Ok(None);
+        };
+        let added = match_arms::added_arms(&before, match_arms::arms(&filled, &expr));
+
+        // Arms are rewritten from the last one up so earlier offsets hold.
+        let mut patterns = Vec::new();
+        for arm in added.iter().rev() {
+            let nested = options
+                .expand_nested
+                .then(|| NestedFields::parse(&arm.pattern))
+                .flatten();
+            let mut expanded = None;
+            if let Some(nested) = nested {
+                let probe = format!("{} => match {} {{}},", nested.pattern, nested.scrutinee);
+                let inner_keyword = arm.range.start + nested.pattern.len() + " => ".len();
+                let mut probe_text = filled.clone();
+                probe_text.replace_range(arm.range.clone(), &probe);
+                if let Some(inner_text) = self
+                    .missing_arms_text(path, &probe_text, inner_keyword)
+                    .await?
+                    && let Some(inner) = match_arms::match_at(&inner_text, inner_keyword)
+                {
+                    expanded = match_arms::arms(&inner_text, &inner)
+                        .iter()
+                        .map(|inner_arm| nested.substitute(&inner_arm.pattern))
+                        .collect::<Option<Vec<String>>>()
+                        .filter(|arms| !arms.is_empty());
+                }
+            }
+
+            match expanded {
+                Some(arms) => {
+                    let body = options
+                        .arm_body
+                        .as_deref()
+                        .unwrap_or(match_arms::DEFAULT_ARM_BODY);
+                    let rendered = match_arms::render_arms(&filled, arm.range.start, &arms, body);
+                    filled.replace_range(arm.range.clone(), &rendered);
+                    patterns.splice(0..0, arms);
+                }
+                None => {
+                    if let Some(body) = &options.arm_body {
+                        filled.replace_range(arm.body.clone(), body);
+                    }
+                    patterns.insert(0, arm.pattern.clone());
+                }
+            }
+        }
+        Ok(Some((filled, patterns)))
+    }
+
+    /// Fill the missing arms of the match around `position` in `file_path`
+    /// or, without a position, of every non-exhaustive match (E0004) in the
+    /// file or the workspace, optionally only those whose missing patterns
+    /// mention `enum_name`.
+    pub async fn fill_match_arms(
+        &mut self,
+        file_path: Option<&str>,
+        position: Option<(u32, u32)>,
+        enum_name: Option<&str>,
+        workspace_path: Option<&str>,
+        options: &FillOptions,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+        let workspace = match workspace_path {
+            Some(path) => edit::absolute_path(path)?,
+            None => std::env::current_dir()?,
+        };
+        let files = match file_path {
+            Some(file_path) => vec![edit::absolute_path(file_path)?],
+            None if position.is_some() => {
+                return Err(anyhow::anyhow!("A position needs a file_path"));
+            }
+            None => crate_index::workspace_rust_files(&workspace),
+        };
+
+        let already_open: BTreeSet<PathBuf> = self.open_documents.keys().cloned().collect();
+        let mut originals = BTreeMap::new();
+        for path in &files {
+            originals.insert(path.clone(), std::fs::read_to_string(path)?);
+            self.open_document(path).await?;
+        }
+        self.wait_until_ready().await?;
+
+        // Keyword offsets of the matches to fill in each file.
+        let mut targets: BTreeMap<PathBuf, BTreeSet<usize>> = BTreeMap::new();
+        if let Some((line, character)) = position {
+            let path = &files[0];
+            let text = &originals[path];
+            let offset =
+                edit::position_to_offset(text, &json!({ "line": line, "character": character }));
+            let expr = match_arms::match_at(text, offset).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No match expression at {}:{}:{}",
+                    path.display(),
+                    line + 1,
+                    character + 1
+                )
+            })?;
+            targets
+                .entry(path.clone())
+                .or_default()
+                .insert(expr.keyword);
+        } else {
+            self.settle_diagnostics().await?;
+            for path in &files {
+                let text = &originals[path];
+                for diagnostic in self.diagnostics.get(path).into_iter().flatten() {
+                    let message = diagnostic["message"].as_str().unwrap_or_default();
+                    if diagnostic["code"].as_str() != Some("E0004")
+                        || enum_name.is_some_and(|name| !syntax::contains_word(message, name))
+                    {
+                        continue;
+                    }
+                    let offset = edit::position_to_offset(text, &diagnostic["range"]["start"]);
+                    if let Some(expr) = match_arms::match_at(text, offset) {
+                        targets
+                            .entry(path.clone())
+                            .or_default()
+                            .insert(expr.keyword);
+                    }
+                }
+            }
+        }
+
+        let outcome = self.fill_targets(targets, &originals, options).await;
+        // Filling and probing edit rust-analyzer's copies; put the disk
+        // contents back whether it succeeded or not.
+        let restored = self.restore_documents(&originals).await;
+        let closed = self.close_documents(&already_open).await;
+        let (filled, not_offered, changes) = outcome?;
+        restored?;
+        closed?;
+
+        if !options.dry_run {
+            self.apply_file_changes(&changes).await?;
+        }
+        let root = match position {
+            Some(_) => syntax::find_manifest_dir(&files[0]).unwrap_or(workspace),
+            None => workspace,
+        };
+        Ok(match_arms::format_report(
+            &root,
+            &filled,
+            &not_offered,
+            &changes,
+            options.dry_run,
+        ))
+    }
+
+    /// Fill the matches at the keyword offsets in `targets`, starting from
+    /// `originals`. Returns the filled matches, those rust-analyzer offered
+    /// no arms for and the changed files.
+    async fn fill_targets(
+        &mut self,
+        targets: BTreeMap<PathBuf, BTreeSet<usize>>,
+        originals: &BTreeMap<PathBuf, String>,
+        options: &FillOptions,
+    ) -> Result<(Vec<Filled>, Vec<(PathBuf, u64)>, Vec<FileChange>)> {
+        let mut filled = Vec::new();
+        let mut not_offered = Vec::new();
+        let mut changes = Vec::new();
+        for (path, keywords) in targets {
+            let original = &originals[&path];
+            let mut text = original.clone();
+            let mut in_file = Vec::new();
+            // Later matches first: filling one only moves text after it.
+            for keyword in keywords.into_iter().rev() {
+                let line = edit::offset_to_position(original, keyword)["line"]
+                    .as_u64()
+                    .unwrap_or(0)
+                    + 1;
+                match self.fill_match(&path, &text, keyword, options).await? {
+                    Some((new_text, patterns)) => {
+                        text = new_text;
+                        in_file.push(Filled {
+                            path: path.clone(),
+                            line,
+                            patterns,
+                        });
+                    }
+                    None => not_offered.push((path.clone(), line)),
+                }
+            }
+            in_file.reverse();
+            filled.extend(in_file);
+            if text != *original {
+                changes.push(FileChange {
+                    path: path.clone(),
+                    old_text: Some(original.clone()),
+                    new_text: Some(text),
+                });
+            }
+        }
+        not_offered.sort();
+        Ok((filled, not_offered, changes))
+    }
+
+    pub async fn workspace_symbols(&mut self, query: &str) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let params = create_workspace_symbol_params(query);
+        let response = self
+            .send_request_internal("workspace/symbol", params)
+            .await?;
+
+        Ok(format!("Workspace symbols response: {response}"))
+    }
+
+    pub async fn rename_symbol(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+        new_name: &str,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let params = create_rename_params(file_path, line, character, new_name);
+        let response = self
+            .send_request_internal("textDocument/rename", params)
+            .await?;
+
+        Ok(format!("Rename response: {response}"))
+    }
+
+    pub async fn format_code(&mut self, file_path: &str) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let params = create_formatting_params(file_path);
+        let response = self
+            .send_request_internal("textDocument/formatting", params)
+            .await?;
+
+        Ok(format!("Formatting response: {response}"))
+    }
+
+    /// Hierarchical symbol tree of a file, as an indented text outline or
+    /// as JSON.
+    pub async fn document_outline(
+        &mut self,
+        file_path: &str,
+        max_depth: Option<usize>,
+        as_json: bool,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let path = edit::absolute_path(file_path)?;
+        self.wait_until_ready().await?;
+        let symbols = self.document_symbols(&path).await?;
+        if as_json {
+            return Ok(serde_json::to_string_pretty(&outline::outline_json(
+                &symbols,
+            ))?);
+        }
+        Ok(format!(
+            "{}\n{}",
+            path.display(),
+            outline::format_outline(&symbols, max_depth)
+        ))
+    }
+
+    /// Completion candidates at a position. A `trigger` (`.` or `::`) is
+    /// inserted there in an in-memory overlay first, so `value` + `.` lists
+    /// the methods and fields of `value` without editing the file.
+    pub async fn complete_at(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+        trigger: Option<&str>,
+        query: Option<&str>,
+        limit: usize,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+        if let Some(trigger) = trigger
+            && trigger != "."
+            && trigger != "::"
+        {
+            return Err(anyhow::anyhow!(
+                "Unsupported trigger '{trigger}', expected . or ::"
+            ));
+        }
+
+        let path = edit::absolute_path(file_path)?;
+        self.wait_until_ready().await?;
+        self.open_document(&path).await?;
+        let text = std::fs::read_to_string(&path)?;
+        let offset =
+            edit::position_to_offset(&text, &json!({ "line": line, "character": character }));
+        let (overlay, offset) = match trigger {
+            Some(trigger) => completion::insert_trigger(&text, offset, trigger),
+            None => (text.clone(), offset),
+        };
+        let query = query.unwrap_or_else(|| completion::typed_prefix(&overlay, offset));
+        let query = query.to_string();
+
+        if trigger.is_some() {
+            self.set_document_text(&path, &overlay).await?;
+        }
+        let position = edit::offset_to_position(&overlay, offset);
+        let params = create_completion_params(
+            &path.to_string_lossy(),
+            position["line"].as_u64().unwrap_or(0) as u32,
+            position["character"].as_u64().unwrap_or(0) as u32,
+            trigger.map(|trigger| &trigger[trigger.len() - 1..]),
+        );
+        let result = self.send_request("textDocument/completion", params).await;
+        if trigger.is_some() {
+            self.set_document_text(&path, &text).await?;
+        }
+        let result = result?;
+
+        let items = result
+            .get("items")
+            .unwrap_or(&result)
+            .as_array()
+            .map(|items| items.iter().filter_map(Completion::from_lsp).collect())
+            .unwrap_or_default();
+        let (items, total) = completion::rank(items, &query, limit);
+        Ok(completion::format_completions(&items, total, &query))
+    }
+
+    /// Signatures of the call surrounding a position, with the parameter
+    /// being filled in highlighted.
+    pub async fn signature_help(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let path = edit::absolute_path(file_path)?;
+        self.wait_until_ready().await?;
+        self.open_document(&path).await?;
+        let params = create_text_document_position_params(&path.to_string_lossy(), line, character);
+        let result = self
+            .send_request("textDocument/signatureHelp", params)
+            .await?;
+        Ok(signature::format_signature_help(&result))
+    }
+
+    /// Source of a file, or of lines `start_line..=end_line` (0-based), with
+    /// type, parameter name, chaining and lifetime elision hints inlined.
+    pub async fn inlay_hints(
+        &mut self,
+        file_path: &str,
+        start_line: Option<u32>,
+        end_line: Option<u32>,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let path = edit::absolute_path(file_path)?;
+        let text = std::fs::read_to_string(&path)?;
+        let line_count = text.lines().count() as u32;
+        let start = start_line.unwrap_or(0);
+        let end = end_line.unwrap_or(line_count.saturating_sub(1));
+        if start > end || start >= line_count {
+            return Err(anyhow::anyhow!(
+                "Invalid line range {start}..={end} for a file with {line_count} lines"
+            ));
+        }
+
+        self.wait_until_ready().await?;
+        self.open_document(&path).await?;
+        let mut params = create_text_document_params(&path.to_string_lossy());
+        params["range"] = json!({
+            "start": { "line": start, "character": 0 },
+            "end": { "line": end + 1, "character": 0 }
+        });
+        let result = self.send_request("textDocument/inlayHint", params).await?;
+        let hints = result.as_array().cloned().unwrap_or_default();
+
+        let annotated = inlay::annotate(&text, &hints, start as usize..end as usize + 1)?;
+        Ok(format!(
+            "{} ({} hints)\n{annotated}",
+            path.display(),
+            hints.len()
+        ))
+    }
+
+    /// Semantic classification of a file, or of lines `start..=end`
+    /// (0-based), optionally restricted to token types and modifiers (e.g.
+    /// modifier `unsafe` for unsafe operations, `mutable` for mutable
+    /// bindings).
+    pub async fn semantic_tokens(
+        &mut self,
+        file_path: &str,
+        lines: Option<(u32, u32)>,
+        token_types: &[String],
+        modifiers: &[String],
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        let path = edit::absolute_path(file_path)?;
+        let text = std::fs::read_to_string(&path)?;
+        let tokens = self.semantic_token_list(&path, &text, lines).await?;
+        let tokens = semantic::filter(tokens, token_types, modifiers);
+        Ok(semantic::format_tokens(&tokens))
+    }
+
+    async fn semantic_token_list(
+        &mut self,
+        path: &Path,
+        text: &str,
+        lines: Option<(u32, u32)>,
+    ) -> Result<Vec<SemanticToken>> {
+        let legend = self.semantic_legend.clone().ok_or_else(|| {
+            anyhow::anyhow!("rust-analyzer did not announce semantic token support")
+        })?;
+        self.wait_until_ready().await?;
+        self.open_document(path).await?;
+        let mut params = create_text_document_params(&path.to_string_lossy());
+        let method = match lines {
+            Some((start, end)) => {
+                params["range"] = json!({
+                    "start": { "line": start, "character": 0 },
+                    "end": { "line": end + 1, "character": 0 }
+                });
+                "textDocument/semanticTokens/range"
+            }
+            None => "textDocument/semanticTokens/full",
+        };
+        let result = self.send_request(method, params).await?;
+        let data: Vec<u64> = result["data"]
+            .as_array()
+            .map(|data| data.iter().filter_map(|v| v.as_u64()).collect())
+            .unwrap_or_default();
+
+        Ok(semantic::decode(&data, &legend, text))
+    }
+
+    /// Every diagnostic in the workspace at `workspace_path` (default: the
+    /// current directory) at or above `min_severity`: rust-analyzer's, with
+    /// all files opened for the call so native diagnostics are computed, and
+    /// with `run_check` those of a `cargo check` run. The result is compared
+    /// with the previous call's snapshot.
+    pub async fn workspace_diagnostics(
+        &mut self,
+        workspace_path: Option<&str>,
+        run_check: bool,
+        min_severity: Severity,
+    ) -> Result<String> {
+        if !self.initialized && !run_check {
+            return Err(anyhow::anyhow!(
+                "Client not initialized; pass check=true to use cargo check alone"
+            ));
+        }
+        let workspace = match workspace_path {
+            Some(path) => edit::absolute_path(path)?,
+            None => std::env::current_dir()?,
+        };
+
+        let mut collected = Vec::new();
+        let mut sources = Vec::new();
+        if self.initialized {
+            let already_open: BTreeSet<PathBuf> = self.open_documents.keys().cloned().collect();
+            for path in crate_index::workspace_rust_files(&workspace) {
+                self.open_document(&path).await?;
+            }
+            self.wait_until_ready().await?;
+            self.settle_diagnostics().await?;
+            for (path, published) in &self.diagnostics {
+                if path.starts_with(&workspace) {
+                    collected.extend(
+                        published
+                            .iter()
+                            .map(|diagnostic| Diagnostic::from_lsp(path, diagnostic)),
+                    );
+                }
+            }
+            self.close_documents(&already_open).await?;
+            sources.push("rust-analyzer");
+        }
+        if run_check {
+            collected.extend(diagnostics::cargo_check(&workspace).await?);
+            sources.push("cargo check");
+        }
+
+        let current = diagnostics::normalize(collected);
+        let shown: Vec<Diagnostic> = current
+            .iter()
+            .filter(|diagnostic| diagnostic.severity <= min_severity)
+            .cloned()
+            .collect();
+        let previous: Option<Vec<Diagnostic>> =
+            self.diagnostics_snapshot.as_ref().map(|snapshot| {
+                snapshot
+                    .iter()
+                    .filter(|diagnostic| diagnostic.severity <= min_severity)
+                    .cloned()
+                    .collect()
+            });
+        let result =
+            diagnostics::format_snapshot(&workspace, &shown, previous.as_deref(), &sources);
+        self.diagnostics_snapshot = Some(current);
+        Ok(result)
+    }
+
+    /// Workspace items nothing references, grouped by module (or file).
+    /// With `delete`, the items are removed and the diffs returned.
+    pub async fn find_unused(
+        &mut self,
+        workspace_path: Option<&str>,
+        include_public: bool,
+        group_by_file: bool,
+        delete: bool,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+        let workspace = match workspace_path {
+            Some(path) => edit::absolute_path(path)?,
+            None => std::env::current_dir()?,
+        };
+        self.wait_until_ready().await?;
+
+        let mut checked = 0;
+        let mut unused = Vec::new();
+        let mut unchecked = Vec::new();
+        for path in crate_index::workspace_rust_files(&workspace) {
+            let Ok(text) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let symbols = self.document_symbols(&path).await?;
+            for candidate in unused::candidates(&path, &text, &symbols, include_public) {
+                let Ok(references) = self.reference_locations(&path, &candidate.position).await
+                else {
+                    unchecked.push(candidate);
+                    continue;
+                };
+                checked += 1;
+                if !references
+                    .iter()
+                    .any(|reference| unused::is_use(&candidate, reference))
+                {
+                    unused.push(candidate);
+                }
+            }
+        }
+
+        let mut result =
+            unused::format_unused(&workspace, &unused, &unchecked, checked, group_by_file);
+        if delete && !unused.is_empty() {
+            let changes = unused::deletions(&unused)?;
+            self.apply_file_changes(&changes).await?;
+            result.push_str(&format!("\n\nDeleted from {} files:\n", changes.len()));
+            for change in &changes {
+                result.push_str(&format!("\n{}", edit::unified_diff(change)));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Inventory of unsafe code in the workspace at `workspace_path`
+    /// (default: the current directory). Unsafe operations inside each site
+    /// are listed when rust-analyzer is running; `include_dependencies` adds
+    /// a textual per-package count for downloaded dependencies.
+    pub async fn audit_unsafe(
+        &mut self,
+        workspace_path: Option<&str>,
+        include_dependencies: bool,
+    ) -> Result<String> {
+        let workspace = match workspace_path {
+            Some(path) => edit::absolute_path(path)?,
+            None => std::env::current_dir()?,
+        };
+
+        let mut files = Vec::new();
+        for path in crate_index::workspace_rust_files(&workspace) {
+            let Ok(text) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let mut sites = unsafe_audit::scan(&text);
+            if sites.is_empty() {
+                continue;
+            }
+            if self.initialized
+                && let Ok(tokens) = self.semantic_token_list(&path, &text, None).await
+            {
+                unsafe_audit::attach_operations(&text, &mut sites, &tokens);
+            }
+            files.push((path, sites));
+        }
+
+        let dependencies = if include_dependencies {
+            let packages = sources::library_packages(&workspace).await?;
+            Some(unsafe_audit::dependency_counts(&packages))
+        } else {
+            None
+        };
+        Ok(unsafe_audit::format_report(
+            &workspace,
+            &files,
+            dependencies.as_deref(),
+        ))
+    }
+
+    /// Recursively expand the macro call at a position. With `fallback`,
+    /// expand the whole module with rustc when rust-analyzer can't.
+    pub async fn expand_macro(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        character: u32,
+        fallback: bool,
+    ) -> Result<String> {
+        let path = edit::absolute_path(file_path)?;
+        if !self.initialized {
+            if fallback {
+                return expand::expand_file_module(&path).await;
+            }
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+
+        self.wait_until_ready().await?;
+        self.open_document(&path).await?;
+        let params = create_text_document_position_params(&path.to_string_lossy(), line, character);
+        let result = self
+            .send_request("rust-analyzer/expandMacro", params)
+            .await?;
+        if let Some(expansion) = result.get("expansion").and_then(|v| v.as_str()) {
+            let name = result
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("macro");
+            let formatted = expand::rustfmt(expansion)
+                .await
+                .unwrap_or_else(|| expansion.to_string());
+            return Ok(format!("Expansion of {name}:\n\n{formatted}"));
+        }
+
+        if fallback {
+            let expansion = expand::expand_file_module(&path).await?;
+            return Ok(format!(
+                "rust-analyzer found no macro call at {line}:{character}.\n{expansion}"
+            ));
+        }
+        Err(anyhow::anyhow!(
+            "No macro call at {}:{line}:{character}",
+            path.display()
+        ))
     }
 
-    async fn send_message(&mut self, message: &Value) -> Result<()> {
-        let content = message.to_string();
-        let header = format!("Content-Length: {}\r\n\r\n", content.len());
+    /// Apply a structural search and replace rule such as
+    /// `foo($a, $b) ==>> bar($b, $a)` to the given files and directories,
+    /// or to the whole workspace when `paths` is empty.
+    pub async fn structural_replace(
+        &mut self,
+        rule: &str,
+        paths: &[String],
+        dry_run: bool,
+    ) -> Result<String> {
+        if !self.initialized {
+            return Err(anyhow::anyhow!("Client not initialized"));
+        }
+        if !rule.contains("==>>") {
+            return Err(anyhow::anyhow!(
+                "Rule must have the form `pattern ==>> replacement`"
+            ));
+        }
 
-        if let Some(child) = &mut self.process {
-            if let Some(stdin) = child.stdin.as_mut() {
-                stdin.write_all(header.as_bytes()).await?;
-                stdin.write_all(content.as_bytes()).await?;
-                stdin.flush().await?;
+        let mut files = Vec::new();
+        for path in paths {
+            let path = edit::absolute_path(path)?;
+            if path.is_dir() {
+                let mut found = Vec::new();
+                crate_index::rust_files(&path, &path, &mut found);
+                files.extend(found);
+            } else if path.is_file() {
+                files.push(path);
+            } else {
+                return Err(anyhow::anyhow!("{} does not exist", path.display()));
             }
         }
+        files.sort();
+        files.dedup();
 
-        Ok(())
-    }
+        self.wait_until_ready().await?;
+        let mut changes = Vec::new();
+        if paths.is_empty() {
+            let context = workspace_context_file().await?;
+            self.open_document(&context).await?;
+            let params = create_ssr_params(&context.to_string_lossy(), rule, Vec::new());
+            let workspace_edit = self.send_request("experimental/ssr", params).await?;
+            changes = edit::preview_workspace_edit(&workspace_edit)?;
+        } else {
+            for file in &files {
+                self.open_document(file).await?;
+                let text = std::fs::read_to_string(file)?;
+                let whole_file = edit::offsets_to_lsp_range(&text, &(0..text.len()));
+                let params = create_ssr_params(&file.to_string_lossy(), rule, vec![whole_file]);
+                let workspace_edit = self.send_request("experimental/ssr", params).await?;
+                changes.extend(edit::preview_workspace_edit(&workspace_edit)?);
+            }
+        }
 
-    async fn read_response(&mut self, expected_id: u64) -> Result<Value> {
-        if let Some(child) = &mut self.process {
-            if let Some(stdout) = child.stdout.as_mut() {
-                let mut reader = BufReader::new(stdout);
-
-                loop {
-                    // Read headers
-                    let mut content_length: Option<usize> = None;
-                    loop {
-                        let mut line = String::new();
-                        reader.read_line(&mut line).await?;
-
-                        if line == "\r\n" {
-                            break;
-                        }
+        if changes.is_empty() {
+            return Ok(format!("No matches for `{rule}`"));
+        }
+        let mut result = if dry_run {
+            format!("Dry run: `{rule}` would change {} files\n\n", changes.len())
+        } else {
+            self.apply_file_changes(&changes).await?;
+            format!("Applied `{rule}` to {} files\n\n", changes.len())
+        };
+        for change in &changes {
+            result.push_str(&edit::unified_diff(change));
+        }
+        Ok(result)
+    }
 
-                        if let Some(stripped) = line.strip_prefix("Content-Length:") {
-                            let length_str = stripped.trim();
-                            content_length = Some(length_str.parse()?);
-                        }
+    /// Read source code outside the workspace: either an item path such as
+    /// `tokio::sync::Mutex::lock`, resolved in the crate versions the
+    /// workspace builds against, or a file (optionally a line range) in the
+    /// workspace, the cargo registry, git checkouts or rust-src.
+    pub async fn read_source(
+        &mut self,
+        item_path: Option<&str>,
+        file_path: Option<&str>,
+        lines: Option<(u32, u32)>,
+        context: usize,
+    ) -> Result<String> {
+        let (path, start, end, others) = match (item_path, file_path) {
+            (Some(item_path), None) => {
+                let workspace = std::env::current_dir()?;
+                let segments: Vec<String> = item_path
+                    .trim_start_matches("::")
+                    .split("::")
+                    .map(|segment| segment.trim().to_string())
+                    .collect();
+                let crate_name = match segments[0].as_str() {
+                    "crate" | "self" => syntax::package_name(&workspace)
+                        .ok_or_else(|| anyhow::anyhow!("No package in {}", workspace.display()))?,
+                    name => name.to_string(),
+                };
+                let root = sources::crate_root(&workspace, &crate_name).await?;
+                if segments.len() == 1 {
+                    let text = std::fs::read_to_string(&root)?;
+                    (root, 0, text.lines().count(), Vec::new())
+                } else {
+                    let mut matches = sources::find_item(&root, &segments[1..]);
+                    if matches.is_empty() {
+                        return Err(anyhow::anyhow!(
+                            "No definition of {item_path} found in {crate_name} ({})",
+                            root.display()
+                        ));
                     }
-
-                    if let Some(length) = content_length {
-                        let mut content = vec![0u8; length];
-                        reader.read_exact(&mut content).await?;
-
-                        let response: Value = serde_json::from_slice(&content)?;
-
-                        if let Some(id) = response.get("id") {
-                            if id.as_u64() == Some(expected_id) {
-                                return Ok(response);
-                            }
-                        }
+                    let best = matches.remove(0);
+                    (best.path, best.start_line, best.end_line, matches)
+                }
+            }
+            (None, Some(file_path)) => {
+                let path = sources::resolve_file(file_path).await?;
+                match lines {
+                    Some((start, end)) => (
+                        path,
+                        (start as usize).saturating_sub(context),
+                        end as usize + context,
+                        Vec::new(),
+                    ),
+                    None => {
+                        let text = std::fs::read_to_string(&path)?;
+                        (path, 0, text.lines().count(), Vec::new())
                     }
                 }
             }
+            _ => {
+                return Err(anyhow::anyhow!("Give either an item path or a file_path"));
+            }
+        };
+
+        let text = std::fs::read_to_string(&path)?;
+        let mut result = format!("{}:{}", path.display(), start + 1);
+        if let Some(label) = sources::SourceOrigin::of(&path).label() {
+            result.push_str(&format!(" [{label}]"));
+        }
+        result.push('\n');
+        result.push_str(&sources::snippet(
+            &text,
+            start,
+            end,
+            sources::MAX_SOURCE_LINES,
+        ));
+        if !others.is_empty() {
+            result.push_str(&format!("\n{} other candidates:\n", others.len()));
+            for other in others.iter().take(10) {
+                result.push_str(&format!(
+                    "  {}:{}\n",
+                    other.path.display(),
+                    other.start_line + 1
+                ));
+            }
         }
+        Ok(result)
+    }
+
+    /// Docs for an item path from rustdoc JSON of the exact package version
+    /// the workspace resolves, generated offline with nightly and cached.
+    pub async fn lookup_docs(
+        &mut self,
+        query: &str,
+        workspace_path: Option<&str>,
+    ) -> Result<String> {
+        let workspace = match workspace_path {
+            Some(path) => edit::absolute_path(path)?,
+            None => std::env::current_dir()?,
+        };
+        let segments: Vec<String> = query
+            .trim_start_matches("::")
+            .split("::")
+            .map(|segment| segment.trim().to_string())
+            .collect();
+        let crate_name = match segments[0].as_str() {
+            "crate" | "self" => syntax::package_name(&workspace)
+                .ok_or_else(|| anyhow::anyhow!("No package in {}", workspace.display()))?
+                .replace('-', "_"),
+            name => name.replace('-', "_"),
+        };
+        let toolchain = expand::nightly_toolchain(&workspace).await.ok_or_else(|| {
+            anyhow::anyhow!(
+                "rustdoc JSON needs a nightly toolchain (rustup toolchain install nightly)"
+            )
+        })?;
 
-        Err(anyhow::anyhow!("Failed to read response"))
+        let (header, docs) = if sources::SYSROOT_CRATES.contains(&crate_name.as_str()) {
+            let docs = rustdoc::sysroot_docs(&workspace, &toolchain, &crate_name).await?;
+            (crate_name.clone(), docs)
+        } else {
+            let packages = sources::library_packages(&workspace).await?;
+            let package = sources::find_library(packages, &crate_name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{crate_name} is not in the dependency graph of {}",
+                    workspace.display()
+                )
+            })?;
+            let docs = rustdoc::package_docs(&workspace, &toolchain, &package).await?;
+            (format!("{} {}", package.name, package.version), docs)
+        };
+        let result = rustdoc::lookup(&docs, &crate_name, &segments[1..], &workspace)?;
+        Ok(format!("[{header}] {result}"))
     }
 
-    // Tool implementation methods
-    pub async fn find_definition(
+    /// Tests, binaries, benches and doctests rust-analyzer can run in a
+    /// file, or only those covering a position when one is given.
+    pub async fn list_runnables(
         &mut self,
         file_path: &str,
-        line: u32,
-        character: u32,
+        position: Option<(u32, u32)>,
     ) -> Result<String> {
         if !self.initialized {
             return Err(anyhow::anyhow!("Client not initialized"));
         }
 
-        let params = create_text_document_position_params(file_path, line, character);
-        let response = self
-            .send_request_internal("textDocument/definition", params)
-            .await?;
+        let path = edit::absolute_path(file_path)?;
+        self.wait_until_ready().await?;
+        self.open_document(&path).await?;
+        let params = create_runnables_params(&path.to_string_lossy(), position);
+        let result = self.send_request("experimental/runnables", params).await?;
 
-        Ok(format!("Definition response: {response}"))
+        self.runnables = result
+            .as_array()
+            .map(|runnables| runnables.iter().filter_map(Runnable::from_lsp).collect())
+            .unwrap_or_default();
+        Ok(runnables::format_runnables(&self.runnables))
     }
 
-    pub async fn find_references(
+    /// Execute a runnable from the last `list_runnables` result. After
+    /// `timeout`, cargo is killed together with the test or binary it
+    /// started.
+    pub async fn run_runnable(
+        &mut self,
+        id: usize,
+        timeout: Duration,
+        output_limit: usize,
+    ) -> Result<String> {
+        let runnable = self.runnables.get(id).cloned().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No runnable with id {id}; call list_runnables first ({} known)",
+                self.runnables.len()
+            )
+        })?;
+
+        let mut command = runnable.command();
+        if runnable.is_test() {
+            command.env("RUST_BACKTRACE", "0");
+        }
+        let output = cargo::run_with_limits(command, timeout, output_limit).await?;
+
+        let mut result = format!("$ {}\n", runnable.command_line());
+        if runnable.is_test() {
+            let cases =
+                cargo::parse_libtest_output(&output.stdout, &cargo::test_suites(&output.stderr));
+            if !cases.is_empty() {
+                result.push_str(&cargo::format_test_results(&cases));
+                result.push('\n');
+            }
+        }
+        result.push_str(&output.format(output_limit));
+        Ok(result)
+    }
+
+    /// Assists and quick fixes rust-analyzer offers for a position, or for
+    /// the range from `start` to `end`, optionally limited to a kind prefix
+    /// such as `refactor.extract` or `quickfix`.
+    pub async fn list_code_actions(
         &mut self,
         file_path: &str,
-        line: u32,
-        character: u32,
+        start: (u32, u32),
+        end: Option<(u32, u32)>,
+        kind: Option<&str>,
     ) -> Result<String> {
         if !self.initialized {
             return Err(anyhow::anyhow!("Client not initialized"));
         }
 
-        let params = create_references_params(file_path, line, character);
-        let response = self
-            .send_request_internal("textDocument/references", params)
-            .await?;
+        let path = edit::absolute_path(file_path)?;
+        self.wait_until_ready().await?;
+        self.open_document(&path).await?;
+        let (end_line, end_character) = end.unwrap_or(start);
+        let range = json!({
+            "start": { "line": start.0, "character": start.1 },
+            "end": { "line": end_line, "character": end_character }
+        });
+        let diagnostics = code_actions::overlapping_diagnostics(
+            self.diagnostics
+                .get(&path)
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+            &range,
+        );
+        let params = create_code_action_params(&path.to_string_lossy(), range, diagnostics, kind);
+        let result = self.send_request("textDocument/codeAction", params).await?;
 
-        Ok(format!("References response: {response}"))
+        self.code_actions = result
+            .as_array()
+            .map(|actions| actions.iter().filter_map(CodeAction::from_lsp).collect())
+            .unwrap_or_default();
+        Ok(code_actions::format_code_actions(&self.code_actions))
     }
 
-    pub async fn get_diagnostics(&mut self, file_path: &str) -> Result<String> {
+    /// Resolve and apply an action from the last `list_code_actions` result.
+    pub async fn apply_code_action(&mut self, id: usize, dry_run: bool) -> Result<String> {
         if !self.initialized {
             return Err(anyhow::anyhow!("Client not initialized"));
         }
 
-        // For diagnostics, we typically receive them via notifications
-        // This is a simplified implementation
-        Ok(format!("Diagnostics for file: {file_path}"))
+        let action = self.code_actions.get(id).cloned().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No code action with id {id}; call list_code_actions first ({} known)",
+                self.code_actions.len()
+            )
+        })?;
+        let resolved = if action.needs_resolve() {
+            self.send_request("codeAction/resolve", action.action.clone())
+                .await?
+        } else {
+            action.action.clone()
+        };
+        let workspace_edit = resolved.get("edit").ok_or_else(|| {
+            anyhow::anyhow!("Code action '{}' has no edit to apply", action.title)
+        })?;
+        let changes = edit::preview_workspace_edit(workspace_edit)?;
+
+        let mut result = if dry_run {
+            format!(
+                "Dry run: '{}' would change {} files\n\n",
+                action.title,
+                changes.len()
+            )
+        } else {
+            self.apply_file_changes(&changes).await?;
+            // Ids refer to the old file contents.
+            self.code_actions.clear();
+            format!("Applied '{}' to {} files\n\n", action.title, changes.len())
+        };
+        for change in &changes {
+            result.push_str(&edit::unified_diff(change));
+        }
+        if let Some(command) = resolved["command"]["command"].as_str() {
+            result.push_str(&format!("\nFollow-up command {command} was not run\n"));
+        }
+        Ok(result)
     }
 
-    pub async fn workspace_symbols(&mut self, query: &str) -> Result<String> {
-        if !self.initialized {
-            return Err(anyhow::anyhow!("Client not initialized"));
+    /// Summarize a manifest: package, workspace, dependency tables and
+    /// the features that can be selected.
+    pub async fn analyze_manifest(&mut self, manifest_path: &str) -> Result<String> {
+        let manifest = manifest::manifest_path(manifest_path)?;
+        let document = manifest::read_document(&manifest)?;
+        let mut result = format!("Manifest {}\n\n", manifest.display());
+        result.push_str(&manifest::describe(&document));
+
+        let graph = FeatureGraph::from_document(&document);
+        let selectable = graph.selectable();
+        if !selectable.is_empty() {
+            result.push_str(&format!(
+                "\nFeatures ({}): {}\n",
+                selectable.len(),
+                selectable.join(", ")
+            ));
+            if let Some(default) = graph.features.get("default") {
+                result.push_str(&format!("  default = {default:?}\n"));
+            }
         }
+        Ok(result)
+    }
 
-        let params = create_workspace_symbol_params(query);
-        let response = self
-            .send_request_internal("workspace/symbol", params)
+    /// Run `cargo check` on the selected targets, killing it after
+    /// `timeout`.
+    pub async fn run_cargo_check(
+        &mut self,
+        workspace_path: &str,
+        options: &cargo::BuildOptions,
+        timeout: Duration,
+    ) -> Result<String> {
+        let workspace = edit::absolute_path(workspace_path)?;
+        let report = cargo::check(&workspace, options, timeout).await?;
+        Ok(report.format("check"))
+    }
+
+    /// Run `cargo test` and report each test. After `timeout`, cargo is
+    /// killed with the test binary it started and the tests that finished
+    /// are reported.
+    pub async fn run_cargo_test(
+        &mut self,
+        workspace_path: &str,
+        options: &cargo::TestOptions,
+        timeout: Duration,
+    ) -> Result<String> {
+        let workspace = edit::absolute_path(workspace_path)?;
+        // libtest's JSON output (with per-test timings) needs a nightly toolchain.
+        let json = tokio::process::Command::new("rustc")
+            .arg("-V")
+            .current_dir(&workspace)
+            .output()
+            .await
+            .map(|output| {
+                let version = String::from_utf8_lossy(&output.stdout);
+                version.contains("nightly") || version.contains("-dev")
+            })
+            .unwrap_or(false);
+
+        let mut command = tokio::process::Command::new("cargo");
+        command
+            .args(options.args(json))
+            .current_dir(&workspace)
+            .env("RUST_BACKTRACE", "0");
+        let output = cargo::run_with_limits(command, timeout, cargo::PARSED_OUTPUT_LIMIT).await?;
+        let (stdout, stderr) = (&output.stdout, &output.stderr);
+
+        let suites = cargo::test_suites(stderr);
+        let cases = if json {
+            cargo::parse_libtest_json(stdout, &suites)
+        } else {
+            cargo::parse_libtest_output(stdout, &suites)
+        };
+        let Some(status) = output.status else {
+            let mut result = format!(
+                "cargo test timed out after {}s and was killed",
+                timeout.as_secs()
+            );
+            if cases.is_empty() {
+                result.push_str(" before any test finished\n");
+            } else {
+                result.push_str("; tests that finished:\n\n");
+                result.push_str(&cargo::format_test_results(&cases));
+            }
+            return Ok(result);
+        };
+        if cases.is_empty() && !status.success() {
+            let errors = cargo::compiler_errors(stderr);
+            return Err(anyhow::anyhow!(
+                "cargo test failed before running any tests:\n{}",
+                if errors.is_empty() {
+                    stderr.trim().to_string()
+                } else {
+                    errors.join("\n")
+                }
+            ));
+        }
+        if cases.is_empty() {
+            return Ok("cargo test: no tests matched".to_string());
+        }
+        Ok(cargo::format_test_results(&cases))
+    }
+
+    /// Run benchmarks, optionally comparing them with a saved baseline
+    /// and saving the results as a new one. After `timeout`, cargo is
+    /// killed with the bench binary and the finished benchmarks are
+    /// reported but not saved.
+    pub async fn run_cargo_bench(
+        &mut self,
+        workspace_path: &str,
+        options: &cargo::BenchOptions,
+        baseline: Option<&str>,
+        save_baseline: Option<&str>,
+        threshold: f64,
+        timeout: Duration,
+    ) -> Result<String> {
+        let workspace = edit::absolute_path(workspace_path)?;
+        let target_directory = cargo::target_directory(&workspace).await?;
+        let compare_with = match baseline {
+            Some(name) => {
+                let path = cargo::baseline_path(&target_directory, name)?;
+                Some((name, cargo::load_baseline(&path)?))
+            }
+            None => None,
+        };
+        let save_path = save_baseline
+            .map(|name| cargo::baseline_path(&target_directory, name))
+            .transpose()?;
+
+        let mut command = tokio::process::Command::new("cargo");
+        command.args(options.args()).current_dir(&workspace);
+        let output = cargo::run_with_limits(command, timeout, cargo::PARSED_OUTPUT_LIMIT).await?;
+        let timings = cargo::parse_bench_output(&output.stdout);
+        let compare_with = compare_with
+            .as_ref()
+            .map(|(name, timings)| (*name, timings));
+        let Some(status) = output.status else {
+            let mut result = format!(
+                "cargo bench timed out after {}s and was killed",
+                timeout.as_secs()
+            );
+            if timings.is_empty() {
+                result.push_str(" before any benchmark finished\n");
+            } else {
+                result.push_str("; benchmarks that finished (no baseline saved):\n\n");
+                result.push_str(&cargo::format_bench_results(
+                    &timings,
+                    compare_with,
+                    threshold,
+                ));
+            }
+            return Ok(result);
+        };
+        if timings.is_empty() {
+            if status.success() {
+                return Ok("cargo bench: no benchmark results found".to_string());
+            }
+            let errors = cargo::compiler_errors(&output.stderr);
+            return Err(anyhow::anyhow!(
+                "cargo bench failed:\n{}",
+                if errors.is_empty() {
+                    output.stderr.trim().to_string()
+                } else {
+                    errors.join("\n")
+                }
+            ));
+        }
+
+        let mut result = cargo::format_bench_results(&timings, compare_with, threshold);
+        if let Some(path) = save_path {
+            cargo::save_baseline(&path, &timings)?;
+            result.push_str(&format!("\nSaved baseline to {}\n", path.display()));
+        }
+        Ok(result)
+    }
+
+    pub async fn cargo_build(
+        &mut self,
+        workspace_path: &str,
+        options: &cargo::BuildOptions,
+    ) -> Result<String> {
+        let workspace = edit::absolute_path(workspace_path)?;
+        let report = cargo::build(&workspace, options).await?;
+        Ok(report.format("build"))
+    }
+
+    /// Build the selected binary or example and run it with `args`,
+    /// capturing its output under a timeout and size limit.
+    pub async fn cargo_run(
+        &mut self,
+        workspace_path: &str,
+        options: &cargo::BuildOptions,
+        args: &[String],
+        timeout: Duration,
+        output_limit: usize,
+    ) -> Result<String> {
+        let workspace = edit::absolute_path(workspace_path)?;
+        let report = cargo::build(&workspace, options).await?;
+        if !report.success {
+            return Ok(format!(
+                "{}\nNot running: the build failed",
+                report.format("build")
+            ));
+        }
+
+        let wanted = options.bin.as_ref().or(options.example.as_ref());
+        let executables: Vec<&cargo::Artifact> = report
+            .executables()
+            .into_iter()
+            .filter(|artifact| wanted.is_none_or(|name| &artifact.name == name))
+            .collect();
+        let artifact = match executables.as_slice() {
+            [artifact] => *artifact,
+            [] => return Err(anyhow::anyhow!("The build produced no executable to run")),
+            many => {
+                let names: Vec<&str> = many.iter().map(|a| a.name.as_str()).collect();
+                return Err(anyhow::anyhow!(
+                    "Several executables were built ({}), pass bin or example to pick one",
+                    names.join(", ")
+                ));
+            }
+        };
+        let executable = artifact
+            .executable
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("{} has no executable", artifact.name))?;
+
+        let mut command = tokio::process::Command::new(executable);
+        command.args(args).current_dir(&workspace);
+        let output = cargo::run_with_limits(command, timeout, output_limit).await?;
+
+        let summary = report.format("build");
+        Ok(format!(
+            "{}\nRunning {}\n{}",
+            summary.lines().next().unwrap_or_default(),
+            executable.display(),
+            output.format(output_limit)
+        ))
+    }
+
+    pub async fn add_dependency(
+        &mut self,
+        manifest_path: &str,
+        spec: &manifest::DependencySpec,
+    ) -> Result<String> {
+        let manifest = manifest::manifest_path(manifest_path)?;
+        let mut document = manifest::read_document(&manifest)?;
+        let (entry, added) = manifest::upsert_dependency(&mut document, spec)?;
+        let warning = self
+            .write_validated_manifest(&manifest, &document.to_string())
             .await?;
 
-        Ok(format!("Workspace symbols response: {response}"))
+        let result = format!(
+            "{} {} in {}:\n{entry}",
+            if added { "Added to" } else { "Updated" },
+            manifest::table_label(spec.kind, spec.target.as_deref()),
+            manifest.display()
+        );
+        Ok(with_warning(result, warning))
     }
 
-    pub async fn rename_symbol(
+    pub async fn remove_dependency(
         &mut self,
-        file_path: &str,
-        line: u32,
-        character: u32,
-        new_name: &str,
+        manifest_path: &str,
+        name: &str,
+        kind: manifest::DependencyKind,
+        target: Option<&str>,
     ) -> Result<String> {
-        if !self.initialized {
-            return Err(anyhow::anyhow!("Client not initialized"));
+        let manifest = manifest::manifest_path(manifest_path)?;
+        let mut document = manifest::read_document(&manifest)?;
+        let label = manifest::table_label(kind, target);
+        if !manifest::remove_dependency(&mut document, name, kind, target)? {
+            return Err(anyhow::anyhow!(
+                "'{name}' is not declared in {label} of {}",
+                manifest.display()
+            ));
         }
+        let warning = self
+            .write_validated_manifest(&manifest, &document.to_string())
+            .await?;
 
-        let params = create_rename_params(file_path, line, character, new_name);
-        let response = self
-            .send_request_internal("textDocument/rename", params)
+        let result = format!("Removed '{name}' from {label} in {}", manifest.display());
+        Ok(with_warning(result, warning))
+    }
+
+    pub async fn list_features(&mut self, manifest_path: &str) -> Result<String> {
+        let manifest = manifest::manifest_path(manifest_path)?;
+        let document = manifest::read_document(&manifest)?;
+        let graph = FeatureGraph::from_document(&document);
+        if graph.features.is_empty() {
+            return Ok(format!("{} defines no features", manifest.display()));
+        }
+
+        let mut result = format!("Features in {}:\n", manifest.display());
+        for (name, activations) in &graph.features {
+            if graph.optional_dependencies.get(name) == Some(&true) {
+                continue;
+            }
+            result.push_str(&format!("\n{name} = {activations:?}\n"));
+            let activation = graph.activate([name.as_str()]);
+            let features: Vec<&String> = activation
+                .features
+                .iter()
+                .filter(|feature| *feature != name)
+                .collect();
+            if !features.is_empty() {
+                result.push_str(&format!("  enables features: {features:?}\n"));
+            }
+            if !activation.dependencies.is_empty() {
+                result.push_str(&format!(
+                    "  enables dependencies: {:?}\n",
+                    activation.dependencies
+                ));
+            }
+            if !activation.dependency_features.is_empty() {
+                result.push_str(&format!(
+                    "  dependency features: {:?}\n",
+                    activation.dependency_features
+                ));
+            }
+        }
+
+        if !graph.optional_dependencies.is_empty() {
+            result.push_str("\nOptional dependencies:\n");
+            for (dependency, implicit) in &graph.optional_dependencies {
+                let gates = graph.gates(dependency);
+                result.push_str(&format!(
+                    "  {dependency}{} enabled by {gates:?}\n",
+                    if *implicit { " (implicit feature)" } else { "" }
+                ));
+            }
+        }
+        Ok(result)
+    }
+
+    pub async fn add_feature(
+        &mut self,
+        manifest_path: &str,
+        name: &str,
+        activations: &[String],
+    ) -> Result<String> {
+        let manifest = manifest::manifest_path(manifest_path)?;
+        let mut document = manifest::read_document(&manifest)?;
+        let replaced = features::define_feature(&mut document, name, activations)?;
+        let warning = self
+            .write_validated_manifest(&manifest, &document.to_string())
             .await?;
 
-        Ok(format!("Rename response: {response}"))
+        let result = format!(
+            "{} feature {name} = {activations:?} in {}",
+            if replaced { "Updated" } else { "Added" },
+            manifest.display()
+        );
+        Ok(with_warning(result, warning))
     }
 
-    pub async fn format_code(&mut self, file_path: &str) -> Result<String> {
-        if !self.initialized {
-            return Err(anyhow::anyhow!("Client not initialized"));
+    pub async fn remove_feature(&mut self, manifest_path: &str, name: &str) -> Result<String> {
+        let manifest = manifest::manifest_path(manifest_path)?;
+        let mut document = manifest::read_document(&manifest)?;
+        let referrers = features::remove_feature(&mut document, name)?;
+        let warning = self
+            .write_validated_manifest(&manifest, &document.to_string())
+            .await?;
+
+        let mut result = format!("Removed feature '{name}' from {}", manifest.display());
+        if !referrers.is_empty() {
+            result.push_str(&format!("\nNo longer enabled by: {referrers:?}"));
         }
+        Ok(with_warning(result, warning))
+    }
 
-        let params = create_formatting_params(file_path);
-        let response = self
-            .send_request_internal("textDocument/formatting", params)
+    pub async fn gate_dependency(
+        &mut self,
+        manifest_path: &str,
+        dependency: &str,
+        gates: &[String],
+    ) -> Result<String> {
+        let manifest = manifest::manifest_path(manifest_path)?;
+        let mut document = manifest::read_document(&manifest)?;
+        features::gate_dependency(&mut document, dependency, gates)?;
+        let warning = self
+            .write_validated_manifest(&manifest, &document.to_string())
             .await?;
 
-        Ok(format!("Formatting response: {response}"))
+        let result = if gates.is_empty() {
+            format!(
+                "'{dependency}' is now a regular dependency in {}",
+                manifest.display()
+            )
+        } else {
+            format!(
+                "'{dependency}' is now optional and enabled by {gates:?} in {}",
+                manifest.display()
+            )
+        };
+        Ok(with_warning(result, warning))
     }
 
-    pub async fn analyze_manifest(&mut self, manifest_path: &str) -> Result<String> {
-        // This would analyze Cargo.toml file
-        Ok(format!("Manifest analysis for: {manifest_path}"))
+    /// Run `cargo check --no-default-features --features ...` for every
+    /// combination of features, or every combination of at most `depth`
+    /// features, giving each check at most `timeout`.
+    pub async fn check_feature_combinations(
+        &mut self,
+        manifest_path: &str,
+        depth: Option<usize>,
+        timeout: Duration,
+    ) -> Result<String> {
+        let manifest = manifest::manifest_path(manifest_path)?;
+        let document = manifest::read_document(&manifest)?;
+        let selectable = FeatureGraph::from_document(&document).selectable();
+        let count = features::powerset_len(selectable.len(), depth);
+        if count > MAX_FEATURE_COMBINATIONS {
+            return Err(anyhow::anyhow!(
+                "{} features give {count} combinations (limit {MAX_FEATURE_COMBINATIONS}), pass a smaller depth",
+                selectable.len()
+            ));
+        }
+        let package_dir = manifest
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("{} has no parent directory", manifest.display()))?;
+
+        let mut failures = Vec::new();
+        let combinations = features::powerset(&selectable, depth);
+        for combination in &combinations {
+            let options = cargo::BuildOptions {
+                features: combination.clone(),
+                no_default_features: true,
+                ..Default::default()
+            };
+            let errors = match cargo::check(package_dir, &options, timeout).await {
+                Ok(report) if report.success => continue,
+                Ok(report) => report.errors(),
+                Err(e) => vec![e.to_string()],
+            };
+            let errors = errors.into_iter().take(10).collect::<Vec<_>>();
+            failures.push((combination.join(","), errors));
+        }
+
+        let mut result = format!(
+            "Checked {} feature combinations of {}: {} passed, {} failed\n",
+            combinations.len(),
+            manifest.display(),
+            combinations.len() - failures.len(),
+            failures.len()
+        );
+        for (features, errors) in failures {
+            let features = if features.is_empty() {
+                "(none)".to_string()
+            } else {
+                features
+            };
+            result.push_str(&format!("\nFAILED --features {features}\n"));
+            for error in errors {
+                result.push_str(&format!("  {error}\n"));
+            }
+        }
+        Ok(result)
     }
 
-    pub async fn run_cargo_check(&mut self, workspace_path: &str) -> Result<String> {
-        // This would run cargo check and parse results
-        Ok(format!("Cargo check results for: {workspace_path}"))
+    /// Write a new manifest and check it with `cargo metadata --offline`,
+    /// restoring the previous manifest and lockfile if cargo rejects it.
+    /// Dependency resolution is only required if it worked before the
+    /// change; otherwise (e.g. sources missing from the local cache) the
+    /// manifest itself is validated with `--no-deps` and the resolution
+    /// error of the edited manifest is returned as a warning. Accepted
+    /// changes are reported to rust-analyzer so it reloads the workspace.
+    async fn write_validated_manifest(
+        &mut self,
+        manifest: &Path,
+        text: &str,
+    ) -> Result<Option<String>> {
+        let original = std::fs::read_to_string(manifest)?;
+        let lockfile = match manifest
+            .ancestors()
+            .skip(1)
+            .map(|dir| dir.join("Cargo.lock"))
+            .find(|path| path.is_file())
+        {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path)?;
+                Some((path, text))
+            }
+            None => None,
+        };
+        let resolves = cargo_metadata(manifest, false).await.is_ok();
+
+        std::fs::write(manifest, text)?;
+        let Err(error) = cargo_metadata(manifest, !resolves).await else {
+            let warning = match resolves {
+                true => None,
+                false => cargo_metadata(manifest, false).await.err().map(|error| {
+                    format!(
+                        "dependencies of {} don't resolve offline, so only the manifest itself was checked:\n{error}",
+                        manifest.display()
+                    )
+                }),
+            };
+            if self.initialized {
+                self.sync_changed_files(&[FileChange {
+                    path: manifest.to_path_buf(),
+                    old_text: Some(original),
+                    new_text: Some(text.to_string()),
+                }])
+                .await?;
+            }
+            return Ok(warning);
+        };
+        std::fs::write(manifest, original)?;
+        if let Some((path, text)) = lockfile {
+            std::fs::write(path, text)?;
+        }
+        Err(anyhow::anyhow!(
+            "cargo metadata --offline rejected the change, {} was left unchanged:\n{error}",
+            manifest.display()
+        ))
     }
 
     pub async fn extract_function(
@@ -413,14 +2783,149 @@ impl RustAnalyzerClient {
         &mut self,
         query: &str,
         workspace_path: &str,
+        add_crate: Option<&str>,
     ) -> Result<String> {
-        if !self.initialized {
-            return Err(anyhow::anyhow!("Client not initialized"));
+        let workspace = edit::absolute_path(workspace_path)?;
+        let workspace = if workspace.is_file() {
+            workspace
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or(workspace)
+        } else {
+            workspace
+        };
+        let index = CrateIndex::discover();
+        if index.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No local crate index found; set CRATES_INDEX_PATH or populate the cargo registry cache"
+            ));
         }
-        // This would analyze code and suggest crates based on usage patterns
-        Ok(format!(
-            "Dependency suggestions for '{query}' in workspace {workspace_path}"
-        ))
+
+        // Crates the code already tries to use: unresolved imports in the
+        // sources plus anything rust-analyzer has reported.
+        let mut wanted: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut referencing: BTreeMap<String, BTreeSet<PathBuf>> = BTreeMap::new();
+        for (name, files) in crate_index::unresolved_crate_references(&workspace) {
+            let entry = wanted.entry(name.clone()).or_default();
+            entry.extend(files.iter().map(|path| path.display().to_string()));
+            referencing.entry(name).or_default().extend(files);
+        }
+        // Names from diagnostics get the same filter as the sources: paths
+        // like `crate::foo` or a declared dependency's aren't missing crates.
+        let mut known_roots: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+        for (path, diagnostics) in &self.diagnostics {
+            if !path.starts_with(&workspace) {
+                continue;
+            }
+            let package = syntax::find_manifest_dir(path).unwrap_or_else(|| workspace.clone());
+            let known = known_roots
+                .entry(package)
+                .or_insert_with_key(|package| crate_index::known_roots(package));
+            for diagnostic in diagnostics {
+                let message = diagnostic["message"].as_str().unwrap_or_default();
+                if let Some(name) = crate_index::crates_from_diagnostic(message)
+                    && !known.contains(&name)
+                {
+                    let line = diagnostic["range"]["start"]["line"].as_u64().unwrap_or(0) + 1;
+                    wanted
+                        .entry(name.clone())
+                        .or_default()
+                        .insert(format!("{}:{line}", path.display()));
+                    referencing.entry(name).or_default().insert(path.clone());
+                }
+            }
+        }
+
+        let mut output = String::new();
+        if !wanted.is_empty() {
+            output.push_str("Unresolved crates referenced in the workspace:\n");
+            for (name, locations) in &wanted {
+                let locations: Vec<&str> = locations.iter().map(String::as_str).collect();
+                output.push_str(&format!("- `{name}` (used in {})\n", locations.join(", ")));
+                match index
+                    .lookup(name)
+                    .or_else(|| index.lookup(&name.replace('_', "-")))
+                {
+                    Some(info) => output.push_str(&format_crate_info(&info, "  ")),
+                    None => output.push_str("  no matching crate in the local index\n"),
+                }
+            }
+        }
+
+        if !query.trim().is_empty() {
+            let matches = index.search(query, 10);
+            if matches.is_empty() {
+                output.push_str(&format!("No local crates match '{query}'\n"));
+            } else {
+                output.push_str(&format!("Crates matching '{query}':\n"));
+                for (position, info) in matches.iter().enumerate() {
+                    output.push_str(&format!("{}. ", position + 1));
+                    output.push_str(format_crate_info(info, "   ").trim_start());
+                }
+            }
+        }
+
+        if let Some(name) = add_crate {
+            let info = index
+                .lookup(name)
+                .ok_or_else(|| anyhow::anyhow!("Crate '{name}' not found in the local index"))?;
+            // Add it to the packages whose code refers to it, or to the
+            // package at `workspace_path` when nothing does yet.
+            let code_name = info.name.replace('-', "_");
+            let mut manifests: BTreeSet<PathBuf> = referencing
+                .get(&code_name)
+                .into_iter()
+                .flatten()
+                .filter_map(|path| syntax::find_manifest_dir(path))
+                .map(|dir| dir.join("Cargo.toml"))
+                .collect();
+            if manifests.is_empty() {
+                let manifest = manifest::manifest_path(&workspace.to_string_lossy())?;
+                if manifest::read_document(&manifest)?.get("package").is_none() {
+                    return Err(anyhow::anyhow!(
+                        "{} is a virtual manifest and no package refers to {}; pass the package directory as workspace_path",
+                        manifest.display(),
+                        info.name
+                    ));
+                }
+                manifests.insert(manifest);
+            }
+
+            for manifest in manifests {
+                let mut document = manifest::read_document(&manifest)?;
+                if manifest::declared_dependency_names(&document).contains(&code_name) {
+                    output.push_str(&format!(
+                        "{} is already a dependency in {}\n",
+                        info.name,
+                        manifest.display()
+                    ));
+                    continue;
+                }
+                let spec = manifest::DependencySpec {
+                    name: info.name.clone(),
+                    version: Some(info.latest_version.clone()),
+                    ..Default::default()
+                };
+                manifest::upsert_dependency(&mut document, &spec)?;
+                let warning = self
+                    .write_validated_manifest(&manifest, &document.to_string())
+                    .await?;
+                output.push_str(&format!(
+                    "Added {} = \"{}\" to {}\n",
+                    info.name,
+                    info.latest_version,
+                    manifest.display()
+                ));
+                if let Some(warning) = warning {
+                    output.push_str(&format!("Warning: {warning}\n"));
+                }
+            }
+        }
+
+        if output.is_empty() {
+            output.push_str("No unresolved crates found and no query given");
+        }
+        Ok(output)
     }
 
     pub async fn create_module(
@@ -447,9 +2952,153 @@ impl RustAnalyzerClient {
         if !self.initialized {
             return Err(anyhow::anyhow!("Client not initialized"));
         }
-        Ok(format!(
-            "Moved {} items from {source_file} to {target_file}: {item_names:?}",
-            item_names.len()
-        ))
+
+        let source = edit::absolute_path(source_file)?;
+        let target = edit::absolute_path(target_file)?;
+        if source == target {
+            return Err(anyhow::anyhow!("Source and target file are the same"));
+        }
+        if !target.is_file() {
+            return Err(anyhow::anyhow!(
+                "Target file {} does not exist; create the module first",
+                target.display()
+            ));
+        }
+
+        self.wait_until_ready().await?;
+        let symbols = self.document_symbols(&source).await?;
+        let items = move_items::select_items(&symbols, item_names)?;
+
+        let mut references = Vec::new();
+        for item in items.iter().filter(|item| !item.is_impl) {
+            let locations = self
+                .reference_locations(&source, &item.symbol["selectionRange"]["start"])
+                .await?;
+            references.push(move_items::SymbolReferences {
+                symbol: item.symbol.clone(),
+                locations,
+            });
+        }
+
+        let source_text = std::fs::read_to_string(&source)?;
+        let mut member_references = Vec::new();
+        for member in move_items::private_members(&source_text, &items) {
+            let locations = self
+                .reference_locations(&source, &member["selectionRange"]["start"])
+                .await?;
+            member_references.push(move_items::SymbolReferences {
+                symbol: member,
+                locations,
+            });
+        }
+
+        let plan = move_items::plan_move(move_items::MoveRequest {
+            source: &source,
+            target: &target,
+            symbols: &symbols,
+            items: &items,
+            references: &references,
+            member_references: &member_references,
+        })?;
+        self.apply_file_changes(&plan.changes).await?;
+
+        Ok(plan.summary)
+    }
+}
+
+/// A source file of the workspace rooted at the current directory, used as
+/// the resolution context for workspace-wide requests: the crate root of a
+/// member reported by `cargo metadata`.
+async fn workspace_context_file() -> Result<PathBuf> {
+    let root = std::env::current_dir()?;
+    cargo::member_crate_roots(&root)
+        .await?
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| anyhow::anyhow!("No crate root found in {}", root.display()))
+}
+
+fn format_crate_info(info: &CrateInfo, indent: &str) -> String {
+    const MAX_FEATURES: usize = 20;
+    let mut text = format!("{indent}{} {}", info.name, info.latest_version);
+    if let Some(description) = &info.description {
+        text.push_str(&format!(" - {description}"));
+    }
+    text.push('\n');
+    if !info.features.is_empty() {
+        let shown: Vec<&str> = info
+            .features
+            .iter()
+            .take(MAX_FEATURES)
+            .map(String::as_str)
+            .collect();
+        text.push_str(&format!("{indent}  features: {}", shown.join(", ")));
+        if info.features.len() > MAX_FEATURES {
+            text.push_str(&format!(" (+{} more)", info.features.len() - MAX_FEATURES));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Append a warning from `write_validated_manifest` to a tool result.
+fn with_warning(mut result: String, warning: Option<String>) -> String {
+    if let Some(warning) = warning {
+        result.push_str(&format!("\n\nWarning: {warning}"));
+    }
+    result
+}
+
+/// Run `cargo metadata --offline` on a manifest, returning cargo's error
+/// output on failure.
+async fn cargo_metadata(manifest: &Path, no_deps: bool) -> Result<(), String> {
+    let mut command = tokio::process::Command::new("cargo");
+    command
+        .args(["metadata", "--offline", "--format-version", "1"])
+        .arg("--manifest-path")
+        .arg(manifest)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    if no_deps {
+        command.arg("--no-deps");
+    }
+    match command.output().await {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        Err(e) => Err(format!("failed to run cargo: {e}")),
+    }
+}
+
+async fn read_messages(stdout: ChildStdout, sender: mpsc::UnboundedSender<Value>) -> Result<()> {
+    let mut reader = BufReader::new(stdout);
+
+    loop {
+        // Read headers
+        let mut content_length: Option<usize> = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+
+            if line == "\r\n" {
+                break;
+            }
+
+            if let Some(stripped) = line.strip_prefix("Content-Length:") {
+                let length_str = stripped.trim();
+                content_length = Some(length_str.parse()?);
+            }
+        }
+
+        if let Some(length) = content_length {
+            let mut content = vec![0u8; length];
+            reader.read_exact(&mut content).await?;
+
+            let message = parse_lsp_message(&content)?;
+            if sender.send(message.content).is_err() {
+                return Ok(());
+            }
+        }
     }
 }
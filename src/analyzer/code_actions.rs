@@ -0,0 +1,67 @@
+// Generic access to rust-analyzer's assists and quick fixes through
+// `textDocument/codeAction` and `codeAction/resolve`.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct CodeAction {
+    pub title: String,
+    pub kind: Option<String>,
+    /// The action as returned by the server, resolved lazily on apply.
+    pub action: Value,
+}
+
+impl CodeAction {
+    /// Parse a `CodeAction` literal. Bare `Command`s and disabled actions
+    /// can't be applied as edits and are skipped.
+    pub fn from_lsp(value: &Value) -> Option<Self> {
+        let title = value["title"].as_str()?;
+        if value["command"].is_string() || value.get("disabled").is_some() {
+            return None;
+        }
+        Some(Self {
+            title: title.to_string(),
+            kind: value["kind"].as_str().map(String::from),
+            action: value.clone(),
+        })
+    }
+
+    pub fn needs_resolve(&self) -> bool {
+        self.action.get("edit").is_none() && self.action.get("data").is_some()
+    }
+}
+
+/// Diagnostics whose range intersects `range`; sent as the request context
+/// so quick fixes for them are offered.
+pub fn overlapping_diagnostics(diagnostics: &[Value], range: &Value) -> Vec<Value> {
+    let position = |value: &Value| {
+        (
+            value["line"].as_u64().unwrap_or(0),
+            value["character"].as_u64().unwrap_or(0),
+        )
+    };
+    let (start, end) = (position(&range["start"]), position(&range["end"]));
+    diagnostics
+        .iter()
+        .filter(|diagnostic| {
+            let range = &diagnostic["range"];
+            position(&range["start"]) <= end && start <= position(&range["end"])
+        })
+        .cloned()
+        .collect()
+}
+
+pub fn format_code_actions(actions: &[CodeAction]) -> String {
+    if actions.is_empty() {
+        return "No code actions available".to_string();
+    }
+    let mut result = format!("{} code actions:\n", actions.len());
+    for (id, action) in actions.iter().enumerate() {
+        result.push_str(&format!("\n[{id}] {}", action.title));
+        if let Some(kind) = &action.kind {
+            result.push_str(&format!(" ({kind})"));
+        }
+    }
+    result.push('\n');
+    result
+}
@@ -0,0 +1,154 @@
+// `textDocument/completion` results reduced to what an agent needs to pick
+// a real name: label, kind, signature and a line of documentation.
+
+use serde_json::Value;
+
+use crate::analyzer::syntax;
+
+/// Default number of candidates returned.
+pub const DEFAULT_COMPLETION_LIMIT: usize = 50;
+
+/// Where a completion should be requested after the optional trigger text
+/// (`.` or `::`) is inserted at `offset`.
+pub fn insert_trigger(text: &str, offset: usize, trigger: &str) -> (String, usize) {
+    let mut overlay = String::with_capacity(text.len() + trigger.len());
+    overlay.push_str(&text[..offset]);
+    overlay.push_str(trigger);
+    overlay.push_str(&text[offset..]);
+    (overlay, offset + trigger.len())
+}
+
+/// The partial identifier ending at `offset`, used to filter candidates the
+/// way an editor does while typing.
+pub fn typed_prefix(text: &str, offset: usize) -> &str {
+    let start = text[..offset]
+        .bytes()
+        .rposition(|byte| !syntax::is_ident_byte(byte))
+        .map_or(0, |index| index + 1);
+    &text[start..offset]
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub label: String,
+    pub kind: &'static str,
+    pub detail: Option<String>,
+    pub documentation: Option<String>,
+    filter_text: String,
+    sort_text: String,
+}
+
+impl Completion {
+    /// Parse a `CompletionItem`; snippets (postfix templates and the like)
+    /// are skipped since they don't name anything in the API.
+    pub fn from_lsp(item: &Value) -> Option<Self> {
+        let kind = item["kind"].as_u64().unwrap_or(0);
+        if kind == 15 {
+            return None;
+        }
+        let label = item["label"].as_str()?.to_string();
+        let detail = item["detail"]
+            .as_str()
+            .or_else(|| item["labelDetails"]["description"].as_str())
+            .filter(|detail| !detail.is_empty())
+            .map(String::from);
+        let documentation = item["documentation"]
+            .as_str()
+            .or_else(|| item["documentation"]["value"].as_str())
+            .and_then(|docs| {
+                docs.split("\n\n")
+                    .map(str::trim)
+                    .find(|paragraph| !paragraph.is_empty())
+            })
+            .map(|paragraph| paragraph.replace('\n', " "));
+        Some(Self {
+            filter_text: item["filterText"].as_str().unwrap_or(&label).to_string(),
+            sort_text: item["sortText"].as_str().unwrap_or(&label).to_string(),
+            label,
+            kind: kind_label(kind),
+            detail,
+            documentation,
+        })
+    }
+
+    /// 0 for a case-sensitive prefix match, 1 for a case-insensitive one,
+    /// 2 for a fuzzy (subsequence) match, `None` if the query doesn't match.
+    fn match_rank(&self, query: &str) -> Option<u8> {
+        if self.filter_text.starts_with(query) {
+            return Some(0);
+        }
+        let text = self.filter_text.to_lowercase();
+        let query = query.to_lowercase();
+        if text.starts_with(&query) {
+            return Some(1);
+        }
+        let mut chars = text.chars();
+        query
+            .chars()
+            .all(|wanted| chars.any(|c| c == wanted))
+            .then_some(2)
+    }
+}
+
+fn kind_label(kind: u64) -> &'static str {
+    match kind {
+        2 => "method",
+        3 | 4 => "fn",
+        5 | 10 => "field",
+        6 => "local",
+        7 | 22 => "struct",
+        8 => "trait",
+        9 => "mod",
+        13 => "enum",
+        14 => "keyword",
+        18 => "reference",
+        20 => "variant",
+        21 => "const",
+        25 => "type param",
+        _ => "item",
+    }
+}
+
+/// Keep the candidates matching `query`, best matches first and otherwise
+/// in rust-analyzer's relevance order, capped at `limit`.
+pub fn rank(items: Vec<Completion>, query: &str, limit: usize) -> (Vec<Completion>, usize) {
+    let mut matching: Vec<(u8, Completion)> = items
+        .into_iter()
+        .filter_map(|item| Some((item.match_rank(query)?, item)))
+        .collect();
+    matching.sort_by(|(a_rank, a), (b_rank, b)| {
+        a_rank
+            .cmp(b_rank)
+            .then_with(|| a.sort_text.cmp(&b.sort_text))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    let total = matching.len();
+    matching.truncate(limit);
+    (matching.into_iter().map(|(_, item)| item).collect(), total)
+}
+
+pub fn format_completions(items: &[Completion], total: usize, query: &str) -> String {
+    if items.is_empty() {
+        return if query.is_empty() {
+            "No completions".to_string()
+        } else {
+            format!("No completions matching '{query}'")
+        };
+    }
+    let mut result = if total > items.len() {
+        format!("{} of {total} completions:\n", items.len())
+    } else {
+        format!("{total} completions:\n")
+    };
+    for item in items {
+        result.push_str(&format!("\n{} {}", item.kind, item.label));
+        if let Some(detail) = &item.detail {
+            result.push_str(&format!(" - {detail}"));
+        }
+        if let Some(documentation) = &item.documentation {
+            result.push_str(&format!("\n    {documentation}"));
+        }
+    }
+    result.push('\n');
+    result
+}
@@ -0,0 +1,611 @@
+// Offline crate lookup for dependency suggestions.
+// Candidates come from an optional crates.io index snapshot (a checkout of
+// the index repository pointed to by CRATES_INDEX_PATH), the sparse index
+// cache and the extracted sources under ~/.cargo/registry.
+
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+
+use crate::analyzer::{manifest, syntax};
+
+/// What we know about a crate without touching the network.
+#[derive(Debug, Clone)]
+pub struct CrateInfo {
+    pub name: String,
+    pub latest_version: String,
+    pub description: Option<String>,
+    pub keywords: Vec<String>,
+    pub features: Vec<String>,
+}
+
+pub struct CrateIndex {
+    snapshot: Option<PathBuf>,
+    cache_dirs: Vec<PathBuf>,
+    src_dirs: Vec<PathBuf>,
+}
+
+pub fn cargo_home() -> PathBuf {
+    std::env::var("CARGO_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            PathBuf::from(home).join(".cargo")
+        })
+}
+
+fn subdirectories(path: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Relative path of a crate inside a crates.io style index. Crate names
+/// are ASCII, so anything else can't be in the index.
+fn index_path(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || !name.is_ascii() {
+        return None;
+    }
+    let name = name.to_ascii_lowercase();
+    Some(match name.len() {
+        1 => PathBuf::from("1").join(&name),
+        2 => PathBuf::from("2").join(&name),
+        3 => PathBuf::from("3").join(&name[..1]).join(&name),
+        _ => PathBuf::from(&name[..2]).join(&name[2..4]).join(&name),
+    })
+}
+
+/// Split an extracted source directory name such as `tokio-util-0.7.10`.
+fn split_versioned_name(dir_name: &str) -> Option<(&str, &str)> {
+    dir_name.match_indices('-').find_map(|(index, _)| {
+        let version = &dir_name[index + 1..];
+        version
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit())
+            .then_some((&dir_name[..index], version))
+    })
+}
+
+/// Compare semver-like versions; pre-releases sort before releases.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parse(version: &str) -> (Vec<u64>, Option<&str>) {
+        let version = version.split('+').next().unwrap_or(version);
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+        let numbers = core
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect();
+        (numbers, pre)
+    }
+    let (a_numbers, a_pre) = parse(a);
+    let (b_numbers, b_pre) = parse(b);
+    a_numbers
+        .cmp(&b_numbers)
+        .then_with(|| match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(b),
+        })
+}
+
+/// Parse index entries: one JSON object per line in the snapshot format,
+/// or NUL separated `version\0json\0` records in the sparse cache format.
+fn parse_index_entries(bytes: &[u8]) -> Vec<Value> {
+    bytes
+        .split(|&byte| byte == b'\n' || byte == 0)
+        .filter(|chunk| chunk.first() == Some(&b'{'))
+        .filter_map(|chunk| serde_json::from_slice(chunk).ok())
+        .collect()
+}
+
+impl Default for CrateIndex {
+    fn default() -> Self {
+        Self::discover()
+    }
+}
+
+impl CrateIndex {
+    pub fn discover() -> Self {
+        let registry = cargo_home().join("registry");
+        let snapshot = std::env::var("CRATES_INDEX_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .filter(|path| path.is_dir());
+        let cache_dirs = subdirectories(&registry.join("index"))
+            .into_iter()
+            .map(|dir| dir.join(".cache"))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        let src_dirs = subdirectories(&registry.join("src"));
+        Self {
+            snapshot,
+            cache_dirs,
+            src_dirs,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshot.is_none() && self.cache_dirs.is_empty() && self.src_dirs.is_empty()
+    }
+
+    fn index_entries(&self, name: &str) -> Vec<Value> {
+        let Some(relative) = index_path(name) else {
+            return Vec::new();
+        };
+        self.snapshot
+            .iter()
+            .chain(&self.cache_dirs)
+            .map(|root| root.join(&relative))
+            .filter_map(|path| std::fs::read(path).ok())
+            .map(|bytes| parse_index_entries(&bytes))
+            .find(|entries| !entries.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Extracted source directories of `name`, newest version last.
+    fn source_dirs(&self, name: &str) -> Vec<(String, PathBuf)> {
+        let mut found = Vec::new();
+        for src in &self.src_dirs {
+            for dir in subdirectories(src) {
+                let dir_name = dir.file_name().unwrap_or_default().to_string_lossy();
+                if let Some((crate_name, version)) = split_versioned_name(&dir_name)
+                    && crate_name.eq_ignore_ascii_case(name)
+                {
+                    found.push((version.to_string(), dir.clone()));
+                }
+            }
+        }
+        found.sort_by(|a, b| compare_versions(&a.0, &b.0));
+        found
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<CrateInfo> {
+        let entries = self.index_entries(name);
+        let sources = self.source_dirs(name);
+
+        let latest_entry = entries
+            .iter()
+            .filter(|entry| !entry["yanked"].as_bool().unwrap_or(false))
+            .max_by(|a, b| {
+                let a = a["vers"].as_str().unwrap_or_default();
+                let b = b["vers"].as_str().unwrap_or_default();
+                // Prefer stable releases over newer pre-releases.
+                (!a.contains('-'))
+                    .cmp(&!b.contains('-'))
+                    .then_with(|| compare_versions(a, b))
+            });
+
+        let mut info = match (latest_entry, sources.last()) {
+            (Some(entry), _) => {
+                let mut features: BTreeSet<String> = BTreeSet::new();
+                for key in ["features", "features2"] {
+                    if let Some(map) = entry[key].as_object() {
+                        features.extend(map.keys().cloned());
+                    }
+                }
+                CrateInfo {
+                    name: entry["name"].as_str().unwrap_or(name).to_string(),
+                    latest_version: entry["vers"].as_str().unwrap_or_default().to_string(),
+                    description: None,
+                    keywords: Vec::new(),
+                    features: features.into_iter().collect(),
+                }
+            }
+            (None, Some((version, dir))) => {
+                let manifest = read_manifest(dir)?;
+                CrateInfo {
+                    name: manifest.name.unwrap_or_else(|| name.to_string()),
+                    latest_version: version.clone(),
+                    description: None,
+                    keywords: Vec::new(),
+                    features: manifest.features,
+                }
+            }
+            (None, None) => return None,
+        };
+
+        // Descriptions only live in the published manifests.
+        if let Some((_, dir)) = sources.last()
+            && let Some(manifest) = read_manifest(dir)
+        {
+            info.description = manifest.description;
+            info.keywords = manifest.keywords;
+        }
+        Some(info)
+    }
+
+    /// All crate names known locally.
+    fn known_names(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        for src in &self.src_dirs {
+            for dir in subdirectories(src) {
+                let dir_name = dir.file_name().unwrap_or_default().to_string_lossy();
+                if let Some((name, _)) = split_versioned_name(&dir_name) {
+                    names.insert(name.to_string());
+                }
+            }
+        }
+        for root in self.snapshot.iter().chain(&self.cache_dirs) {
+            collect_index_names(root, 0, &mut names);
+        }
+        names
+    }
+
+    /// Rank locally known crates against a free-text query.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<CrateInfo> {
+        let normalized = query.trim().to_lowercase().replace('_', "-");
+        let tokens: Vec<String> = normalized
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|token| token.len() > 1)
+            .map(str::to_string)
+            .collect();
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: BTreeMap<String, usize> = BTreeMap::new();
+        let described: BTreeMap<String, PathBuf> = self
+            .src_dirs
+            .iter()
+            .flat_map(|src| subdirectories(src))
+            .filter_map(|dir| {
+                let dir_name = dir.file_name()?.to_string_lossy().into_owned();
+                let (name, _) = split_versioned_name(&dir_name)?;
+                Some((name.to_string(), dir))
+            })
+            .collect();
+
+        for name in self.known_names() {
+            let lower = name.to_lowercase().replace('_', "-");
+            let mut score = 0;
+            if lower == normalized {
+                score += 100;
+            }
+            for token in &tokens {
+                if lower == *token {
+                    score += 20;
+                } else if lower.split('-').any(|part| part == token) {
+                    score += 10;
+                } else if lower.contains(token.as_str()) {
+                    score += 4;
+                }
+            }
+            if let Some(dir) = described.get(&name)
+                && let Some(manifest) = read_manifest(dir)
+            {
+                let description = manifest.description.unwrap_or_default().to_lowercase();
+                for token in &tokens {
+                    if manifest.keywords.iter().any(|keyword| keyword == token) {
+                        score += 5;
+                    }
+                    if description.contains(token.as_str()) {
+                        score += 3;
+                    }
+                }
+            }
+            if score > 0 {
+                scored.insert(name, score);
+            }
+        }
+
+        let mut ranked: Vec<(String, usize)> = scored.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .filter_map(|(name, _)| self.lookup(&name))
+            .collect()
+    }
+}
+
+fn collect_index_names(dir: &Path, depth: usize, names: &mut BTreeSet<String>) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if file_name.starts_with('.') || file_name == "config.json" {
+            continue;
+        }
+        if path.is_dir() {
+            if depth < 2 {
+                collect_index_names(&path, depth + 1, names);
+            }
+        } else if depth > 0 {
+            names.insert(file_name);
+        }
+    }
+}
+
+struct PublishedManifest {
+    name: Option<String>,
+    description: Option<String>,
+    keywords: Vec<String>,
+    features: Vec<String>,
+}
+
+fn read_manifest(dir: &Path) -> Option<PublishedManifest> {
+    let text = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    let document: toml_edit::DocumentMut = text.parse().ok()?;
+    let package = document.get("package");
+    let string = |key: &str| {
+        package
+            .and_then(|package| package.get(key))
+            .and_then(|value| value.as_str())
+            .map(|value| value.split_whitespace().collect::<Vec<_>>().join(" "))
+    };
+    let keywords = package
+        .and_then(|package| package.get("keywords"))
+        .and_then(|value| value.as_array())
+        .map(|array| {
+            array
+                .iter()
+                .filter_map(|value| value.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    let features = document
+        .get("features")
+        .and_then(|features| features.as_table_like())
+        .map(|table| table.iter().map(|(key, _)| key.to_string()).collect())
+        .unwrap_or_default();
+
+    Some(PublishedManifest {
+        name: string("name"),
+        description: string("description"),
+        keywords,
+        features,
+    })
+}
+
+/// Crate names referenced by compiler diagnostics such as
+/// "use of undeclared crate or module `regex`".
+pub fn crates_from_diagnostic(message: &str) -> Option<String> {
+    const PATTERNS: &[&str] = &[
+        "use of undeclared crate or module `",
+        "use of unresolved module or unlinked crate `",
+        "unresolved import `",
+        "can't find crate for `",
+        "unlinked crate `",
+    ];
+    PATTERNS.iter().find_map(|pattern| {
+        let start = message.find(pattern)? + pattern.len();
+        let name = message[start..].split('`').next()?;
+        let first = name.split("::").next()?.trim();
+        (!first.is_empty()).then(|| first.to_string())
+    })
+}
+
+const BUILTIN_ROOTS: &[&str] = &[
+    "std",
+    "core",
+    "alloc",
+    "proc_macro",
+    "test",
+    "crate",
+    "self",
+    "super",
+    "Self",
+];
+
+pub fn rust_files(dir: &Path, package_root: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_dir() {
+            let nested_package = path != package_root && path.join("Cargo.toml").is_file();
+            if !name.starts_with('.') && name != "target" && !nested_package {
+                rust_files(&path, package_root, files);
+            }
+        } else if name.ends_with(".rs") {
+            files.push(path);
+        }
+    }
+}
+
+/// Rust files of every package under `workspace`, sorted.
+pub fn workspace_rust_files(workspace: &Path) -> Vec<PathBuf> {
+    let mut packages = Vec::new();
+    package_dirs(workspace, 3, &mut packages);
+    let mut files = Vec::new();
+    for package in packages {
+        rust_files(&package, &package, &mut files);
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Crate names (as written in paths) of the packages under `workspace`.
+pub fn workspace_crate_names(workspace: &Path) -> Vec<String> {
+    let mut packages = Vec::new();
+    package_dirs(workspace, 3, &mut packages);
+    packages
+        .iter()
+        .filter_map(|package| syntax::package_name(package))
+        .map(|name| name.replace('-', "_"))
+        .collect()
+}
+
+fn package_dirs(dir: &Path, depth: usize, packages: &mut Vec<PathBuf>) {
+    if dir.join("Cargo.toml").is_file() {
+        packages.push(dir.to_path_buf());
+    }
+    if depth == 0 {
+        return;
+    }
+    for sub in subdirectories(dir) {
+        let name = sub.file_name().unwrap_or_default().to_string_lossy();
+        if !name.starts_with('.') && name != "target" {
+            package_dirs(&sub, depth - 1, packages);
+        }
+    }
+}
+
+/// Names declared by items in `masked` source, which may shadow crate
+/// names in 2018-edition paths.
+fn declared_item_names(masked: &str) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for keyword in [
+        "mod", "enum", "struct", "trait", "type", "union", "fn", "const", "static",
+    ] {
+        for index in syntax::find_word(masked, keyword) {
+            let rest = masked[index + keyword.len()..].trim_start();
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            if !name.is_empty() {
+                names.insert(name);
+            }
+        }
+    }
+    names
+}
+
+/// Roots a path can start with without naming a missing crate: the builtin
+/// roots, the package's own crate names, its declared dependencies and the
+/// items declared in its (masked) sources.
+fn known_names<'a>(
+    document: &DocumentMut,
+    masked_sources: impl IntoIterator<Item = &'a str>,
+) -> BTreeSet<String> {
+    let mut known = manifest::declared_dependency_names(document);
+    known.extend(BUILTIN_ROOTS.iter().map(|root| root.to_string()));
+    for key in ["package", "lib"] {
+        if let Some(name) = document
+            .get(key)
+            .and_then(|table| table.get("name"))
+            .and_then(|name| name.as_str())
+        {
+            known.insert(name.replace('-', "_"));
+        }
+    }
+    for masked in masked_sources {
+        known.extend(declared_item_names(masked));
+    }
+    known
+}
+
+/// `known_names` for the package whose manifest is in `package`; only the
+/// builtin roots if it has none.
+pub fn known_roots(package: &Path) -> BTreeSet<String> {
+    let Ok(document) = manifest::read_document(&package.join("Cargo.toml")) else {
+        return BUILTIN_ROOTS.iter().map(|root| root.to_string()).collect();
+    };
+    let mut files = Vec::new();
+    rust_files(package, package, &mut files);
+    let masked: Vec<String> = files
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|text| syntax::mask_comments_and_strings(&text))
+        .collect();
+    known_names(&document, masked.iter().map(String::as_str))
+}
+
+/// Crates referenced from `use` and `extern crate` items in the workspace
+/// that no manifest declares, mapped to the files referencing them.
+pub fn unresolved_crate_references(workspace: &Path) -> BTreeMap<String, BTreeSet<PathBuf>> {
+    let mut unresolved: BTreeMap<String, BTreeSet<PathBuf>> = BTreeMap::new();
+    let mut packages = Vec::new();
+    package_dirs(workspace, 3, &mut packages);
+
+    for package in packages {
+        let Ok(document) = manifest::read_document(&package.join("Cargo.toml")) else {
+            continue;
+        };
+        if document.get("package").is_none() {
+            continue;
+        }
+        let mut files = Vec::new();
+        rust_files(&package, &package, &mut files);
+        let masked: Vec<(PathBuf, String, String)> = files
+            .into_iter()
+            .filter_map(|path| {
+                let text = std::fs::read_to_string(&path).ok()?;
+                let masked = syntax::mask_comments_and_strings(&text);
+                Some((path, text, masked))
+            })
+            .collect();
+        let known = known_names(
+            &document,
+            masked.iter().map(|(_, _, masked)| masked.as_str()),
+        );
+
+        for (path, text, masked) in &masked {
+            let mut roots = Vec::new();
+            for statement in syntax::use_statements(text) {
+                for leaf in statement.tree.leaves() {
+                    if let Some(first) = leaf.path.first() {
+                        roots.push(first.trim_start_matches("::").to_string());
+                    }
+                }
+            }
+            for index in syntax::find_word(masked, "extern") {
+                let rest = masked[index + 6..].trim_start();
+                if let Some(rest) = rest.strip_prefix("crate") {
+                    let name: String = rest
+                        .trim_start()
+                        .chars()
+                        .take_while(|c| c.is_alphanumeric() || *c == '_')
+                        .collect();
+                    roots.push(name);
+                }
+            }
+            for root in roots {
+                if root.is_empty() || known.contains(&root) {
+                    continue;
+                }
+                unresolved.entry(root).or_default().insert(path.clone());
+            }
+        }
+    }
+
+    unresolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_paths_follow_the_crates_io_layout() {
+        assert_eq!(index_path("a"), Some(PathBuf::from("1/a")));
+        assert_eq!(index_path("Syn"), Some(PathBuf::from("3/s/syn")));
+        assert_eq!(index_path("serde"), Some(PathBuf::from("se/rd/serde")));
+        assert_eq!(index_path("éa"), None);
+        assert_eq!(index_path(""), None);
+    }
+
+    #[test]
+    fn known_names_cover_builtin_roots_dependencies_and_local_items() {
+        let document: DocumentMut =
+            "[package]\nname = \"my-app\"\n\n[dependencies]\nserde-json = \"1\"\n"
+                .parse()
+                .unwrap();
+        let known = known_names(&document, ["mod parser;\nfn main() {}"]);
+        for name in [
+            "crate",
+            "self",
+            "super",
+            "std",
+            "my_app",
+            "serde_json",
+            "parser",
+        ] {
+            assert!(known.contains(name), "{name}");
+        }
+        assert!(!known.contains("regex"));
+    }
+}
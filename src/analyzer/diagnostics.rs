@@ -0,0 +1,308 @@
+// Workspace-wide diagnostics: rust-analyzer's published diagnostics and
+// `cargo check` messages merged into one deduplicated, sorted list, with a
+// diff between successive snapshots.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::analyzer::cargo::BuildReport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Severity {
+    /// From an LSP `DiagnosticSeverity` (missing means error).
+    pub fn from_lsp(severity: Option<u64>) -> Self {
+        match severity {
+            Some(2) => Self::Warning,
+            Some(3) => Self::Information,
+            Some(4) => Self::Hint,
+            _ => Self::Error,
+        }
+    }
+
+    /// From a rustc message level.
+    pub fn from_level(level: &str) -> Option<Self> {
+        match level {
+            "error" | "error: internal compiler error" => Some(Self::Error),
+            "warning" => Some(Self::Warning),
+            "note" | "help" => Some(Self::Information),
+            _ => None,
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Self::Error),
+            "warning" => Some(Self::Warning),
+            "information" | "info" => Some(Self::Information),
+            "hint" => Some(Self::Hint),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Information => "info",
+            Self::Hint => "hint",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    /// 1-based line and column.
+    pub line: u64,
+    pub column: u64,
+    pub severity: Severity,
+    pub code: Option<String>,
+    /// First line of the message.
+    pub message: String,
+    /// `rust-analyzer`, `rustc` or `clippy`.
+    pub source: String,
+}
+
+impl Diagnostic {
+    pub fn from_lsp(path: &Path, diagnostic: &Value) -> Self {
+        let start = &diagnostic["range"]["start"];
+        let code = match &diagnostic["code"] {
+            Value::String(code) => Some(code.clone()),
+            Value::Number(code) => Some(code.to_string()),
+            _ => None,
+        };
+        Self {
+            path: path.to_path_buf(),
+            line: start["line"].as_u64().unwrap_or(0) + 1,
+            column: start["character"].as_u64().unwrap_or(0) + 1,
+            severity: Severity::from_lsp(diagnostic["severity"].as_u64()),
+            code,
+            message: first_line(diagnostic["message"].as_str().unwrap_or_default()),
+            source: diagnostic["source"]
+                .as_str()
+                .unwrap_or("rust-analyzer")
+                .to_string(),
+        }
+    }
+
+    /// Identity used for deduplication: rust-analyzer republishes
+    /// `cargo check` results, and both report some errors natively.
+    fn key(&self) -> (PathBuf, u64, Severity, String) {
+        (
+            self.path.clone(),
+            self.line,
+            self.severity,
+            self.message.clone(),
+        )
+    }
+
+    /// Identity across snapshots, ignoring positions that shift when code
+    /// above the diagnostic changes.
+    fn snapshot_key(&self) -> (&Path, Severity, Option<&str>, &str) {
+        (
+            &self.path,
+            self.severity,
+            self.code.as_deref(),
+            &self.message,
+        )
+    }
+
+    pub fn render(&self, root: &Path, with_path: bool) -> String {
+        let mut text = String::new();
+        if with_path {
+            text.push_str(&format!(
+                "{}:",
+                self.path.strip_prefix(root).unwrap_or(&self.path).display()
+            ));
+        }
+        text.push_str(&format!(
+            "{}:{} {}",
+            self.line,
+            self.column,
+            self.severity.label()
+        ));
+        if let Some(code) = &self.code {
+            text.push_str(&format!("[{code}]"));
+        }
+        text.push_str(&format!(" {} ({})", self.message, self.source));
+        text
+    }
+}
+
+fn first_line(message: &str) -> String {
+    message
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// Run `cargo check` on every target of the workspace and collect its
+/// messages with a primary span.
+pub async fn cargo_check(workspace: &Path) -> Result<Vec<Diagnostic>> {
+    let output = Command::new("cargo")
+        .args([
+            "check",
+            "--workspace",
+            "--all-targets",
+            "--message-format",
+            "json",
+        ])
+        .current_dir(workspace)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+    let report = BuildReport::parse(&String::from_utf8_lossy(&output.stdout));
+    if !output.status.success() && report.messages.is_empty() {
+        return Err(anyhow::anyhow!(
+            "cargo check failed:\n{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let mut diagnostics = Vec::new();
+    for message in report.messages {
+        let (Some(location), Some(severity)) =
+            (&message.location, Severity::from_level(&message.level))
+        else {
+            continue;
+        };
+        let mut parts = location.rsplitn(3, ':');
+        let column = parts.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+        let line = parts.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+        let file = parts.next().unwrap_or_default();
+        let source = match &message.code {
+            Some(code) if code.starts_with("clippy::") => "clippy",
+            _ => "rustc",
+        };
+        diagnostics.push(Diagnostic {
+            path: workspace.join(file),
+            line,
+            column,
+            severity,
+            code: message.code.clone(),
+            message: first_line(&message.message),
+            source: source.to_string(),
+        });
+    }
+    Ok(diagnostics)
+}
+
+/// Sort and deduplicate, keeping the first source to report a diagnostic.
+pub fn normalize(mut diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    diagnostics.sort();
+    let mut seen = BTreeSet::new();
+    diagnostics.retain(|diagnostic| seen.insert(diagnostic.key()));
+    diagnostics
+}
+
+/// Diagnostics in `current` but not `previous` and the other way around,
+/// matched as multisets so that unrelated line shifts don't count.
+pub fn diff<'a>(
+    previous: &'a [Diagnostic],
+    current: &'a [Diagnostic],
+) -> (Vec<&'a Diagnostic>, Vec<&'a Diagnostic>) {
+    let unmatched = |from: &'a [Diagnostic], against: &'a [Diagnostic]| {
+        let mut available: BTreeMap<_, usize> = BTreeMap::new();
+        for diagnostic in against {
+            *available.entry(diagnostic.snapshot_key()).or_default() += 1;
+        }
+        from.iter()
+            .filter(
+                |diagnostic| match available.get_mut(&diagnostic.snapshot_key()) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+            )
+            .collect::<Vec<_>>()
+    };
+    (unmatched(current, previous), unmatched(previous, current))
+}
+
+fn severity_counts<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> String {
+    let mut counts: BTreeMap<Severity, usize> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        *counts.entry(diagnostic.severity).or_default() += 1;
+    }
+    counts
+        .iter()
+        .map(|(severity, count)| {
+            format!(
+                "{count} {}{}",
+                severity.label(),
+                if *count == 1 { "" } else { "s" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn format_snapshot(
+    root: &Path,
+    diagnostics: &[Diagnostic],
+    previous: Option<&[Diagnostic]>,
+    sources: &[&str],
+) -> String {
+    let files: BTreeSet<&Path> = diagnostics.iter().map(|d| d.path.as_path()).collect();
+    let mut result = if diagnostics.is_empty() {
+        format!("No diagnostics ({})\n", sources.join(", "))
+    } else {
+        format!(
+            "{} in {} file{} ({})\n",
+            severity_counts(diagnostics),
+            files.len(),
+            if files.len() == 1 { "" } else { "s" },
+            sources.join(", ")
+        )
+    };
+
+    match previous {
+        Some(previous) => {
+            let (introduced, fixed) = diff(previous, diagnostics);
+            if introduced.is_empty() && fixed.is_empty() {
+                result.push_str("\nUnchanged since the previous snapshot\n");
+            } else {
+                result.push_str(&format!(
+                    "\nSince the previous snapshot: {} introduced, {} fixed\n",
+                    introduced.len(),
+                    fixed.len()
+                ));
+                for diagnostic in introduced {
+                    result.push_str(&format!("  + {}\n", diagnostic.render(root, true)));
+                }
+                for diagnostic in fixed {
+                    result.push_str(&format!("  - {}\n", diagnostic.render(root, true)));
+                }
+            }
+        }
+        None => result.push_str("\nFirst snapshot; later calls report what changed\n"),
+    }
+
+    for file in files {
+        let in_file: Vec<&Diagnostic> = diagnostics.iter().filter(|d| d.path == file).collect();
+        result.push_str(&format!(
+            "\n{} ({})\n",
+            file.strip_prefix(root).unwrap_or(file).display(),
+            severity_counts(in_file.iter().copied())
+        ));
+        for diagnostic in in_file {
+            result.push_str(&format!("  {}\n", diagnostic.render(root, false)));
+        }
+    }
+    result
+}
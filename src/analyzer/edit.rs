@@ -0,0 +1,408 @@
+// Text and workspace edit utilities shared by the refactoring tools.
+// LSP positions are UTF-16 based, so everything here converts between
+// those positions and byte offsets before touching file contents.
+
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A replacement of a byte range in a single file.
+#[derive(Debug, Clone)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, new_text: impl Into<String>) -> Self {
+        Self {
+            range,
+            new_text: new_text.into(),
+        }
+    }
+
+    pub fn insert(offset: usize, new_text: impl Into<String>) -> Self {
+        Self::new(offset..offset, new_text)
+    }
+
+    pub fn delete(range: Range<usize>) -> Self {
+        Self::new(range, String::new())
+    }
+}
+
+/// The outcome of applying an edit to one file.
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+pub fn uri_to_path(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    PathBuf::from(percent_decode(path))
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+            && let Ok(byte) = u8::from_str_radix(&input[i + 1..i + 3], 16)
+        {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Resolve a user supplied path against the current directory.
+pub fn absolute_path(file_path: &str) -> Result<PathBuf> {
+    let path = Path::new(file_path);
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+/// Convert an LSP `Position` (line + UTF-16 column) into a byte offset.
+pub fn position_to_offset(text: &str, position: &Value) -> usize {
+    let line = position.get("line").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    let character = position
+        .get("character")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as usize;
+
+    let mut line_start = 0;
+    for _ in 0..line {
+        match text[line_start..].find('\n') {
+            Some(index) => line_start += index + 1,
+            None => return text.len(),
+        }
+    }
+
+    let line_end = text[line_start..]
+        .find('\n')
+        .map(|index| line_start + index)
+        .unwrap_or(text.len());
+    let mut units = 0;
+    for (index, ch) in text[line_start..line_end].char_indices() {
+        if units >= character {
+            return line_start + index;
+        }
+        units += ch.len_utf16();
+    }
+    line_end
+}
+
+/// Convert a byte offset into an LSP `Position`.
+pub fn offset_to_position(text: &str, offset: usize) -> Value {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    json!({ "line": line, "character": character })
+}
+
+/// Convert an LSP `Range` into a byte range.
+pub fn lsp_range_to_offsets(text: &str, range: &Value) -> Range<usize> {
+    let start = position_to_offset(text, &range["start"]);
+    let end = position_to_offset(text, &range["end"]);
+    start..end.max(start)
+}
+
+/// Convert a byte range into an LSP `Range`.
+pub fn offsets_to_lsp_range(text: &str, range: &Range<usize>) -> Value {
+    json!({
+        "start": offset_to_position(text, range.start),
+        "end": offset_to_position(text, range.end)
+    })
+}
+
+/// Apply a set of non-overlapping edits to `text`.
+pub fn apply_text_edits(text: &str, edits: &[TextEdit]) -> Result<String> {
+    let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+    // Stable sort keeps insertions at the same offset in their given order.
+    sorted.sort_by_key(|edit| (edit.range.start, edit.range.end));
+
+    let mut result = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for edit in sorted {
+        if edit.range.start < cursor {
+            return Err(anyhow::anyhow!(
+                "Overlapping edits at byte offset {}",
+                edit.range.start
+            ));
+        }
+        if edit.range.end > text.len() {
+            return Err(anyhow::anyhow!(
+                "Edit range {:?} is outside of the document",
+                edit.range
+            ));
+        }
+        result.push_str(&text[cursor..edit.range.start]);
+        result.push_str(&edit.new_text);
+        cursor = edit.range.end;
+    }
+    result.push_str(&text[cursor..]);
+    Ok(result)
+}
+
+/// Convert LSP `TextEdit` objects into byte based edits for `text`.
+pub fn lsp_text_edits(text: &str, edits: &[Value]) -> Vec<TextEdit> {
+    edits
+        .iter()
+        .map(|edit| {
+            let new_text = edit
+                .get("newText")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            TextEdit::new(lsp_range_to_offsets(text, &edit["range"]), new_text)
+        })
+        .collect()
+}
+
+/// Compute the file changes described by an LSP `WorkspaceEdit` without
+/// writing anything to disk.
+pub fn preview_workspace_edit(edit: &Value) -> Result<Vec<FileChange>> {
+    let mut contents: BTreeMap<PathBuf, Option<String>> = BTreeMap::new();
+    let mut originals: BTreeMap<PathBuf, Option<String>> = BTreeMap::new();
+
+    fn load(
+        path: &Path,
+        contents: &mut BTreeMap<PathBuf, Option<String>>,
+        originals: &mut BTreeMap<PathBuf, Option<String>>,
+    ) -> Result<()> {
+        if !contents.contains_key(path) {
+            let text = if path.exists() {
+                Some(std::fs::read_to_string(path)?)
+            } else {
+                None
+            };
+            originals.insert(path.to_path_buf(), text.clone());
+            contents.insert(path.to_path_buf(), text);
+        }
+        Ok(())
+    }
+
+    fn apply_edits(
+        uri: &str,
+        edits: &[Value],
+        contents: &mut BTreeMap<PathBuf, Option<String>>,
+        originals: &mut BTreeMap<PathBuf, Option<String>>,
+    ) -> Result<()> {
+        let path = uri_to_path(uri);
+        load(&path, contents, originals)?;
+        let current = contents
+            .get(&path)
+            .cloned()
+            .flatten()
+            .ok_or_else(|| anyhow::anyhow!("Cannot edit missing file {}", path.display()))?;
+        let updated = apply_text_edits(&current, &lsp_text_edits(&current, edits))?;
+        contents.insert(path, Some(updated));
+        Ok(())
+    }
+
+    if let Some(document_changes) = edit.get("documentChanges").and_then(|v| v.as_array()) {
+        for change in document_changes {
+            match change.get("kind").and_then(|v| v.as_str()) {
+                Some("create") => {
+                    let path = uri_to_path(change["uri"].as_str().unwrap_or_default());
+                    load(&path, &mut contents, &mut originals)?;
+                    let overwrite = change["options"]["overwrite"].as_bool().unwrap_or(false);
+                    if contents[&path].is_none() || overwrite {
+                        contents.insert(path, Some(String::new()));
+                    }
+                }
+                Some("rename") => {
+                    let old_path = uri_to_path(change["oldUri"].as_str().unwrap_or_default());
+                    let new_path = uri_to_path(change["newUri"].as_str().unwrap_or_default());
+                    load(&old_path, &mut contents, &mut originals)?;
+                    load(&new_path, &mut contents, &mut originals)?;
+                    let text = contents.insert(old_path, None).flatten();
+                    contents.insert(new_path, text);
+                }
+                Some("delete") => {
+                    let path = uri_to_path(change["uri"].as_str().unwrap_or_default());
+                    load(&path, &mut contents, &mut originals)?;
+                    contents.insert(path, None);
+                }
+                _ => {
+                    let uri = change["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let edits = change["edits"].as_array().cloned().unwrap_or_default();
+                    apply_edits(uri, &edits, &mut contents, &mut originals)?;
+                }
+            }
+        }
+    } else if let Some(changes) = edit.get("changes").and_then(|v| v.as_object()) {
+        for (uri, edits) in changes {
+            let edits = edits.as_array().cloned().unwrap_or_default();
+            apply_edits(uri, &edits, &mut contents, &mut originals)?;
+        }
+    }
+
+    Ok(contents
+        .into_iter()
+        .filter_map(|(path, new_text)| {
+            let old_text = originals.remove(&path).flatten();
+            (old_text != new_text).then_some(FileChange {
+                path,
+                old_text,
+                new_text,
+            })
+        })
+        .collect())
+}
+
+/// The LSP text edits of a `WorkspaceEdit` grouped by file, or `None` if it
+/// also creates, renames or deletes files.
+pub fn workspace_text_edits(edit: &Value) -> Option<BTreeMap<PathBuf, Vec<Value>>> {
+    let mut edits: BTreeMap<PathBuf, Vec<Value>> = BTreeMap::new();
+    if let Some(document_changes) = edit.get("documentChanges").and_then(|v| v.as_array()) {
+        for change in document_changes {
+            if change.get("kind").is_some() {
+                return None;
+            }
+            let uri = change["textDocument"]["uri"].as_str()?;
+            edits
+                .entry(uri_to_path(uri))
+                .or_default()
+                .extend(change["edits"].as_array().cloned().unwrap_or_default());
+        }
+    } else if let Some(changes) = edit.get("changes").and_then(|v| v.as_object()) {
+        for (uri, file_edits) in changes {
+            edits
+                .entry(uri_to_path(uri))
+                .or_default()
+                .extend(file_edits.as_array().cloned().unwrap_or_default());
+        }
+    }
+    Some(edits)
+}
+
+/// Render a change as a unified diff with three lines of context.
+pub fn unified_diff(change: &FileChange) -> String {
+    let path = change.path.display().to_string();
+    let header = |text: &Option<String>| {
+        if text.is_some() {
+            path.clone()
+        } else {
+            "/dev/null".to_string()
+        }
+    };
+    similar::TextDiff::from_lines(
+        change.old_text.as_deref().unwrap_or_default(),
+        change.new_text.as_deref().unwrap_or_default(),
+    )
+    .unified_diff()
+    .context_radius(3)
+    .header(&header(&change.old_text), &header(&change.new_text))
+    .to_string()
+}
+
+/// Write previously computed file changes to disk.
+pub fn write_file_changes(changes: &[FileChange]) -> Result<()> {
+    for change in changes {
+        match &change.new_text {
+            Some(text) => {
+                if let Some(parent) = change.path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&change.path, text)?;
+            }
+            None => {
+                if change.path.exists() {
+                    std::fs::remove_file(&change.path)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `😀` is four bytes in UTF-8 and two UTF-16 code units.
+    const TEXT: &str = "a😀b\nx";
+
+    #[test]
+    fn positions_count_utf16_units() {
+        let position = |line: u64, character: u64| json!({ "line": line, "character": character });
+        assert_eq!(position_to_offset(TEXT, &position(0, 1)), 1);
+        assert_eq!(position_to_offset(TEXT, &position(0, 3)), 5);
+        // A position inside the surrogate pair moves past the character.
+        assert_eq!(position_to_offset(TEXT, &position(0, 2)), 5);
+        assert_eq!(position_to_offset(TEXT, &position(0, 9)), 6);
+        assert_eq!(position_to_offset(TEXT, &position(1, 1)), 8);
+        assert_eq!(position_to_offset(TEXT, &position(4, 0)), TEXT.len());
+
+        assert_eq!(offset_to_position(TEXT, 5), position(0, 3));
+        assert_eq!(offset_to_position(TEXT, 7), position(1, 0));
+        assert_eq!(offset_to_position(TEXT, 100), position(1, 1));
+        let range = offsets_to_lsp_range(TEXT, &(1..5));
+        assert_eq!(lsp_range_to_offsets(TEXT, &range), 1..5);
+    }
+
+    #[test]
+    fn applies_edits_in_offset_order() {
+        let edits = [
+            TextEdit::new(5..6, "B"),
+            TextEdit::insert(0, "<"),
+            TextEdit::insert(0, ">"),
+            TextEdit::delete(1..5),
+        ];
+        assert_eq!(apply_text_edits(TEXT, &edits).unwrap(), "<>aB\nx");
+    }
+
+    #[test]
+    fn rejects_overlapping_and_out_of_range_edits() {
+        let overlapping = [TextEdit::new(0..5, "x"), TextEdit::new(1..6, "y")];
+        let error = apply_text_edits(TEXT, &overlapping).unwrap_err();
+        assert_eq!(error.to_string(), "Overlapping edits at byte offset 1");
+
+        let inside = [TextEdit::delete(0..6), TextEdit::insert(3, "z")];
+        assert!(apply_text_edits(TEXT, &inside).is_err());
+
+        let outside = [TextEdit::insert(TEXT.len() + 1, "z")];
+        assert!(apply_text_edits(TEXT, &outside).is_err());
+    }
+
+    #[test]
+    fn converts_uris_and_workspace_edits() {
+        let path = uri_to_path("file:///tmp/a%20b%2Fc.rs");
+        assert_eq!(path, PathBuf::from("/tmp/a b/c.rs"));
+        assert_eq!(
+            uri_to_path(&path_to_uri(Path::new("/tmp/x.rs"))),
+            Path::new("/tmp/x.rs")
+        );
+
+        let edit = json!({
+            "changes": {
+                "file:///tmp/x.rs": [
+                    { "range": offsets_to_lsp_range(TEXT, &(5..6)), "newText": "c" }
+                ]
+            }
+        });
+        let edits = workspace_text_edits(&edit).unwrap();
+        let lsp_edits = lsp_text_edits(TEXT, &edits[Path::new("/tmp/x.rs")]);
+        assert_eq!(apply_text_edits(TEXT, &lsp_edits).unwrap(), "a😀c\nx");
+    }
+}
@@ -0,0 +1,197 @@
+// Macro expansion helpers: formatting rust-analyzer's expansions with
+// rustfmt, and whole-module expansion through `rustc -Zunpretty=expanded`
+// (what `cargo expand` does) when a nightly toolchain is installed.
+
+use anyhow::Result;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::analyzer::syntax;
+
+/// Format an expansion with rustfmt. Expansions that aren't a list of items
+/// (e.g. an expression macro) are formatted as a function body. Returns
+/// `None` if rustfmt can't make sense of it either way.
+pub async fn rustfmt(text: &str) -> Option<String> {
+    if let Some(formatted) = run_rustfmt(text).await {
+        return Some(formatted);
+    }
+    let wrapped = run_rustfmt(&format!("fn __expansion() {{\n{text}\n}}\n")).await?;
+    let body: Vec<&str> = wrapped
+        .lines()
+        .skip(1)
+        .take_while(|line| *line != "}")
+        .map(|line| line.strip_prefix("    ").unwrap_or(line))
+        .collect();
+    Some(body.join("\n"))
+}
+
+async fn run_rustfmt(text: &str) -> Option<String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2024", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdin = child.stdin.take()?;
+    stdin.write_all(text.as_bytes()).await.ok()?;
+    drop(stdin);
+    let output = child.wait_with_output().await.ok()?;
+    output.status.success().then(|| {
+        String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string()
+    })
+}
+
+/// Toolchain arguments for running cargo on nightly in `dir`: nothing if
+/// the active toolchain already is nightly, `+nightly` if rustup has one
+/// installed, `None` otherwise.
+pub async fn nightly_toolchain(dir: &Path) -> Option<Vec<String>> {
+    let active = Command::new("rustc")
+        .arg("-V")
+        .current_dir(dir)
+        .output()
+        .await
+        .ok()?;
+    let version = String::from_utf8_lossy(&active.stdout);
+    if version.contains("nightly") || version.contains("-dev") {
+        return Some(Vec::new());
+    }
+    let installed = Command::new("rustup")
+        .args(["run", "nightly", "rustc", "-V"])
+        .current_dir(dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .ok()?;
+    installed.success().then(|| vec!["+nightly".to_string()])
+}
+
+/// Cargo target selection (`--lib`, `--bin x`, ...) for the crate target
+/// containing `file`, plus the module path of the file inside it.
+pub fn target_for_file(crate_dir: &Path, file: &Path) -> (Vec<String>, Vec<String>) {
+    let package = syntax::package_name(crate_dir).unwrap_or_default();
+    let components: Vec<String> = file
+        .strip_prefix(crate_dir)
+        .map(|relative| {
+            relative
+                .iter()
+                .map(|component| component.to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    let stem = |name: &str| name.strip_suffix(".rs").unwrap_or(name).to_string();
+    let target = |kind: &str, name: String| vec![format!("--{kind}"), name];
+
+    let segments = syntax::module_location(file)
+        .map(|location| location.segments)
+        .unwrap_or_default();
+    match components
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["src", "bin", name, ..] => (
+            target("bin", stem(name)),
+            segments.into_iter().skip(2).collect(),
+        ),
+        ["src", "main.rs"] => (target("bin", package), Vec::new()),
+        ["examples", name, ..] => (target("example", stem(name)), Vec::new()),
+        ["tests", name, ..] => (target("test", stem(name)), Vec::new()),
+        ["benches", name, ..] => (target("bench", stem(name)), Vec::new()),
+        _ if crate_dir.join("src/lib.rs").is_file() => (vec!["--lib".to_string()], segments),
+        _ => (target("bin", package), segments),
+    }
+}
+
+/// Expand a whole crate target with `rustc -Zunpretty=expanded`.
+pub async fn expand_target(
+    crate_dir: &Path,
+    toolchain: &[String],
+    target: &[String],
+) -> Result<String> {
+    let output = Command::new("cargo")
+        .args(toolchain)
+        .arg("rustc")
+        .args(target)
+        .args(["--profile=check", "--", "-Zunpretty=expanded"])
+        .current_dir(crate_dir)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "rustc -Zunpretty=expanded failed:\n{}",
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Cut the module at `segments` out of a pretty-printed crate.
+pub fn extract_module(expanded: &str, segments: &[String]) -> Option<String> {
+    let masked = syntax::mask_comments_and_strings(expanded);
+    let mut start = 0;
+    let mut body = 0..expanded.len();
+    for segment in segments {
+        let text = &masked[body.clone()];
+        let (found, open) = syntax::find_word(text, "mod")
+            .into_iter()
+            .filter(|&index| syntax::brace_depth(text, index) == 0)
+            .find_map(|index| {
+                let rest = text[index + 3..]
+                    .trim_start()
+                    .strip_prefix(segment.as_str())?;
+                if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+                    || !rest.trim_start().starts_with('{')
+                {
+                    return None;
+                }
+                Some((index, text.len() - rest.trim_start().len()))
+            })?;
+        start = syntax::line_start(expanded, body.start + found);
+        let open = body.start + open;
+        body = open + 1..syntax::matching_brace(&masked, open)?;
+    }
+    let end = if segments.is_empty() {
+        expanded.len()
+    } else {
+        body.end + 1
+    };
+    Some(expanded[start..end].to_string())
+}
+
+/// `cargo expand`-style expansion of the module defined by `file`.
+pub async fn expand_file_module(file: &Path) -> Result<String> {
+    let crate_dir = syntax::find_manifest_dir(file)
+        .ok_or_else(|| anyhow::anyhow!("No Cargo.toml found above {}", file.display()))?;
+    let toolchain = nightly_toolchain(&crate_dir).await.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Whole-module expansion needs a nightly toolchain (rustup toolchain install nightly)"
+        )
+    })?;
+    let (target, segments) = target_for_file(&crate_dir, file);
+    let expanded = expand_target(&crate_dir, &toolchain, &target).await?;
+    let module = extract_module(&expanded, &segments).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Module {} not found in the expanded output",
+            segments.join("::")
+        )
+    })?;
+    Ok(format!(
+        "Expanded {} ({}) with rustc -Zunpretty=expanded:\n\n{}",
+        if segments.is_empty() {
+            "crate root".to_string()
+        } else {
+            format!("module {}", segments.join("::"))
+        },
+        target.join(" "),
+        module.trim_end()
+    ))
+}
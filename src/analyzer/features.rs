@@ -0,0 +1,325 @@
+// Cargo feature graph helpers: reading `[features]` together with the
+// implicit features of optional dependencies, resolving what a feature
+// transitively turns on, and editing feature definitions in place.
+
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use toml_edit::{DocumentMut, Item, value};
+
+use crate::analyzer::manifest;
+
+/// The feature table of a package as cargo sees it.
+#[derive(Debug, Default)]
+pub struct FeatureGraph {
+    /// Explicit and implicit features with their direct activations.
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Optional dependencies, mapped to whether cargo creates an implicit
+    /// feature for them (it doesn't once any `dep:name` is used).
+    pub optional_dependencies: BTreeMap<String, bool>,
+}
+
+/// Everything a feature turns on once cargo has followed all activations.
+#[derive(Debug, Default)]
+pub struct Activation {
+    pub features: BTreeSet<String>,
+    pub dependencies: BTreeSet<String>,
+    pub dependency_features: BTreeSet<String>,
+}
+
+impl FeatureGraph {
+    pub fn from_document(document: &DocumentMut) -> Self {
+        let mut features: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        if let Some(table) = document.get("features").and_then(Item::as_table_like) {
+            for (name, activations) in table.iter() {
+                let activations = activations
+                    .as_array()
+                    .map(|array| {
+                        array
+                            .iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                features.insert(name.to_string(), activations);
+            }
+        }
+
+        let explicit_deps: BTreeSet<String> = features
+            .values()
+            .flatten()
+            .filter_map(|activation| activation.strip_prefix("dep:"))
+            .map(String::from)
+            .collect();
+        let mut optional_dependencies = BTreeMap::new();
+        for name in manifest::optional_dependency_names(document) {
+            let implicit = !explicit_deps.contains(&name) && !features.contains_key(&name);
+            if implicit {
+                features.insert(name.clone(), vec![format!("dep:{name}")]);
+            }
+            optional_dependencies.insert(name, implicit);
+        }
+
+        Self {
+            features,
+            optional_dependencies,
+        }
+    }
+
+    /// Features that can be toggled from the command line, i.e. everything
+    /// except `default`.
+    pub fn selectable(&self) -> Vec<String> {
+        self.features
+            .keys()
+            .filter(|name| *name != "default")
+            .cloned()
+            .collect()
+    }
+
+    /// Follow the activations of `roots` the way cargo's resolver does.
+    pub fn activate<'a>(&self, roots: impl IntoIterator<Item = &'a str>) -> Activation {
+        let mut activation = Activation::default();
+        let mut visited = BTreeSet::new();
+        let mut pending: Vec<String> = roots.into_iter().map(String::from).collect();
+        while let Some(feature) = pending.pop() {
+            if !visited.insert(feature.clone()) {
+                continue;
+            }
+            // An implicit feature is nothing more than `dep:name`.
+            if self.optional_dependencies.get(&feature) == Some(&true) {
+                activation.dependencies.insert(feature);
+                continue;
+            }
+            activation.features.insert(feature.clone());
+            for item in self.features.get(&feature).into_iter().flatten() {
+                if let Some(dependency) = item.strip_prefix("dep:") {
+                    activation.dependencies.insert(dependency.to_string());
+                } else if item.contains("?/") {
+                    // Weak: only applies if something else enables the dependency.
+                    activation.dependency_features.insert(item.clone());
+                } else if let Some((dependency, _)) = item.split_once('/') {
+                    activation.dependency_features.insert(item.clone());
+                    if self.optional_dependencies.contains_key(dependency) {
+                        activation.dependencies.insert(dependency.to_string());
+                    }
+                } else {
+                    pending.push(item.clone());
+                }
+            }
+        }
+        activation
+    }
+
+    /// Features that directly list `dependency` as something they enable.
+    pub fn gates(&self, dependency: &str) -> Vec<String> {
+        self.features
+            .iter()
+            .filter(|(name, activations)| {
+                *name != dependency
+                    && activations.iter().any(|item| {
+                        item == &format!("dep:{dependency}")
+                            || item == dependency
+                            || item.starts_with(&format!("{dependency}/"))
+                    })
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+/// Every subset of `features` with at most `depth` members (all subsets
+/// when `depth` is `None`), smallest first.
+pub fn powerset(features: &[String], depth: Option<usize>) -> Vec<Vec<String>> {
+    let depth = depth.unwrap_or(features.len()).min(features.len());
+    let mut combinations = vec![Vec::new()];
+    let mut previous: Vec<(Vec<String>, usize)> = vec![(Vec::new(), 0)];
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for (combination, start) in &previous {
+            for (index, feature) in features.iter().enumerate().skip(*start) {
+                let mut extended = combination.clone();
+                extended.push(feature.clone());
+                next.push((extended, index + 1));
+            }
+        }
+        combinations.extend(next.iter().map(|(combination, _)| combination.clone()));
+        previous = next;
+    }
+    combinations
+}
+
+/// Number of subsets `powerset` would return, saturating on overflow.
+pub fn powerset_len(count: usize, depth: Option<usize>) -> usize {
+    let depth = depth.unwrap_or(count).min(count);
+    let mut total: usize = 0;
+    let mut binomial: usize = 1;
+    for k in 0..=depth {
+        total = total.saturating_add(binomial);
+        binomial = binomial.saturating_mul(count - k) / (k + 1);
+    }
+    total
+}
+
+fn features_table(document: &mut DocumentMut) -> Result<&mut dyn toml_edit::TableLike> {
+    document
+        .entry("features")
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow::anyhow!("[features] is not a table"))
+}
+
+/// Define `name = [activations...]`, replacing an existing definition.
+/// Returns whether an existing definition was replaced.
+pub fn define_feature(
+    document: &mut DocumentMut,
+    name: &str,
+    activations: &[String],
+) -> Result<bool> {
+    let table = features_table(document)?;
+    let array: toml_edit::Array = activations.iter().map(String::as_str).collect();
+    match table.get_mut(name).and_then(Item::as_value_mut) {
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = array.into();
+            *existing.decor_mut() = decor;
+            Ok(true)
+        }
+        None => {
+            table.insert(name, value(array));
+            Ok(false)
+        }
+    }
+}
+
+/// Remove a feature definition and every activation of it by other
+/// features. Returns the features that referred to it.
+pub fn remove_feature(document: &mut DocumentMut, name: &str) -> Result<Vec<String>> {
+    let table = document
+        .get_mut("features")
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| anyhow::anyhow!("The manifest has no [features] table"))?;
+    if table.remove(name).is_none() {
+        return Err(anyhow::anyhow!("Feature '{name}' is not defined"));
+    }
+    let mut referrers = Vec::new();
+    for (feature, activations) in table.iter_mut() {
+        if let Some(array) = activations.as_array_mut() {
+            let before = array.len();
+            array.retain(|item| item.as_str() != Some(name));
+            if array.len() != before {
+                referrers.push(feature.to_string());
+            }
+        }
+    }
+    Ok(referrers)
+}
+
+/// Make `dependency` optional and enabled exactly by `gates`, or a regular
+/// dependency again when `gates` is empty.
+pub fn gate_dependency(
+    document: &mut DocumentMut,
+    dependency: &str,
+    gates: &[String],
+) -> Result<()> {
+    let updated = manifest::set_optional(document, dependency, !gates.is_empty())?;
+    if updated == 0 {
+        return Err(anyhow::anyhow!(
+            "'{dependency}' is not declared in [dependencies] or [build-dependencies]"
+        ));
+    }
+
+    let explicit = format!("dep:{dependency}");
+    let weak_prefix = format!("{dependency}?/");
+    // Only create [features] when there are gates to add to it.
+    let table = match document.get_mut("features") {
+        Some(features) => features
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow::anyhow!("[features] is not a table"))?,
+        None if gates.is_empty() => return Ok(()),
+        None => features_table(document)?,
+    };
+    // A bare `dependency` refers to an explicit feature of that name if one
+    // is defined and outlives the change, not to the dependency's implicit
+    // feature.
+    let feature_named = table.get(dependency).is_some_and(|feature| {
+        !gates.is_empty()
+            || feature
+                .as_array()
+                .is_none_or(|array| array.iter().any(|item| item.as_str() != Some(&explicit)))
+    });
+    for (_, activations) in table.iter_mut() {
+        if let Some(array) = activations.as_array_mut() {
+            let before = array.len();
+            let single_line = !array.to_string().contains('\n');
+            array.retain(|item| {
+                let item = item.as_str().unwrap_or_default();
+                item != explicit && (item != dependency || feature_named)
+            });
+            if array.len() != before && single_line {
+                array.fmt();
+            }
+            if gates.is_empty() {
+                // `name?/feature` is only valid for optional dependencies.
+                for item in array.iter_mut() {
+                    if let Some(feature) = item.as_str().and_then(|s| s.strip_prefix(&weak_prefix))
+                    {
+                        let decor = item.decor().clone();
+                        *item = format!("{dependency}/{feature}").into();
+                        *item.decor_mut() = decor;
+                    }
+                }
+            }
+        }
+    }
+    // A regular dependency can't share its name with a feature.
+    if gates.is_empty()
+        && table
+            .get(dependency)
+            .and_then(Item::as_array)
+            .is_some_and(|array| array.is_empty())
+    {
+        table.remove(dependency);
+    }
+    for gate in gates {
+        match table.get_mut(gate).and_then(Item::as_array_mut) {
+            Some(array) => array.push(explicit.as_str()),
+            None => {
+                table.insert(
+                    gate,
+                    value(toml_edit::Array::from_iter([explicit.as_str()])),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gate_dependency_keeps_explicit_features_of_the_same_name() {
+        let mut document: DocumentMut = "[dependencies]\nserde = \"1\"\nother = { version = \"1\", optional = true }\n\n[features]\nserde = [\"other\"]\nfull = [\"serde\"]\n"
+            .parse()
+            .unwrap();
+        let mut gates = vec!["json".to_string()];
+        gate_dependency(&mut document, "other", &gates).unwrap();
+        gates[0] = "std".to_string();
+        gate_dependency(&mut document, "serde", &gates).unwrap();
+        let features = document["features"].to_string();
+        assert!(features.contains("full = [\"serde\"]"));
+        assert!(features.contains("std = [\"dep:serde\"]"));
+        assert!(features.contains("json = [\"dep:other\"]"));
+    }
+
+    #[test]
+    fn ungating_without_features_adds_no_table() {
+        let mut document: DocumentMut =
+            "[dependencies]\nserde = { version = \"1\", optional = true }\n"
+                .parse()
+                .unwrap();
+        gate_dependency(&mut document, "serde", &[]).unwrap();
+        assert!(document.get("features").is_none());
+        assert!(!document.to_string().contains("optional"));
+    }
+}
@@ -0,0 +1,168 @@
+// Imports for unresolved names: candidates come from rust-analyzer's
+// `auto_import` quick fix, one is chosen by a crate preference list, and the
+// quick fix offering it is applied, so the `use` item lands in the scope
+// rust-analyzer picks for it, e.g. the enclosing inline module.
+
+use serde_json::Value;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::analyzer::crate_index;
+use crate::analyzer::syntax;
+
+/// rustc error codes for names that fail to resolve, including methods of
+/// traits that aren't in scope.
+const UNRESOLVED_CODES: &[&str] = &[
+    "E0405", "E0412", "E0422", "E0423", "E0425", "E0433", "E0531", "E0532", "E0574", "E0599",
+];
+
+/// Whether a published diagnostic is about an unresolved name.
+pub fn is_unresolved(diagnostic: &Value) -> bool {
+    let code = diagnostic["code"].as_str().unwrap_or_default();
+    let message = diagnostic["message"].as_str().unwrap_or_default();
+    UNRESOLVED_CODES.contains(&code)
+        || code.starts_with("unresolved-")
+        || message.starts_with("cannot find macro")
+}
+
+/// Import paths offered by `Import `path`` quick fixes.
+pub fn candidates(actions: &Value) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for action in actions.as_array().into_iter().flatten() {
+        let Some(title) = action["title"].as_str() else {
+            continue;
+        };
+        if let Some(path) = title
+            .strip_prefix("Import `")
+            .and_then(|rest| rest.strip_suffix('`'))
+            && !paths.iter().any(|known| known == path)
+        {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
+/// Local paths first, then workspace crates, then the standard library
+/// facades in the order `std`, `alloc`, `core`.
+pub fn default_preferences(workspace: &Path, file: &Path) -> Vec<String> {
+    let mut preferences: Vec<String> = ["crate", "self", "super"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    if let Some(location) = syntax::module_location(file) {
+        preferences.push(location.crate_name);
+    }
+    for name in crate_index::workspace_crate_names(workspace) {
+        if !preferences.contains(&name) {
+            preferences.push(name);
+        }
+    }
+    preferences.extend(["std", "alloc", "core"].into_iter().map(String::from));
+    preferences
+}
+
+/// Position of the first preference `path` starts with; unmatched paths
+/// rank after all of them.
+fn rank(path: &str, preferences: &[String]) -> usize {
+    preferences
+        .iter()
+        .position(|prefix| {
+            path == prefix
+                || path
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with("::"))
+        })
+        .unwrap_or(preferences.len())
+}
+
+pub enum Resolution {
+    Import(String),
+    Ambiguous(Vec<String>),
+    Unavailable,
+}
+
+/// The single best-ranked candidate, or all tied ones.
+pub fn resolve(candidates: Vec<String>, preferences: &[String]) -> Resolution {
+    let Some(best) = candidates.iter().map(|path| rank(path, preferences)).min() else {
+        return Resolution::Unavailable;
+    };
+    let mut tied: Vec<String> = candidates
+        .into_iter()
+        .filter(|path| rank(path, preferences) == best)
+        .collect();
+    if tied.len() == 1 {
+        Resolution::Import(tied.remove(0))
+    } else {
+        Resolution::Ambiguous(tied)
+    }
+}
+
+/// An unresolved name whose import is still to be applied.
+pub struct Pending {
+    pub name: String,
+    /// Where the name is in the current text.
+    pub range: Range<usize>,
+    pub diagnostic: Value,
+    /// The path chosen for it, once candidates were ranked.
+    pub import: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// Unresolved name and the path imported for it.
+    pub imported: Vec<(String, String)>,
+    pub ambiguous: Vec<(String, Vec<String>)>,
+    /// Unresolved names without candidates, with their 1-based line.
+    pub unavailable: Vec<(String, u64)>,
+    /// Chosen imports whose quick fix couldn't be applied, with the reason.
+    pub not_applied: Vec<(String, String)>,
+}
+
+impl Outcome {
+    pub fn format(&self, path: &Path, diff: Option<String>, dry_run: bool) -> String {
+        if self.imported.is_empty()
+            && self.ambiguous.is_empty()
+            && self.unavailable.is_empty()
+            && self.not_applied.is_empty()
+        {
+            return format!("No unresolved names in {}", path.display());
+        }
+        let mut result = String::new();
+        if !self.imported.is_empty() {
+            result.push_str(&format!(
+                "{} {} imports to {}:\n",
+                if dry_run { "Would add" } else { "Added" },
+                self.imported.len(),
+                path.display()
+            ));
+            for (name, import) in &self.imported {
+                result.push_str(&format!("  {name} -> use {import};\n"));
+            }
+        }
+        if !self.ambiguous.is_empty() {
+            result.push_str(
+                "\nAmbiguous (put the crate to use first in `prefer`, or add the import yourself):\n",
+            );
+            for (name, candidates) in &self.ambiguous {
+                result.push_str(&format!("  {name}: {}\n", candidates.join(" | ")));
+            }
+        }
+        if !self.unavailable.is_empty() {
+            result.push_str("\nNo import candidates:\n");
+            for (name, line) in &self.unavailable {
+                result.push_str(&format!("  {name} (line {line})\n"));
+            }
+        }
+        if !self.not_applied.is_empty() {
+            result.push_str("\nNot applied:\n");
+            for (name, reason) in &self.not_applied {
+                result.push_str(&format!("  {name}: {reason}\n"));
+            }
+        }
+        if let Some(diff) = diff {
+            result.push_str(&format!("\n{diff}"));
+        }
+        result.trim_start().to_string()
+    }
+}
@@ -0,0 +1,73 @@
+// Source text annotated with `textDocument/inlayHint` results, so the
+// inferred types and parameter names an editor would show become visible
+// in plain text. Hints are rendered as `/*...*/` comments.
+
+use serde_json::Value;
+use std::ops::Range;
+
+use crate::analyzer::edit::{self, TextEdit};
+
+fn hint_label(hint: &Value) -> String {
+    match &hint["label"] {
+        Value::String(label) => label.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["value"].as_str())
+            .collect(),
+        _ => String::new(),
+    }
+}
+
+/// Lines `lines` (0-based, end exclusive) of `text` with the hints inserted
+/// and 1-based line numbers in the margin.
+pub fn annotate(text: &str, hints: &[Value], lines: Range<usize>) -> anyhow::Result<String> {
+    let mut edits: Vec<TextEdit> = hints
+        .iter()
+        .filter_map(|hint| {
+            let label = hint_label(hint);
+            if label.is_empty() {
+                return None;
+            }
+            let left = if hint["paddingLeft"].as_bool() == Some(true) {
+                " "
+            } else {
+                ""
+            };
+            let right = if hint["paddingRight"].as_bool() == Some(true) {
+                " "
+            } else {
+                ""
+            };
+            let offset = edit::position_to_offset(text, &hint["position"]);
+            Some(TextEdit::insert(
+                offset,
+                format!("{left}/*{}*/{right}", label.trim()),
+            ))
+        })
+        .collect();
+    // Hints at the same offset keep the server's order.
+    edits.sort_by_key(|edit| edit.range.start);
+    let mut merged: Vec<TextEdit> = Vec::new();
+    for edit in edits {
+        match merged.last_mut() {
+            Some(last) if last.range.start == edit.range.start => {
+                last.new_text.push_str(&edit.new_text)
+            }
+            _ => merged.push(edit),
+        }
+    }
+    let annotated = edit::apply_text_edits(text, &merged)?;
+
+    let total = annotated.lines().count();
+    let width = total.to_string().len();
+    let mut result = String::new();
+    for (index, line) in annotated
+        .lines()
+        .enumerate()
+        .skip(lines.start)
+        .take(lines.len())
+    {
+        result.push_str(&format!("{:>width$} | {line}\n", index + 1));
+    }
+    Ok(result)
+}
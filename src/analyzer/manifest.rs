@@ -0,0 +1,496 @@
+// Format-preserving Cargo.toml helpers built on toml_edit.
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, value};
+
+/// Find the manifest for a path that is either a `Cargo.toml` or a
+/// directory containing one.
+pub fn manifest_path(path: &str) -> Result<PathBuf> {
+    let path = crate::analyzer::edit::absolute_path(path)?;
+    let manifest = if path.is_dir() {
+        path.join("Cargo.toml")
+    } else {
+        path
+    };
+    if !manifest.is_file() {
+        return Err(anyhow::anyhow!(
+            "No Cargo.toml found at {}",
+            manifest.display()
+        ));
+    }
+    Ok(manifest)
+}
+
+pub fn read_document(manifest: &Path) -> Result<DocumentMut> {
+    let text = std::fs::read_to_string(manifest)?;
+    text.parse::<DocumentMut>()
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {e}", manifest.display()))
+}
+
+/// Names usable in code (`-` replaced by `_`) of every dependency declared
+/// in any dependency table, including target-specific ones.
+pub fn declared_dependency_names(document: &DocumentMut) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let mut collect = |table: Option<&Item>| {
+        if let Some(table) = table.and_then(Item::as_table_like) {
+            for (key, _) in table.iter() {
+                names.insert(key.replace('-', "_"));
+            }
+        }
+    };
+    for kind in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        collect(document.get(kind));
+        if let Some(targets) = document.get("target").and_then(Item::as_table_like) {
+            for (_, target) in targets.iter() {
+                collect(target.get(kind));
+            }
+        }
+    }
+    if let Some(workspace) = document.get("workspace") {
+        collect(workspace.get("dependencies"));
+    }
+    names
+}
+
+/// Which dependency table an entry lives in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DependencyKind {
+    #[default]
+    Normal,
+    Dev,
+    Build,
+}
+
+impl DependencyKind {
+    pub fn parse(kind: Option<&str>) -> Result<Self> {
+        match kind.unwrap_or("normal") {
+            "normal" | "" => Ok(Self::Normal),
+            "dev" => Ok(Self::Dev),
+            "build" => Ok(Self::Build),
+            other => Err(anyhow::anyhow!(
+                "Unknown dependency kind '{other}', expected normal, dev or build"
+            )),
+        }
+    }
+
+    pub fn table_name(self) -> &'static str {
+        match self {
+            Self::Normal => "dependencies",
+            Self::Dev => "dev-dependencies",
+            Self::Build => "build-dependencies",
+        }
+    }
+}
+
+/// A requested dependency entry. `None` fields are left as they are when
+/// updating an existing entry.
+#[derive(Debug, Clone, Default)]
+pub struct DependencySpec {
+    pub name: String,
+    pub version: Option<String>,
+    pub features: Option<Vec<String>>,
+    pub optional: Option<bool>,
+    pub default_features: Option<bool>,
+    pub workspace: Option<bool>,
+    pub kind: DependencyKind,
+    /// `cfg(...)` expression or target triple for `[target.'...'.dependencies]`.
+    pub target: Option<String>,
+}
+
+/// Human readable name of a dependency table, e.g.
+/// `[target.'cfg(unix)'.dev-dependencies]`.
+pub fn table_label(kind: DependencyKind, target: Option<&str>) -> String {
+    match target {
+        Some(target) => format!("[target.'{target}'.{}]", kind.table_name()),
+        None => format!("[{}]", kind.table_name()),
+    }
+}
+
+fn dependency_table<'a>(
+    document: &'a mut DocumentMut,
+    kind: DependencyKind,
+    target: Option<&str>,
+    create: bool,
+) -> Option<&'a mut dyn toml_edit::TableLike> {
+    let root = document.as_table_mut();
+    let parent = match target {
+        Some(target) => {
+            if create && !root.contains_key("target") {
+                let mut targets = toml_edit::Table::new();
+                targets.set_implicit(true);
+                root.insert("target", Item::Table(targets));
+            }
+            let targets = root.get_mut("target")?.as_table_like_mut()?;
+            if create && !targets.contains_key(target) {
+                let mut table = toml_edit::Table::new();
+                table.set_implicit(true);
+                targets.insert(target, Item::Table(table));
+            }
+            targets.get_mut(target)?.as_table_like_mut()?
+        }
+        None => root as &mut dyn toml_edit::TableLike,
+    };
+    if create && !parent.contains_key(kind.table_name()) {
+        parent.insert(kind.table_name(), toml_edit::table());
+    }
+    parent.get_mut(kind.table_name())?.as_table_like_mut()
+}
+
+/// Add `spec` or update the existing entry with the same name in place,
+/// keeping the surrounding formatting. Returns the rendered entry and
+/// whether it was newly added.
+pub fn upsert_dependency(
+    document: &mut DocumentMut,
+    spec: &DependencySpec,
+) -> Result<(String, bool)> {
+    if spec.workspace == Some(true) && spec.version.is_some() {
+        return Err(anyhow::anyhow!(
+            "A dependency inherited with workspace = true cannot also set a version"
+        ));
+    }
+    let table = dependency_table(document, spec.kind, spec.target.as_deref(), true)
+        .ok_or_else(|| anyhow::anyhow!("Dependency table is not a table"))?;
+
+    let existing = table.get(&spec.name).cloned();
+    let is_new = existing.is_none();
+    let mut entry = match existing {
+        Some(Item::Value(toml_edit::Value::String(version))) => {
+            let mut inline = toml_edit::InlineTable::new();
+            inline.insert("version", version.value().as_str().into());
+            Item::Value(toml_edit::Value::InlineTable(inline))
+        }
+        Some(item) => item,
+        None if spec.workspace != Some(true) && spec.version.is_none() => {
+            return Err(anyhow::anyhow!(
+                "A version (or workspace = true) is required to add '{}'",
+                spec.name
+            ));
+        }
+        None => Item::Value(toml_edit::Value::InlineTable(toml_edit::InlineTable::new())),
+    };
+
+    let fields = entry
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow::anyhow!("Existing entry for '{}' is not a table", spec.name))?;
+    if let Some(workspace) = spec.workspace {
+        if workspace {
+            fields.remove("version");
+            fields.insert("workspace", value(true));
+        } else {
+            fields.remove("workspace");
+        }
+    }
+    if let Some(version) = &spec.version {
+        fields.remove("workspace");
+        fields.insert("version", value(version.as_str()));
+    }
+    if let Some(features) = &spec.features {
+        if features.is_empty() {
+            fields.remove("features");
+        } else {
+            let array: toml_edit::Array = features.iter().map(String::as_str).collect();
+            fields.insert("features", value(array));
+        }
+    }
+    if let Some(default_features) = spec.default_features {
+        fields.remove("default_features");
+        if default_features {
+            fields.remove("default-features");
+        } else {
+            fields.insert("default-features", value(false));
+        }
+    }
+    if let Some(optional) = spec.optional {
+        if optional {
+            fields.insert("optional", value(true));
+        } else {
+            fields.remove("optional");
+        }
+    }
+
+    // Collapse `{ version = "x" }` back to the short `"x"` form.
+    let only_version = fields.len() == 1 && fields.contains_key("version");
+    if only_version && entry.is_inline_table() {
+        let version = entry["version"].clone();
+        entry = version;
+    }
+    if let Some(inline) = entry.as_inline_table_mut() {
+        inline.fmt();
+    }
+
+    let rendered = match &entry {
+        Item::Table(table) => format!("{}\n{}", spec.name, table.to_string().trim_end()),
+        other => format!("{} = {}", spec.name, other.to_string().trim()),
+    };
+    if is_new {
+        table.insert(&spec.name, entry);
+    } else if let Some(slot) = table.get_mut(&spec.name) {
+        // Keep the key's position and any comments attached to it.
+        let decor = slot.as_value().map(|v| v.decor().clone());
+        *slot = entry;
+        if let (Some(decor), Some(value)) = (decor, slot.as_value_mut()) {
+            *value.decor_mut() = decor;
+        }
+    }
+    Ok((rendered, is_new))
+}
+
+/// Remove a dependency and any feature entries that refer to it.
+/// Returns false if no such dependency was declared.
+pub fn remove_dependency(
+    document: &mut DocumentMut,
+    name: &str,
+    kind: DependencyKind,
+    target: Option<&str>,
+) -> Result<bool> {
+    let Some(table) = dependency_table(document, kind, target, false) else {
+        return Ok(false);
+    };
+    if table.remove(name).is_none() {
+        return Ok(false);
+    }
+    let now_empty = table.is_empty();
+
+    if now_empty && let Some(target) = target {
+        let targets = document.get_mut("target").and_then(Item::as_table_like_mut);
+        if let Some(targets) = targets
+            && let Some(entry) = targets.get_mut(target).and_then(Item::as_table_like_mut)
+        {
+            entry.remove(kind.table_name());
+            if entry.is_empty() {
+                targets.remove(target);
+            }
+        }
+    }
+
+    let still_declared = declared_dependency_names(document).contains(&name.replace('-', "_"));
+    if !still_declared
+        && let Some(features) = document
+            .get_mut("features")
+            .and_then(Item::as_table_like_mut)
+    {
+        // A bare `name` may also refer to an explicit feature of that name,
+        // which stays valid.
+        let feature_named = features.contains_key(name);
+        for (_, activations) in features.iter_mut() {
+            if let Some(array) = activations.as_array_mut() {
+                let before = array.len();
+                let single_line = !array.to_string().contains('\n');
+                array.retain(|activation| {
+                    let activation = activation.as_str().unwrap_or_default();
+                    (activation != name || feature_named)
+                        && activation != format!("dep:{name}")
+                        && !activation.starts_with(&format!("{name}/"))
+                        && !activation.starts_with(&format!("{name}?/"))
+                });
+                if array.len() != before && single_line {
+                    array.fmt();
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Every dependency table of the manifest, top-level tables first, with
+/// its kind and target.
+fn dependency_tables(
+    document: &DocumentMut,
+) -> Vec<(DependencyKind, Option<&str>, &dyn toml_edit::TableLike)> {
+    let kinds = [
+        DependencyKind::Normal,
+        DependencyKind::Dev,
+        DependencyKind::Build,
+    ];
+    let mut tables = Vec::new();
+    for kind in kinds {
+        if let Some(table) = document
+            .get(kind.table_name())
+            .and_then(Item::as_table_like)
+        {
+            tables.push((kind, None, table));
+        }
+    }
+    if let Some(targets) = document.get("target").and_then(Item::as_table_like) {
+        for (target, tables_of_target) in targets.iter() {
+            for kind in kinds {
+                if let Some(table) = tables_of_target
+                    .get(kind.table_name())
+                    .and_then(Item::as_table_like)
+                {
+                    tables.push((kind, Some(target), table));
+                }
+            }
+        }
+    }
+    tables
+}
+
+/// A value as written, with `[dependencies.name]` style tables shown inline.
+fn render(item: &Item) -> String {
+    match item.clone().into_value() {
+        Ok(mut value) => {
+            value.decor_mut().clear();
+            value.to_string()
+        }
+        Err(item) => item.to_string().trim().to_string(),
+    }
+}
+
+/// The package, workspace and dependency tables of a manifest.
+pub fn describe(document: &DocumentMut) -> String {
+    let mut result = String::new();
+    if let Some(package) = document.get("package").and_then(Item::as_table_like) {
+        let field = |key: &str| {
+            package
+                .get(key)
+                .map(|item| item.as_str().map_or_else(|| render(item), String::from))
+        };
+        result.push_str(&format!(
+            "Package: {}",
+            field("name").unwrap_or_else(|| "(unnamed)".to_string())
+        ));
+        for key in ["version", "edition", "rust-version"] {
+            if let Some(value) = field(key) {
+                result.push_str(&format!(", {key} {value}"));
+            }
+        }
+        result.push('\n');
+    }
+    if let Some(workspace) = document.get("workspace").and_then(Item::as_table_like) {
+        let members = workspace.get("members").map(render);
+        result.push_str(&format!(
+            "Workspace members: {}\n",
+            members.as_deref().unwrap_or("(root package only)")
+        ));
+        if let Some(dependencies) = workspace.get("dependencies").and_then(Item::as_table_like) {
+            result.push_str(&format!(
+                "\n[workspace.dependencies] ({}):\n",
+                dependencies.len()
+            ));
+            for (name, entry) in dependencies.iter() {
+                result.push_str(&format!("  {name} = {}\n", render(entry)));
+            }
+        }
+    }
+
+    let tables = dependency_tables(document);
+    if tables.is_empty() {
+        result.push_str("No dependencies\n");
+    }
+    for (kind, target, table) in tables {
+        result.push_str(&format!(
+            "\n{} ({}):\n",
+            table_label(kind, target),
+            table.len()
+        ));
+        for (name, entry) in table.iter() {
+            result.push_str(&format!("  {name} = {}\n", render(entry)));
+        }
+    }
+    result
+}
+
+/// Dependency tables that may declare optional dependencies, across all
+/// targets. Dev-dependencies can't be optional.
+fn optional_capable_tables(document: &DocumentMut) -> Vec<&dyn toml_edit::TableLike> {
+    let mut tables = Vec::new();
+    for kind in [DependencyKind::Normal, DependencyKind::Build] {
+        if let Some(table) = document
+            .get(kind.table_name())
+            .and_then(Item::as_table_like)
+        {
+            tables.push(table);
+        }
+        if let Some(targets) = document.get("target").and_then(Item::as_table_like) {
+            for (_, target) in targets.iter() {
+                if let Some(table) = target.get(kind.table_name()).and_then(Item::as_table_like) {
+                    tables.push(table);
+                }
+            }
+        }
+    }
+    tables
+}
+
+/// Names (as written in the manifest) of all optional dependencies.
+pub fn optional_dependency_names(document: &DocumentMut) -> BTreeSet<String> {
+    optional_capable_tables(document)
+        .into_iter()
+        .flat_map(|table| table.iter())
+        .filter(|(_, entry)| {
+            entry
+                .get("optional")
+                .and_then(Item::as_bool)
+                .unwrap_or(false)
+        })
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// Set or clear `optional = true` on every `[dependencies]` and
+/// `[build-dependencies]` entry named `name`, including target-specific
+/// ones. Returns the number of entries found.
+pub fn set_optional(document: &mut DocumentMut, name: &str, optional: bool) -> Result<usize> {
+    let mut locations: Vec<(DependencyKind, Option<String>)> = Vec::new();
+    for kind in [DependencyKind::Normal, DependencyKind::Build] {
+        if document
+            .get(kind.table_name())
+            .and_then(|table| table.get(name))
+            .is_some()
+        {
+            locations.push((kind, None));
+        }
+        if let Some(targets) = document.get("target").and_then(Item::as_table_like) {
+            for (target, table) in targets.iter() {
+                if table
+                    .get(kind.table_name())
+                    .and_then(|table| table.get(name))
+                    .is_some()
+                {
+                    locations.push((kind, Some(target.to_string())));
+                }
+            }
+        }
+    }
+
+    for (kind, target) in &locations {
+        let spec = DependencySpec {
+            name: name.to_string(),
+            optional: Some(optional),
+            kind: *kind,
+            target: target.clone(),
+            ..Default::default()
+        };
+        upsert_dependency(document, &spec)?;
+    }
+    Ok(locations.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_dependency_keeps_explicit_features_of_the_same_name() {
+        let mut document: DocumentMut = "[dependencies]\nserde = { version = \"1\", optional = true }\nother = \"1\"\n\n[features]\nserde = [\"dep:serde\"]\nfull = [\"serde\", \"other\"]\nderive = [\"serde/derive\", \"other\"]\n"
+            .parse()
+            .unwrap();
+        assert!(remove_dependency(&mut document, "serde", DependencyKind::Normal, None).unwrap());
+        let features = document["features"].to_string();
+        assert!(features.contains("serde = []"));
+        assert!(features.contains("full = [\"serde\", \"other\"]"));
+        assert!(features.contains("derive = [\"other\"]"));
+    }
+
+    #[test]
+    fn remove_dependency_strips_bare_activations_without_a_feature() {
+        let mut document: DocumentMut = "[dependencies]\nserde = { version = \"1\", optional = true }\n\n[features]\nfull = [\"serde\", \"other\"]\n"
+            .parse()
+            .unwrap();
+        remove_dependency(&mut document, "serde", DependencyKind::Normal, None).unwrap();
+        assert!(document.to_string().contains("full = [\"other\"]"));
+    }
+}
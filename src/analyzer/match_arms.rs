@@ -0,0 +1,442 @@
+// Match arm filling on top of rust-analyzer's `add_missing_match_arms`
+// assist: locating the `match` around a position, telling the arms the
+// assist added from the existing ones, and expanding `_` fields of
+// tuple-variant arms into the variants of their own type.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::edit::{self, FileChange};
+use crate::analyzer::syntax;
+
+/// Body of the arms added when no other expression is given.
+pub const DEFAULT_ARM_BODY: &str = "todo!()";
+
+/// Binders standing in for `_` fields while their type's variants are
+/// looked up.
+const NESTED_BINDER: &str = "__nested";
+
+/// How `fill_match_arms` writes the arms it adds.
+#[derive(Debug, Clone, Default)]
+pub struct FillOptions {
+    /// Expand `_` fields of tuple-variant arms into the variants of their
+    /// type, one level deep.
+    pub expand_nested: bool,
+    /// Body of every added arm instead of `todo!()`.
+    pub arm_body: Option<String>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchExpr {
+    /// Offset of the `match` keyword.
+    pub keyword: usize,
+    /// Between the braces of the arm list.
+    pub arm_list: Range<usize>,
+}
+
+/// The innermost `match` expression whose keyword, scrutinee or arm list
+/// contains `offset`.
+pub fn match_at(text: &str, offset: usize) -> Option<MatchExpr> {
+    let masked = syntax::mask_comments_and_strings(text);
+    syntax::find_word(&masked, "match")
+        .into_iter()
+        .filter(|&keyword| keyword <= offset)
+        .rev()
+        .find_map(|keyword| {
+            let open = scrutinee_end(&masked, keyword + "match".len())?;
+            let close = syntax::matching_brace(&masked, open)?;
+            (offset <= close).then_some(MatchExpr {
+                keyword,
+                arm_list: open + 1..close,
+            })
+        })
+}
+
+/// Offset of the `{` opening the arm list: the first brace outside
+/// parentheses and brackets after the scrutinee starts.
+fn scrutinee_end(masked: &str, from: usize) -> Option<usize> {
+    let mut nesting = 0usize;
+    for (index, byte) in masked.bytes().enumerate().skip(from) {
+        match byte {
+            b'(' | b'[' => nesting += 1,
+            b')' | b']' => nesting = nesting.saturating_sub(1),
+            b'{' if nesting == 0 => return Some(index),
+            b';' if nesting == 0 => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone)]
+pub struct Arm {
+    /// From the pattern to the trailing comma, if any.
+    pub range: Range<usize>,
+    /// Pattern and guard, whitespace collapsed.
+    pub pattern: String,
+    pub body: Range<usize>,
+}
+
+/// Arms of a match expression. Block-like bodies without a trailing comma
+/// other than plain blocks are not told apart from the next arm.
+pub fn arms(text: &str, expr: &MatchExpr) -> Vec<Arm> {
+    let masked = syntax::mask_comments_and_strings(text);
+    let bytes = masked.as_bytes();
+    let end = expr.arm_list.end;
+    let skip_whitespace = |mut at: usize| {
+        while at < end && bytes[at].is_ascii_whitespace() {
+            at += 1;
+        }
+        at
+    };
+
+    let mut arms = Vec::new();
+    let mut at = skip_whitespace(expr.arm_list.start);
+    while at < end {
+        let Some(arrow) = top_level_find(&masked[..end], at, "=>") else {
+            break;
+        };
+        let body_start = skip_whitespace(arrow + 2);
+        let (body_end, mut arm_end) = if bytes.get(body_start) == Some(&b'{') {
+            let close = syntax::matching_brace(&masked, body_start).unwrap_or(end - 1) + 1;
+            (close, close)
+        } else {
+            let comma = top_level_find(&masked[..end], body_start, ",").unwrap_or(end);
+            let body_end = body_start + masked[body_start..comma].trim_end().len();
+            (body_end, comma)
+        };
+        let after = skip_whitespace(arm_end);
+        if after < end && bytes[after] == b',' {
+            arm_end = after + 1;
+        }
+        arms.push(Arm {
+            range: at..arm_end,
+            pattern: collapse_whitespace(&text[at..arrow]),
+            body: body_start..body_end,
+        });
+        at = skip_whitespace(arm_end);
+    }
+    arms
+}
+
+/// First occurrence of `needle` at or after `from` that is not nested in
+/// parentheses, brackets or braces.
+fn top_level_find(masked: &str, from: usize, needle: &str) -> Option<usize> {
+    let mut nesting = 0usize;
+    let bytes = masked.as_bytes();
+    let mut index = from;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'(' | b'[' | b'{' => nesting += 1,
+            b')' | b']' | b'}' => nesting = nesting.saturating_sub(1),
+            _ if nesting == 0 && masked[index..].starts_with(needle) => return Some(index),
+            _ => {}
+        }
+        index += 1;
+    }
+    None
+}
+
+/// Split on commas outside parentheses, brackets and braces.
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    while let Some(comma) = top_level_find(text, start, ",") {
+        parts.push(text[start..comma].trim());
+        start = comma + 1;
+    }
+    if !text[start..].trim().is_empty() {
+        parts.push(text[start..].trim());
+    }
+    parts
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Arms of `after` whose pattern `before` doesn't have, i.e. the ones the
+/// assist added.
+pub fn added_arms(before: &[Arm], after: Vec<Arm>) -> Vec<Arm> {
+    let mut existing: BTreeMap<&str, usize> = BTreeMap::new();
+    for arm in before {
+        *existing.entry(&arm.pattern).or_default() += 1;
+    }
+    after
+        .into_iter()
+        .filter(|arm| match existing.get_mut(arm.pattern.as_str()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+/// A tuple-variant pattern with `_` fields, rewritten to bind them.
+#[derive(Debug, Clone)]
+pub struct NestedFields {
+    /// The pattern with binders in place of the `_` fields.
+    pub pattern: String,
+    /// Expression to match the binders against: one binder or a tuple.
+    pub scrutinee: String,
+    fields: Vec<String>,
+    /// Indices of the `_` fields.
+    wildcards: Vec<usize>,
+    path: String,
+}
+
+impl NestedFields {
+    /// `Path(_, x, _)` patterns; other patterns have nothing to expand.
+    pub fn parse(pattern: &str) -> Option<Self> {
+        let inner = pattern.strip_suffix(')')?;
+        let open = inner.find('(')?;
+        let path = inner[..open].trim();
+        if path.is_empty()
+            || !path
+                .bytes()
+                .all(|byte| syntax::is_ident_byte(byte) || byte == b':')
+        {
+            return None;
+        }
+        let fields: Vec<String> = split_top_level(&inner[open + 1..])
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let wildcards: Vec<usize> = (0..fields.len()).filter(|&i| fields[i] == "_").collect();
+        if wildcards.is_empty() {
+            return None;
+        }
+        let binders: Vec<String> = wildcards
+            .iter()
+            .map(|index| format!("{NESTED_BINDER}{index}"))
+            .collect();
+        let mut bound = fields.clone();
+        for (index, binder) in wildcards.iter().zip(&binders) {
+            bound[*index] = binder.clone();
+        }
+        let scrutinee = match binders.as_slice() {
+            [binder] => binder.clone(),
+            _ => format!("({})", binders.join(", ")),
+        };
+        Some(Self {
+            pattern: format!("{path}({})", bound.join(", ")),
+            scrutinee,
+            fields,
+            wildcards,
+            path: path.to_string(),
+        })
+    }
+
+    /// The original pattern with the `_` fields replaced by the patterns of
+    /// one arm of the nested match, or None if its shape doesn't fit.
+    pub fn substitute(&self, nested: &str) -> Option<String> {
+        let patterns = match self.wildcards.len() {
+            1 => vec![nested],
+            count => {
+                let parts = split_top_level(nested.strip_prefix('(')?.strip_suffix(')')?);
+                if parts.len() != count {
+                    return None;
+                }
+                parts
+            }
+        };
+        let mut fields = self.fields.clone();
+        for (index, pattern) in self.wildcards.iter().zip(patterns) {
+            fields[*index] = pattern.to_string();
+        }
+        Some(format!("{}({})", self.path, fields.join(", ")))
+    }
+}
+
+/// Source for `arms`, one per line at the indentation of the line holding
+/// `at`.
+pub fn render_arms(text: &str, at: usize, patterns: &[String], body: &str) -> String {
+    let line_start = syntax::line_start(text, at);
+    let indent = &text[line_start..at];
+    let indent = if indent.trim().is_empty() { indent } else { "" };
+    patterns
+        .iter()
+        .map(|pattern| format!("{pattern} => {body},"))
+        .collect::<Vec<_>>()
+        .join(&format!("\n{indent}"))
+}
+
+/// The arms added to one match expression.
+#[derive(Debug)]
+pub struct Filled {
+    pub path: PathBuf,
+    /// 1-based line of the `match` keyword.
+    pub line: u64,
+    pub patterns: Vec<String>,
+}
+
+pub fn format_report(
+    root: &Path,
+    filled: &[Filled],
+    not_offered: &[(PathBuf, u64)],
+    changes: &[FileChange],
+    dry_run: bool,
+) -> String {
+    let relative = |path: &Path| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    let arms: usize = filled.iter().map(|fill| fill.patterns.len()).sum();
+    let mut result = if filled.is_empty() {
+        "No match arms added\n".to_string()
+    } else {
+        format!(
+            "{} {arms} match arm{} to {} match{} in {} file{}:\n",
+            if dry_run { "Would add" } else { "Added" },
+            if arms == 1 { "" } else { "s" },
+            filled.len(),
+            if filled.len() == 1 { "" } else { "es" },
+            changes.len(),
+            if changes.len() == 1 { "" } else { "s" }
+        )
+    };
+    for fill in filled {
+        result.push_str(&format!(
+            "\n{}:{} (+{})\n",
+            relative(&fill.path),
+            fill.line,
+            fill.patterns.len()
+        ));
+        for pattern in &fill.patterns {
+            result.push_str(&format!("  {pattern}\n"));
+        }
+    }
+
+    if !not_offered.is_empty() {
+        result.push_str("\nrust-analyzer offered no arms for:\n");
+        for (path, line) in not_offered {
+            result.push_str(&format!("  {}:{line}\n", relative(path)));
+        }
+    }
+    for change in changes {
+        result.push_str(&format!("\n{}", edit::unified_diff(change)));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NESTED: &str = "fn f(a: A, b: B) {
+    match a {
+        A::X => match b {
+            B::One => {}
+        },
+        A::Y(_) => {}
+    }
+}
+";
+
+    fn patterns(arms: &[Arm]) -> Vec<&str> {
+        arms.iter().map(|arm| arm.pattern.as_str()).collect()
+    }
+
+    #[test]
+    fn match_at_picks_the_innermost_match() {
+        let outer = NESTED.find("match a").unwrap();
+        let inner = NESTED.find("match b").unwrap();
+        let one = NESTED.find("B::One").unwrap();
+        assert_eq!(match_at(NESTED, one).unwrap().keyword, inner);
+        let y = NESTED.find("A::Y").unwrap();
+        assert_eq!(match_at(NESTED, y).unwrap().keyword, outer);
+        assert_eq!(match_at(NESTED, outer + 2).unwrap().keyword, outer);
+        assert!(match_at(NESTED, 0).is_none());
+    }
+
+    #[test]
+    fn arms_of_nested_matches_stay_separate() {
+        let outer = match_at(NESTED, NESTED.find("A::Y").unwrap()).unwrap();
+        let outer_arms = arms(NESTED, &outer);
+        assert_eq!(patterns(&outer_arms), ["A::X", "A::Y(_)"]);
+        assert!(NESTED[outer_arms[0].body.clone()].starts_with("match b {"));
+
+        let inner = match_at(NESTED, NESTED.find("B::One").unwrap()).unwrap();
+        assert_eq!(patterns(&arms(NESTED, &inner)), ["B::One"]);
+    }
+
+    #[test]
+    fn block_bodies_without_commas_and_guards() {
+        let text = "match v {
+    Some(x) if x > 0 => { pos(x) }
+    Some(_) => {
+        other()
+    }
+    None => zero(), // the end
+}";
+        let expr = match_at(text, 0).unwrap();
+        let found = arms(text, &expr);
+        assert_eq!(patterns(&found), ["Some(x) if x > 0", "Some(_)", "None"]);
+        assert_eq!(&text[found[0].body.clone()], "{ pos(x) }");
+        assert_eq!(&text[found[2].body.clone()], "zero()");
+        assert!(text[found[2].range.clone()].ends_with(','));
+    }
+
+    #[test]
+    fn added_arms_are_the_new_patterns_only() {
+        let before = "match e { E::A => 1, E::B(_) => 2 }";
+        let after = "match e { E::A => 1, E::B(_) => 2, E::C => todo!(), E::A => todo!() }";
+        let before_arms = arms(before, &match_at(before, 0).unwrap());
+        let after_arms = arms(after, &match_at(after, 0).unwrap());
+        assert_eq!(
+            patterns(&added_arms(&before_arms, after_arms)),
+            ["E::C", "E::A"]
+        );
+    }
+
+    #[test]
+    fn nested_fields_bind_and_substitute_wildcards() {
+        let single = NestedFields::parse("Msg::Move(_)").unwrap();
+        assert_eq!(single.pattern, "Msg::Move(__nested0)");
+        assert_eq!(single.scrutinee, "__nested0");
+        assert_eq!(
+            single.substitute("Dir::Up").as_deref(),
+            Some("Msg::Move(Dir::Up)")
+        );
+
+        let multi = NestedFields::parse("Pair(_, x, _)").unwrap();
+        assert_eq!(multi.pattern, "Pair(__nested0, x, __nested2)");
+        assert_eq!(multi.scrutinee, "(__nested0, __nested2)");
+        assert_eq!(
+            multi.substitute("(A::B(1, 2), C)").as_deref(),
+            Some("Pair(A::B(1, 2), x, C)")
+        );
+
+        assert!(NestedFields::parse("Msg::Quit").is_none());
+        assert!(NestedFields::parse("Msg::Move(x)").is_none());
+        assert!(NestedFields::parse("Msg::Move { .. }").is_none());
+    }
+
+    #[test]
+    fn substitute_rejects_mismatched_shapes() {
+        let multi = NestedFields::parse("Pair(_, _)").unwrap();
+        assert!(multi.substitute("A").is_none());
+        assert!(multi.substitute("(A, B, C)").is_none());
+        assert!(multi.substitute("(A)").is_none());
+    }
+
+    #[test]
+    fn render_arms_uses_the_indentation_of_the_arm() {
+        let text = "match m {\n        Msg::Move(_) => todo!(),\n}";
+        let at = text.find("Msg").unwrap();
+        let patterns = [
+            "Msg::Move(Dir::Up)".to_string(),
+            "Msg::Move(Dir::Down)".to_string(),
+        ];
+        assert_eq!(
+            render_arms(text, at, &patterns, "todo!()"),
+            "Msg::Move(Dir::Up) => todo!(),\n        Msg::Move(Dir::Down) => todo!(),"
+        );
+    }
+}
@@ -1,5 +1,28 @@
+pub mod cargo;
 pub mod client;
+pub mod code_actions;
+pub mod completion;
+pub mod crate_index;
+pub mod diagnostics;
+pub mod edit;
+pub mod expand;
+pub mod features;
+pub mod imports;
+pub mod inlay;
 pub mod lsp;
+pub mod manifest;
+pub mod match_arms;
+pub mod move_items;
+pub mod outline;
 pub mod protocol;
+pub mod quick_fixes;
+pub mod runnables;
+pub mod rustdoc;
+pub mod semantic;
+pub mod signature;
+pub mod sources;
+pub mod syntax;
+pub mod unsafe_audit;
+pub mod unused;
 
 pub use client::RustAnalyzerClient;
@@ -0,0 +1,893 @@
+// Planning for the `move_items` refactoring.
+// The client gathers document symbols and references from rust-analyzer;
+// this module turns them into file changes: cutting the items, inserting
+// them with the imports they need, and rewriting paths that pointed at
+// the old module.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::edit::{self, FileChange, TextEdit};
+use crate::analyzer::outline;
+use crate::analyzer::syntax::{self, ModuleLocation, UseLeaf, UseTree, UseTreeKind};
+
+/// A top-level item of the source file selected for moving.
+#[derive(Debug, Clone)]
+pub struct SelectedItem {
+    pub name: String,
+    pub symbol: Value,
+    pub is_impl: bool,
+}
+
+/// References to a selected item or to one of its members.
+#[derive(Debug, Clone)]
+pub struct SymbolReferences {
+    pub symbol: Value,
+    pub locations: Vec<Value>,
+}
+
+pub struct MoveRequest<'a> {
+    pub source: &'a Path,
+    pub target: &'a Path,
+    pub symbols: &'a [Value],
+    pub items: &'a [SelectedItem],
+    /// References to each non-impl selected item.
+    pub references: &'a [SymbolReferences],
+    /// References to private members (fields, methods) of selected items.
+    pub member_references: &'a [SymbolReferences],
+}
+
+pub struct MovePlan {
+    pub changes: Vec<FileChange>,
+    pub summary: String,
+}
+
+fn symbol_name(symbol: &Value) -> &str {
+    symbol["name"].as_str().unwrap_or_default()
+}
+
+fn is_impl_symbol(symbol: &Value) -> bool {
+    outline::kind_label(symbol["kind"].as_u64().unwrap_or(0)) == "impl"
+}
+
+/// Resolve requested item names against the top-level symbols of the
+/// source file. Inherent and trait impls of a moved type move with it.
+pub fn select_items(symbols: &[Value], item_names: &[&str]) -> Result<Vec<SelectedItem>> {
+    let mut selected: Vec<SelectedItem> = Vec::new();
+    let mut missing = Vec::new();
+
+    for &name in item_names {
+        let mut found = false;
+        for symbol in symbols {
+            let symbol_name = symbol_name(symbol);
+            let is_impl = is_impl_symbol(symbol);
+            let header = symbol_name.strip_prefix("impl").unwrap_or(symbol_name);
+            let matches =
+                symbol_name == name || (is_impl && syntax::impl_self_type(header) == name);
+            if !matches {
+                continue;
+            }
+            found = true;
+            let already_selected = selected
+                .iter()
+                .any(|item| item.symbol["range"] == symbol["range"]);
+            if !already_selected {
+                selected.push(SelectedItem {
+                    name: if is_impl && symbol_name != name {
+                        name.to_string()
+                    } else {
+                        symbol_name.to_string()
+                    },
+                    symbol: symbol.clone(),
+                    is_impl,
+                });
+            }
+        }
+        if !found {
+            missing.push(name);
+        }
+    }
+
+    if !missing.is_empty() {
+        let available: Vec<&str> = symbols
+            .iter()
+            .map(symbol_name)
+            .filter(|name| !name.is_empty())
+            .collect();
+        return Err(anyhow::anyhow!(
+            "Items not found in source file: {}. Top-level items: {}",
+            missing.join(", "),
+            available.join(", ")
+        ));
+    }
+
+    Ok(selected)
+}
+
+/// Members of selected items that are private and might need a wider
+/// visibility once they live in another module.
+pub fn private_members(source_text: &str, items: &[SelectedItem]) -> Vec<Value> {
+    let mut members = Vec::new();
+    for item in items {
+        // Trait impl members take the trait's visibility.
+        if item.is_impl && symbol_name(&item.symbol).contains(" for ") {
+            continue;
+        }
+        for child in item.symbol["children"].as_array().into_iter().flatten() {
+            let range = edit::lsp_range_to_offsets(source_text, &child["range"]);
+            let text = &source_text[range];
+            let header = syntax::item_header_offset(text);
+            if !syntax::has_visibility(&text[header..]) {
+                members.push(child.clone());
+            }
+        }
+    }
+    members
+}
+
+/// Expand a symbol range to whole lines, taking a following blank line too.
+fn item_span(text: &str, symbol: &Value) -> Range<usize> {
+    let range = edit::lsp_range_to_offsets(text, &symbol["range"]);
+    let mut start = range.start;
+    let line_start = syntax::line_start(text, start);
+    if text[line_start..start].trim().is_empty() {
+        start = line_start;
+    }
+    let mut end = range.end;
+    let line_end = syntax::line_end(text, end);
+    if text[end..line_end].trim().is_empty() {
+        end = line_end;
+        let next_line_end = syntax::line_end(text, end);
+        if next_line_end > end && text[end..next_line_end].trim().is_empty() {
+            end = next_line_end;
+        }
+    }
+    start..end
+}
+
+struct Files {
+    texts: BTreeMap<PathBuf, String>,
+    masked: BTreeMap<PathBuf, String>,
+    edits: BTreeMap<PathBuf, Vec<TextEdit>>,
+}
+
+impl Files {
+    fn text(&mut self, path: &Path) -> Result<&str> {
+        if !self.texts.contains_key(path) {
+            let text = std::fs::read_to_string(path)?;
+            self.masked
+                .insert(path.to_path_buf(), syntax::mask_comments_and_strings(&text));
+            self.texts.insert(path.to_path_buf(), text);
+        }
+        Ok(&self.texts[path])
+    }
+
+    fn masked(&mut self, path: &Path) -> Result<&str> {
+        self.text(path)?;
+        Ok(&self.masked[path])
+    }
+
+    fn push(&mut self, path: &Path, edit: TextEdit) {
+        self.edits.entry(path.to_path_buf()).or_default().push(edit);
+    }
+}
+
+/// Whether `path` (written in `from`) names the module at `module`.
+fn resolves_to(from: &ModuleLocation, path: &[String], module: &ModuleLocation) -> bool {
+    let first = path.first().map(|s| s.trim_start_matches("::"));
+    if from.crate_dir != module.crate_dir {
+        return first == Some(module.crate_name.as_str()) && path[1..] == module.segments[..];
+    }
+    from.resolve(path).as_deref() == Some(&module.segments[..])
+}
+
+fn render_use(visibility: &str, path: &str, name: &str, alias: Option<&str>) -> String {
+    match alias {
+        Some(alias) => format!("{visibility}use {path}::{name} as {alias};"),
+        None => format!("{visibility}use {path}::{name};"),
+    }
+}
+
+pub fn plan_move(request: MoveRequest<'_>) -> Result<MovePlan> {
+    let not_in_crate = |path: &Path| {
+        anyhow::anyhow!(
+            "{} is not inside the src/ directory of a Cargo package",
+            path.display()
+        )
+    };
+    let source_module =
+        syntax::module_location(request.source).ok_or_else(|| not_in_crate(request.source))?;
+    let target_module =
+        syntax::module_location(request.target).ok_or_else(|| not_in_crate(request.target))?;
+    if source_module.crate_dir != target_module.crate_dir {
+        return Err(anyhow::anyhow!(
+            "Moving items between different crates is not supported"
+        ));
+    }
+
+    let mut files = Files {
+        texts: BTreeMap::new(),
+        masked: BTreeMap::new(),
+        edits: BTreeMap::new(),
+    };
+    let source_text = files.text(request.source)?.to_string();
+    let source_masked = files.masked(request.source)?.to_string();
+    let target_text = files.text(request.target)?.to_string();
+
+    let mut spans: Vec<Range<usize>> = request
+        .items
+        .iter()
+        .map(|item| item_span(&source_text, &item.symbol))
+        .collect();
+    spans.sort_by_key(|span| span.start);
+    spans.dedup_by(|next, previous| next.start < previous.end);
+    let in_moved = |path: &Path, offset: usize| {
+        path == request.source && spans.iter().any(|span| span.contains(&offset))
+    };
+    let is_outside = |location: &Value, files: &mut Files| -> Result<bool> {
+        let path = edit::uri_to_path(location["uri"].as_str().unwrap_or_default());
+        let text = files.text(&path)?;
+        let offset = edit::position_to_offset(text, &location["range"]["start"]);
+        Ok(!in_moved(&path, offset))
+    };
+
+    let mut notes = Vec::new();
+    let mut visibility_changes = Vec::new();
+
+    // Widen the visibility of moved items and members used from outside.
+    let mut moved_edits = Vec::new();
+    for references in request.references.iter().chain(request.member_references) {
+        let mut used_outside = false;
+        for location in &references.locations {
+            if is_outside(location, &mut files)? {
+                used_outside = true;
+                break;
+            }
+        }
+        if !used_outside {
+            continue;
+        }
+        let range = edit::lsp_range_to_offsets(&source_text, &references.symbol["range"]);
+        let header = range.start + syntax::item_header_offset(&source_text[range.clone()]);
+        if !syntax::has_visibility(&source_text[header..]) {
+            moved_edits.push(TextEdit::insert(header, "pub(crate) "));
+            visibility_changes.push(symbol_name(&references.symbol).to_string());
+        }
+    }
+
+    let mut moved_text = String::new();
+    for span in &spans {
+        let edits: Vec<TextEdit> = moved_edits
+            .iter()
+            .filter(|edit| span.contains(&edit.range.start))
+            .map(|edit| TextEdit::insert(edit.range.start - span.start, edit.new_text.clone()))
+            .collect();
+        let snippet = edit::apply_text_edits(&source_text[span.clone()], &edits)?;
+        let snippet = snippet.trim_end();
+        if !moved_text.is_empty() {
+            moved_text.push_str("\n\n");
+        }
+        moved_text.push_str(snippet);
+    }
+    moved_text.push('\n');
+    let moved_masked = syntax::mask_comments_and_strings(&moved_text);
+
+    // Imports the moved code relies on.
+    let existing_target_leaves: BTreeSet<(Vec<String>, Option<String>)> =
+        syntax::use_statements(&target_text)
+            .iter()
+            .flat_map(|statement| statement.tree.leaves())
+            .map(|leaf| (leaf.path, leaf.alias))
+            .collect();
+    let source_top_level: Vec<&Value> = request.symbols.iter().collect();
+    let is_local_module = |name: &str| {
+        source_top_level
+            .iter()
+            .any(|symbol| symbol_name(symbol) == name && symbol["kind"].as_u64() == Some(2))
+    };
+
+    let mut target_imports: Vec<UseLeaf> = Vec::new();
+    let push_import = |leaf: UseLeaf, imports: &mut Vec<UseLeaf>| {
+        let key = (leaf.path.clone(), leaf.alias.clone());
+        if !existing_target_leaves.contains(&key)
+            && !imports
+                .iter()
+                .any(|existing| existing.path == leaf.path && existing.alias == leaf.alias)
+        {
+            imports.push(leaf);
+        }
+    };
+
+    for statement in syntax::use_statements(&source_text) {
+        if syntax::brace_depth(&source_masked, statement.range.start) != 0
+            || spans
+                .iter()
+                .any(|span| span.contains(&statement.range.start))
+        {
+            continue;
+        }
+        for leaf in statement.tree.leaves() {
+            let needed = match leaf.binding() {
+                Some(binding) => syntax::contains_word(&moved_masked, binding),
+                None => true,
+            };
+            if !needed {
+                continue;
+            }
+            let first = leaf.path.first().map(String::as_str).unwrap_or_default();
+            let relative = matches!(first, "crate" | "self" | "super") || is_local_module(first);
+            let mut path = leaf.path.clone();
+            if relative && let Some(resolved) = source_module.resolve(&leaf.path) {
+                if leaf.glob && resolved == target_module.segments {
+                    continue;
+                }
+                if !leaf.glob
+                    && resolved[..resolved.len().saturating_sub(1)] == target_module.segments[..]
+                {
+                    continue;
+                }
+                path = std::iter::once("crate".to_string())
+                    .chain(resolved)
+                    .collect();
+            }
+            push_import(
+                UseLeaf {
+                    path,
+                    alias: leaf.alias.clone(),
+                    glob: leaf.glob,
+                },
+                &mut target_imports,
+            );
+        }
+    }
+
+    // Items left behind in the source module that the moved code uses.
+    let moved_names: BTreeSet<&str> = request
+        .items
+        .iter()
+        .map(|item| item.name.as_str())
+        .collect();
+    for symbol in request.symbols {
+        let name = symbol_name(symbol);
+        let range = edit::lsp_range_to_offsets(&source_text, &symbol["range"]);
+        if is_impl_symbol(symbol)
+            || moved_names.contains(name)
+            || spans.iter().any(|span| span.contains(&range.start))
+            || !syntax::contains_word(&moved_masked, name)
+        {
+            continue;
+        }
+        let path = std::iter::once("crate".to_string())
+            .chain(source_module.segments.iter().cloned())
+            .chain(std::iter::once(name.to_string()))
+            .collect();
+        push_import(
+            UseLeaf {
+                path,
+                alias: None,
+                glob: false,
+            },
+            &mut target_imports,
+        );
+        let header = range.start + syntax::item_header_offset(&source_text[range.clone()]);
+        if !syntax::has_visibility(&source_text[header..]) {
+            files.push(request.source, TextEdit::insert(header, "pub(crate) "));
+            visibility_changes.push(name.to_string());
+        }
+    }
+
+    // Drop source imports that only the moved code used.
+    let mut remaining_masked = source_masked.clone();
+    for span in &spans {
+        remaining_masked.replace_range(span.clone(), &" ".repeat(span.len()));
+    }
+    let source_uses = syntax::use_statements(&source_text);
+    for statement in &source_uses {
+        let range = statement.range.clone();
+        remaining_masked.replace_range(range.clone(), &" ".repeat(range.len()));
+    }
+    for statement in source_uses {
+        if syntax::brace_depth(&source_masked, statement.range.start) != 0
+            || spans
+                .iter()
+                .any(|span| span.contains(&statement.range.start))
+        {
+            continue;
+        }
+        let unused = |leaf: &UseLeaf| {
+            leaf.binding().is_some_and(|binding| {
+                syntax::contains_word(&moved_masked, binding)
+                    && !syntax::contains_word(&remaining_masked, binding)
+            })
+        };
+        if !statement.tree.leaves().iter().any(unused) {
+            continue;
+        }
+        let mut tree = statement.tree.clone();
+        if tree.retain_leaves(&[], &unused) {
+            files.push(
+                request.source,
+                TextEdit::new(statement.range.clone(), statement.render(&simplify(tree))),
+            );
+        } else {
+            let line = syntax::line_start(&source_text, statement.range.start)
+                ..syntax::line_end(&source_text, statement.range.end);
+            files.push(request.source, TextEdit::delete(line));
+        }
+    }
+
+    // Cut from the source, paste into the target.
+    for span in &spans {
+        files.push(request.source, TextEdit::delete(span.clone()));
+    }
+    if !target_imports.is_empty() {
+        let offset = syntax::import_insertion_offset(&target_text);
+        let mut lines: String = target_imports
+            .iter()
+            .map(|leaf| format!("use {};\n", leaf.to_tree()))
+            .collect();
+        if offset < target_text.len()
+            && !target_text[offset..].starts_with('\n')
+            && !target_text[..offset].contains("use ")
+        {
+            lines.push('\n');
+        }
+        files.push(request.target, TextEdit::insert(offset, lines));
+    }
+    let separator = if target_text.is_empty() || target_text.ends_with("\n\n") {
+        ""
+    } else if target_text.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    files.push(
+        request.target,
+        TextEdit::insert(target_text.len(), format!("{separator}{moved_text}")),
+    );
+
+    // Rewrite references that pointed at the old module.
+    let mut use_rewrites: BTreeMap<(PathBuf, usize), (syntax::UseStatement, BTreeSet<String>)> =
+        BTreeMap::new();
+    let mut bare_names: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+    let mut rewritten_paths = 0;
+
+    for references in request.references {
+        let name = symbol_name(&references.symbol).to_string();
+        for location in &references.locations {
+            let path = edit::uri_to_path(location["uri"].as_str().unwrap_or_default());
+            let offset = {
+                let text = files.text(&path)?;
+                edit::position_to_offset(text, &location["range"]["start"])
+            };
+            if in_moved(&path, offset) {
+                continue;
+            }
+            let text = files.text(&path)?.to_string();
+            if let Some(statement) = syntax::enclosing_use_statement(&text, offset) {
+                use_rewrites
+                    .entry((path.clone(), statement.range.start))
+                    .or_insert_with(|| (statement, BTreeSet::new()))
+                    .1
+                    .insert(name.clone());
+                continue;
+            }
+            let masked = files.masked(&path)?.to_string();
+            match syntax::path_prefix_before(&masked, offset) {
+                Some((prefix_range, segments)) => {
+                    let Some(from) = syntax::module_location(&path) else {
+                        continue;
+                    };
+                    if resolves_to(&from, &segments, &source_module) {
+                        let mut new_prefix = target_module.path_from(&from);
+                        if segments[0].starts_with("::")
+                            && from.crate_dir != target_module.crate_dir
+                        {
+                            new_prefix = format!("::{new_prefix}");
+                        }
+                        files.push(
+                            &path,
+                            TextEdit::new(prefix_range, format!("{new_prefix}::")),
+                        );
+                        rewritten_paths += 1;
+                    }
+                }
+                None => {
+                    bare_names.entry(path).or_default().insert(name.clone());
+                }
+            }
+        }
+    }
+
+    let mut imported_names: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+    for ((path, _), (statement, names)) in &use_rewrites {
+        let Some(from) = syntax::module_location(path) else {
+            continue;
+        };
+        let masked = files.masked(path)?.to_string();
+        let top_level_of_target =
+            path == request.target && syntax::brace_depth(&masked, statement.range.start) == 0;
+
+        let moved_leaves: Vec<UseLeaf> = statement
+            .tree
+            .leaves()
+            .into_iter()
+            .filter(|leaf| {
+                !leaf.glob
+                    && leaf.path.last().is_some_and(|last| names.contains(last))
+                    && resolves_to(&from, &leaf.path[..leaf.path.len() - 1], &source_module)
+            })
+            .collect();
+        if moved_leaves.is_empty() {
+            continue;
+        }
+
+        let mut tree = statement.tree.clone();
+        let keep = tree.retain_leaves(&[], &|leaf| moved_leaves.contains(leaf));
+        let mut lines = Vec::new();
+        if keep {
+            lines.push(statement.render(&simplify(tree)));
+        }
+        for leaf in &moved_leaves {
+            let name = leaf.path.last().cloned().unwrap_or_default();
+            imported_names
+                .entry(path.clone())
+                .or_default()
+                .insert(name.clone());
+            if top_level_of_target && leaf.alias.is_none() {
+                continue;
+            }
+            let mut module_path = target_module.path_from(&from);
+            if leaf.path[0].starts_with("::") && from.crate_dir != target_module.crate_dir {
+                module_path = format!("::{module_path}");
+            }
+            lines.push(render_use(
+                &statement.visibility,
+                &module_path,
+                &name,
+                leaf.alias.as_deref(),
+            ));
+        }
+        rewritten_paths += moved_leaves.len();
+
+        let text = files.text(path)?.to_string();
+        let indent_start = syntax::line_start(&text, statement.range.start);
+        let indent: String = text[indent_start..statement.range.start]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+        let mut range = statement.range.clone();
+        if lines.is_empty() && text[indent_start..statement.range.start].trim().is_empty() {
+            range = indent_start..syntax::line_end(&text, statement.range.end);
+        }
+        files.push(
+            path,
+            TextEdit::new(range, lines.join(&format!("\n{indent}"))),
+        );
+    }
+
+    // Files that used the items unqualified now need an import.
+    let mut added_imports = Vec::new();
+    for (path, names) in &bare_names {
+        if path == request.target {
+            continue;
+        }
+        let already = imported_names.get(path);
+        let missing: Vec<&String> = names
+            .iter()
+            .filter(|name| already.is_none_or(|imported| !imported.contains(*name)))
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+        let Some(from) = syntax::module_location(path) else {
+            notes.push(format!(
+                "{} uses moved items but is not in a crate's src/ tree; update it manually",
+                path.display()
+            ));
+            continue;
+        };
+        let module_path = target_module.path_from(&from);
+        let text = files.text(path)?.to_string();
+        let offset = syntax::import_insertion_offset(&text);
+        let tree = if missing.len() == 1 {
+            format!("{module_path}::{}", missing[0])
+        } else {
+            let names: Vec<&str> = missing.iter().map(|name| name.as_str()).collect();
+            format!("{module_path}::{{{}}}", names.join(", "))
+        };
+        let mut line = format!("use {tree};\n");
+        if !text[..offset].contains("use ")
+            && offset < text.len()
+            && !text[offset..].starts_with('\n')
+        {
+            line.push('\n');
+        }
+        files.push(path, TextEdit::insert(offset, line));
+        added_imports.push(format!("{} ({tree})", path.display()));
+    }
+
+    for item in request.items {
+        for child in item.symbol["children"].as_array().into_iter().flatten() {
+            let range = edit::lsp_range_to_offsets(&source_text, &child["range"]);
+            if child["kind"].as_u64() == Some(8)
+                && !request.member_references.iter().any(|r| r.symbol == *child)
+                && !syntax::has_visibility(
+                    &source_text
+                        [range.start + syntax::item_header_offset(&source_text[range.clone()])..],
+                )
+            {
+                notes.push(format!(
+                    "Field `{}` of `{}` is private; code left in the source module can no longer access it",
+                    symbol_name(child),
+                    item.name
+                ));
+            }
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (path, edits) in &files.edits {
+        let old_text = files.texts[path].clone();
+        let new_text = edit::apply_text_edits(&old_text, edits)?;
+        if new_text != old_text {
+            changes.push(FileChange {
+                path: path.clone(),
+                old_text: Some(old_text),
+                new_text: Some(new_text),
+            });
+        }
+    }
+
+    let mut summary = String::new();
+    writeln!(
+        summary,
+        "Moved {} items from {} to {}:",
+        request.items.len(),
+        request.source.display(),
+        request.target.display()
+    )?;
+    for item in request.items {
+        writeln!(summary, "  - {}", symbol_name(&item.symbol))?;
+    }
+    writeln!(
+        summary,
+        "Updated {rewritten_paths} paths; new module path is {}",
+        target_module.path_from(&source_module)
+    )?;
+    if !target_imports.is_empty() {
+        writeln!(summary, "Imports carried over to the target:")?;
+        for leaf in &target_imports {
+            writeln!(summary, "  - use {};", leaf.to_tree())?;
+        }
+    }
+    if !added_imports.is_empty() {
+        writeln!(summary, "Imports added:")?;
+        for import in &added_imports {
+            writeln!(summary, "  - {import}")?;
+        }
+    }
+    if !visibility_changes.is_empty() {
+        writeln!(
+            summary,
+            "Made pub(crate): {}",
+            visibility_changes.join(", ")
+        )?;
+    }
+    writeln!(summary, "Files changed:")?;
+    for change in &changes {
+        writeln!(summary, "  - {}", change.path.display())?;
+    }
+    for note in &notes {
+        writeln!(summary, "Note: {note}")?;
+    }
+
+    Ok(MovePlan { changes, summary })
+}
+
+/// Collapse a group with a single child, e.g. `a::{B}` to `a::B`.
+fn simplify(tree: UseTree) -> UseTree {
+    match tree.kind {
+        UseTreeKind::Group(mut children) if children.len() == 1 => {
+            let child = simplify(children.remove(0));
+            let mut path = tree.path;
+            if child.path.first().map(String::as_str) == Some("self") && !path.is_empty() {
+                return UseTree {
+                    path,
+                    kind: child.kind,
+                };
+            }
+            path.extend(child.path);
+            UseTree {
+                path,
+                kind: child.kind,
+            }
+        }
+        UseTreeKind::Group(children) => UseTree {
+            path: tree.path,
+            kind: UseTreeKind::Group(children.into_iter().map(simplify).collect()),
+        },
+        kind => UseTree {
+            path: tree.path,
+            kind,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SOURCE: &str = "use std::collections::HashMap;
+
+pub struct Helper;
+
+fn implode() -> u32 {
+    0
+}
+
+struct Cache {
+    map: HashMap<u32, Helper>,
+}
+
+impl Cache {
+    fn get(&self) -> u32 {
+        implode()
+    }
+}
+
+pub fn use_cache(cache: &Cache) -> u32 {
+    cache.get()
+}
+";
+
+    fn range_of(text: &str, snippet: &str) -> Value {
+        let start = text.find(snippet).unwrap();
+        edit::offsets_to_lsp_range(text, &(start..start + snippet.len()))
+    }
+
+    fn symbol(name: &str, kind: u64, snippet: &str, children: Vec<Value>) -> Value {
+        json!({
+            "name": name,
+            "kind": kind,
+            "range": range_of(SOURCE, snippet),
+            "children": children,
+        })
+    }
+
+    fn symbols() -> Vec<Value> {
+        let map = symbol("map", 8, "map: HashMap<u32, Helper>", vec![]);
+        let get = symbol(
+            "get",
+            6,
+            "fn get(&self) -> u32 {\n        implode()\n    }",
+            vec![],
+        );
+        vec![
+            symbol("Helper", 23, "pub struct Helper;", vec![]),
+            symbol("implode", 12, "fn implode() -> u32 {\n    0\n}", vec![]),
+            symbol(
+                "Cache",
+                23,
+                "struct Cache {\n    map: HashMap<u32, Helper>,\n}",
+                vec![map],
+            ),
+            symbol(
+                "impl Cache",
+                19,
+                "impl Cache {\n    fn get(&self) -> u32 {\n        implode()\n    }\n}",
+                vec![get],
+            ),
+            symbol("use_cache", 12, "pub fn use_cache", vec![]),
+        ]
+    }
+
+    #[test]
+    fn selects_items_with_their_impls_and_private_members() {
+        let symbols = symbols();
+        let items = select_items(&symbols, &["Cache"]).unwrap();
+        let names: Vec<(&str, bool)> = items
+            .iter()
+            .map(|item| (item.name.as_str(), item.is_impl))
+            .collect();
+        assert_eq!(names, [("Cache", false), ("Cache", true)]);
+
+        let members = private_members(SOURCE, &items);
+        let names: Vec<&str> = members.iter().map(symbol_name).collect();
+        assert_eq!(names, ["map", "get"]);
+
+        // `implode` is a function, not an impl of `ode`.
+        let error = select_items(&symbols, &["ode"]).unwrap_err().to_string();
+        assert!(error.starts_with("Items not found in source file: ode."));
+    }
+
+    #[test]
+    fn plan_move_widens_visibility_and_carries_imports() {
+        let root = std::env::temp_dir().join(format!("rustmcp-move-items-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"fixture\"\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "mod a;\nmod b;\n").unwrap();
+        let source = root.join("src/a.rs");
+        let target = root.join("src/b.rs");
+        std::fs::write(&source, SOURCE).unwrap();
+        std::fs::write(&target, "pub struct Other;\n").unwrap();
+
+        let symbols = symbols();
+        let items = select_items(&symbols, &["Cache"]).unwrap();
+
+        let uri = edit::path_to_uri(&source);
+        let location = |snippet: &str| json!({ "uri": uri, "range": range_of(SOURCE, snippet) });
+        let references = [SymbolReferences {
+            symbol: symbols[2].clone(),
+            locations: vec![
+                location("Cache {\n    map"),
+                location("Cache {\n    fn"),
+                location("Cache) -> u32"),
+            ],
+        }];
+        let member_references = [SymbolReferences {
+            symbol: symbols[3]["children"][0].clone(),
+            locations: vec![location("get(&self)"), location("get()")],
+        }];
+
+        let plan = plan_move(MoveRequest {
+            source: &source,
+            target: &target,
+            symbols: &symbols,
+            items: &items,
+            references: &references,
+            member_references: &member_references,
+        });
+        std::fs::remove_dir_all(&root).unwrap();
+        let plan = plan.unwrap();
+
+        let text = |path: &Path| {
+            let change = plan.changes.iter().find(|change| change.path == path);
+            change.and_then(|change| change.new_text.clone()).unwrap()
+        };
+        assert_eq!(
+            text(&source),
+            "use crate::b::Cache;
+
+pub struct Helper;
+
+pub(crate) fn implode() -> u32 {
+    0
+}
+
+pub fn use_cache(cache: &Cache) -> u32 {
+    cache.get()
+}
+"
+        );
+        assert_eq!(
+            text(&target),
+            "use std::collections::HashMap;
+use crate::a::Helper;
+use crate::a::implode;
+
+pub struct Other;
+
+pub(crate) struct Cache {
+    map: HashMap<u32, Helper>,
+}
+
+impl Cache {
+    pub(crate) fn get(&self) -> u32 {
+        implode()
+    }
+}
+"
+        );
+        assert!(
+            plan.summary
+                .contains("Made pub(crate): Cache, get, implode")
+        );
+        assert!(plan.summary.contains("Field `map` of `Cache` is private"));
+    }
+}
@@ -0,0 +1,114 @@
+// File structure from `textDocument/documentSymbol`, as a compact text tree
+// for reading and as trimmed-down JSON for programmatic use.
+
+use serde_json::{Value, json};
+
+/// Rust-flavoured label for an LSP `SymbolKind` as rust-analyzer uses them.
+pub fn kind_label(kind: u64) -> &'static str {
+    match kind {
+        2 | 3 => "mod",
+        5 | 23 => "struct",
+        6 | 12 => "fn",
+        7 | 8 => "field",
+        10 => "enum",
+        11 => "trait",
+        13 => "static",
+        14 => "const",
+        19 => "impl",
+        22 => "variant",
+        26 => "type",
+        _ => "item",
+    }
+}
+
+/// 1-based inclusive line span of a `DocumentSymbol` (or a flat
+/// `SymbolInformation`).
+fn line_span(symbol: &Value) -> (u64, u64) {
+    let range = if symbol.get("range").is_some() {
+        &symbol["range"]
+    } else {
+        &symbol["location"]["range"]
+    };
+    (
+        range["start"]["line"].as_u64().unwrap_or(0) + 1,
+        range["end"]["line"].as_u64().unwrap_or(0) + 1,
+    )
+}
+
+fn header(symbol: &Value) -> String {
+    let name = symbol["name"].as_str().unwrap_or_default();
+    let kind = kind_label(symbol["kind"].as_u64().unwrap_or(0));
+    // Impl blocks are already named `impl Trait for Type`.
+    let mut text = if name.starts_with("impl") {
+        name.to_string()
+    } else {
+        format!("{kind} {name}")
+    };
+    if let Some(detail) = symbol["detail"].as_str().filter(|d| !d.is_empty()) {
+        text.push_str(&format!(": {detail}"));
+    }
+    text
+}
+
+/// Indented tree, one symbol per line with its line span. Children deeper
+/// than `max_depth` (0 = top level only) are summarized as a count.
+pub fn format_outline(symbols: &[Value], max_depth: Option<usize>) -> String {
+    fn render(symbols: &[Value], depth: usize, max_depth: Option<usize>, out: &mut String) {
+        for symbol in symbols {
+            let (start, end) = line_span(symbol);
+            let indent = "  ".repeat(depth);
+            let span = if start == end {
+                format!("{start}")
+            } else {
+                format!("{start}-{end}")
+            };
+            out.push_str(&format!("{indent}{} [{span}]\n", header(symbol)));
+            let children = symbol["children"].as_array().map(Vec::as_slice);
+            if let Some(children) = children.filter(|c| !c.is_empty()) {
+                if max_depth.is_some_and(|max| depth >= max) {
+                    let plural = if children.len() == 1 { "" } else { "s" };
+                    out.push_str(&format!(
+                        "{indent}  ... {} member{plural}\n",
+                        children.len()
+                    ));
+                } else {
+                    render(children, depth + 1, max_depth, out);
+                }
+            }
+        }
+    }
+
+    if symbols.is_empty() {
+        return "No symbols found".to_string();
+    }
+    let mut result = String::new();
+    render(symbols, 0, max_depth, &mut result);
+    result
+}
+
+/// The symbol tree with only names, kinds, signatures and line spans.
+pub fn outline_json(symbols: &[Value]) -> Value {
+    Value::Array(
+        symbols
+            .iter()
+            .map(|symbol| {
+                let (start, end) = line_span(symbol);
+                let mut node = json!({
+                    "name": symbol["name"],
+                    "kind": kind_label(symbol["kind"].as_u64().unwrap_or(0)),
+                    "start_line": start,
+                    "end_line": end,
+                });
+                if let Some(detail) = symbol["detail"].as_str() {
+                    node["detail"] = json!(detail);
+                }
+                if let Some(children) = symbol["children"].as_array()
+                    && !children.is_empty()
+                {
+                    node["children"] = outline_json(children);
+                }
+                node
+            })
+            .collect(),
+    )
+}
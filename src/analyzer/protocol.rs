@@ -57,3 +57,62 @@ pub fn create_formatting_params(file_path: &str) -> Value {
         }
     })
 }
+
+pub fn create_text_document_params(file_path: &str) -> Value {
+    json!({
+        "textDocument": {
+            "uri": format!("file://{}", file_path)
+        }
+    })
+}
+
+pub fn create_runnables_params(file_path: &str, position: Option<(u32, u32)>) -> Value {
+    let mut params = create_text_document_params(file_path);
+    if let Some((line, character)) = position {
+        params["position"] = json!({
+            "line": line,
+            "character": character
+        });
+    }
+    params
+}
+
+/// Params for `experimental/ssr`. The document and position give the rule's
+/// paths a resolution context; non-empty `selections` restrict matching to
+/// those ranges of the document, otherwise the whole workspace is searched.
+pub fn create_ssr_params(file_path: &str, query: &str, selections: Vec<Value>) -> Value {
+    let mut params = create_text_document_position_params(file_path, 0, 0);
+    params["query"] = json!(query);
+    params["parseOnly"] = json!(false);
+    params["selections"] = json!(selections);
+    params
+}
+
+pub fn create_code_action_params(
+    file_path: &str,
+    range: Value,
+    diagnostics: Vec<Value>,
+    only: Option<&str>,
+) -> Value {
+    let mut params = create_text_document_params(file_path);
+    params["range"] = range;
+    params["context"] = json!({ "diagnostics": diagnostics });
+    if let Some(kind) = only {
+        params["context"]["only"] = json!([kind]);
+    }
+    params
+}
+
+pub fn create_completion_params(
+    file_path: &str,
+    line: u32,
+    character: u32,
+    trigger_character: Option<&str>,
+) -> Value {
+    let mut params = create_text_document_position_params(file_path, line, character);
+    params["context"] = match trigger_character {
+        Some(character) => json!({ "triggerKind": 2, "triggerCharacter": character }),
+        None => json!({ "triggerKind": 1 }),
+    };
+    params
+}
@@ -0,0 +1,203 @@
+// Batch application of rust-analyzer quick fixes: one unambiguous fix per
+// diagnostic, merged with the fixes for other diagnostics as long as their
+// edits don't overlap. Conflicting fixes wait for the next round.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::code_actions::CodeAction;
+use crate::analyzer::diagnostics::Diagnostic;
+use crate::analyzer::edit::{self, FileChange, TextEdit};
+
+/// Passes over the diagnostics `fix_all_diagnostics` makes by default.
+pub const DEFAULT_FIX_ROUNDS: usize = 10;
+
+pub enum Choice {
+    Apply(CodeAction),
+    /// Several fixes and none preferred, e.g. imports from different crates.
+    Ambiguous(Vec<String>),
+    Unavailable,
+}
+
+/// Pick the fix to apply unattended: the preferred quick fix, or the only
+/// one offered.
+pub fn choose(actions: Vec<CodeAction>) -> Choice {
+    let mut fixes: Vec<CodeAction> = actions
+        .into_iter()
+        .filter(|action| {
+            action
+                .kind
+                .as_deref()
+                .is_some_and(|kind| kind.starts_with("quickfix"))
+        })
+        .collect();
+    if let Some(index) = fixes
+        .iter()
+        .position(|fix| fix.action["isPreferred"].as_bool() == Some(true))
+    {
+        return Choice::Apply(fixes.swap_remove(index));
+    }
+    match fixes.len() {
+        0 => Choice::Unavailable,
+        1 => Choice::Apply(fixes.remove(0)),
+        _ => Choice::Ambiguous(fixes.into_iter().map(|fix| fix.title).collect()),
+    }
+}
+
+/// Whether a fix's edits leave every file as it was.
+pub fn is_empty_fix(fix: &BTreeMap<PathBuf, Vec<TextEdit>>) -> bool {
+    fix.values().all(Vec::is_empty)
+}
+
+/// Non-overlapping edits collected during one round.
+#[derive(Debug, Default)]
+pub struct Batch {
+    edits: BTreeMap<PathBuf, Vec<TextEdit>>,
+}
+
+impl Batch {
+    /// Take the edits of one fix unless they overlap edits already taken.
+    /// A fix identical to one already taken is accepted as a no-op; a fix
+    /// without edits is rejected.
+    pub fn try_add(&mut self, fix: BTreeMap<PathBuf, Vec<TextEdit>>) -> bool {
+        if is_empty_fix(&fix) {
+            return false;
+        }
+        let same = |a: &TextEdit, b: &TextEdit| a.range == b.range && a.new_text == b.new_text;
+        let overlaps = |a: &TextEdit, b: &TextEdit| {
+            (a.range.start < b.range.end && b.range.start < a.range.end)
+                || (a.range.is_empty() && a.range == b.range)
+        };
+
+        let duplicate = fix.iter().all(|(path, edits)| {
+            let taken = self.edits.get(path).map(Vec::as_slice).unwrap_or_default();
+            edits
+                .iter()
+                .all(|edit| taken.iter().any(|other| same(edit, other)))
+        });
+        if duplicate {
+            return true;
+        }
+        let conflict = fix.iter().any(|(path, edits)| {
+            let taken = self.edits.get(path).map(Vec::as_slice).unwrap_or_default();
+            edits
+                .iter()
+                .any(|edit| taken.iter().any(|other| overlaps(edit, other)))
+        });
+        if conflict {
+            return false;
+        }
+        for (path, edits) in fix {
+            self.edits.entry(path).or_default().extend(edits);
+        }
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Where `offset` in `path` ends up once the batch is applied, for
+    /// offsets outside the edited ranges.
+    pub fn map_offset(&self, path: &Path, offset: usize) -> usize {
+        let edits = self.edits.get(path).map(Vec::as_slice).unwrap_or_default();
+        edits
+            .iter()
+            .filter(|edit| edit.range.end <= offset)
+            .fold(offset, |mapped, edit| {
+                mapped + edit.new_text.len() - edit.range.len()
+            })
+    }
+
+    /// New contents of every file the batch edits.
+    pub fn apply(&self, texts: &BTreeMap<PathBuf, String>) -> Result<BTreeMap<PathBuf, String>> {
+        let mut updated = BTreeMap::new();
+        for (path, edits) in &self.edits {
+            let text = texts
+                .get(path)
+                .ok_or_else(|| anyhow::anyhow!("No contents loaded for {}", path.display()))?;
+            updated.insert(path.clone(), edit::apply_text_edits(text, edits)?);
+        }
+        Ok(updated)
+    }
+}
+
+/// What one pass over the diagnostics found.
+#[derive(Debug, Default)]
+pub struct Round {
+    pub batch: Batch,
+    /// Diagnostics whose fix is in the batch, with the fix title.
+    pub fixed: Vec<(Diagnostic, String)>,
+    /// Diagnostics left alone, with the reason.
+    pub remaining: Vec<(Diagnostic, String)>,
+}
+
+pub fn format_report(
+    root: &Path,
+    applied: &[(usize, Diagnostic, String)],
+    remaining: &[(Diagnostic, String)],
+    changes: &[FileChange],
+    dry_run: bool,
+) -> String {
+    let rounds = applied.iter().map(|(round, ..)| *round).max().unwrap_or(0);
+    let mut result = if applied.is_empty() {
+        "No quick fixes applied\n".to_string()
+    } else {
+        format!(
+            "{} {} quick fixes in {rounds} round{} to {} files:\n",
+            if dry_run { "Would apply" } else { "Applied" },
+            applied.len(),
+            if rounds == 1 { "" } else { "s" },
+            changes.len()
+        )
+    };
+    for (round, diagnostic, title) in applied {
+        result.push_str(&format!(
+            "  [{round}] {} -> {title}\n",
+            diagnostic.render(root, true)
+        ));
+    }
+
+    if remaining.is_empty() {
+        result.push_str("\nNo diagnostics remain\n");
+    } else {
+        result.push_str(&format!("\n{} diagnostics remain:\n", remaining.len()));
+        for (diagnostic, reason) in remaining {
+            result.push_str(&format!("  {}: {reason}\n", diagnostic.render(root, true)));
+        }
+    }
+
+    for change in changes {
+        result.push_str(&format!("\n{}", edit::unified_diff(change)));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(edits: Vec<TextEdit>) -> BTreeMap<PathBuf, Vec<TextEdit>> {
+        BTreeMap::from([(PathBuf::from("/ws/src/lib.rs"), edits)])
+    }
+
+    #[test]
+    fn batch_rejects_empty_and_overlapping_fixes() {
+        let mut batch = Batch::default();
+        assert!(!batch.try_add(BTreeMap::new()));
+        assert!(!batch.try_add(fix(Vec::new())));
+        assert!(batch.is_empty());
+
+        assert!(batch.try_add(fix(vec![TextEdit::new(4..8, "x")])));
+        assert!(batch.try_add(fix(vec![TextEdit::new(4..8, "x")])));
+        assert!(!batch.try_add(fix(vec![TextEdit::new(6..10, "y")])));
+        assert!(batch.try_add(fix(vec![TextEdit::insert(0, "z")])));
+        assert!(!batch.try_add(fix(vec![TextEdit::insert(0, "w")])));
+
+        assert_eq!(batch.map_offset(Path::new("/ws/src/lib.rs"), 12), 10);
+        let texts = BTreeMap::from([(PathBuf::from("/ws/src/lib.rs"), "0123456789ab".into())]);
+        let updated = batch.apply(&texts).unwrap();
+        assert_eq!(updated[Path::new("/ws/src/lib.rs")], "z0123x89ab");
+    }
+}
@@ -0,0 +1,129 @@
+// rust-analyzer's `experimental/runnables` extension: the tests, binaries,
+// benches and doctests it can run, and the command line for each.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tokio::process::Command;
+
+use crate::analyzer::edit;
+
+#[derive(Debug, Clone)]
+pub struct Runnable {
+    pub label: String,
+    /// `file:line` of the item, 1-based.
+    pub location: Option<String>,
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub environment: BTreeMap<String, String>,
+}
+
+impl Runnable {
+    /// Parse one runnable, either of `"kind": "cargo"` or `"kind": "shell"`.
+    pub fn from_lsp(value: &Value) -> Option<Self> {
+        let args = &value["args"];
+        let strings = |value: &Value| -> Vec<String> {
+            value
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let (program, command_args) = match value["kind"].as_str()? {
+            "cargo" => {
+                let mut command_args = strings(&args["cargoArgs"]);
+                // Older rust-analyzer versions split these out.
+                command_args.extend(strings(&args["cargoExtraArgs"]));
+                let executable_args = strings(&args["executableArgs"]);
+                if !executable_args.is_empty() {
+                    command_args.push("--".to_string());
+                    command_args.extend(executable_args);
+                }
+                let program = args["overrideCargo"]
+                    .as_str()
+                    .unwrap_or("cargo")
+                    .to_string();
+                (program, command_args)
+            }
+            "shell" => (
+                args["program"].as_str()?.to_string(),
+                strings(&args["args"]),
+            ),
+            _ => return None,
+        };
+
+        let location = value["location"]["targetUri"].as_str().map(|uri| {
+            let line = value["location"]["targetSelectionRange"]["start"]["line"]
+                .as_u64()
+                .unwrap_or(0);
+            format!("{}:{}", edit::uri_to_path(uri).display(), line + 1)
+        });
+        let cwd = args["cwd"]
+            .as_str()
+            .or_else(|| args["workspaceRoot"].as_str())
+            .map(PathBuf::from);
+        let environment = args["environment"]
+            .as_object()
+            .map(|env| {
+                env.iter()
+                    .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            label: value["label"].as_str().unwrap_or_default().to_string(),
+            location,
+            program,
+            args: command_args,
+            cwd,
+            environment,
+        })
+    }
+
+    pub fn is_test(&self) -> bool {
+        self.program == "cargo" && self.args.first().is_some_and(|arg| arg == "test")
+    }
+
+    /// The invocation as it would be typed in a shell.
+    pub fn command_line(&self) -> String {
+        let mut parts = vec![self.program.clone()];
+        parts.extend(self.args.iter().map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) || arg.contains('"') {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            } else {
+                arg.clone()
+            }
+        }));
+        parts.join(" ")
+    }
+
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).envs(&self.environment);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        command
+    }
+}
+
+pub fn format_runnables(runnables: &[Runnable]) -> String {
+    if runnables.is_empty() {
+        return "No runnables found".to_string();
+    }
+    let mut result = format!("{} runnables:\n", runnables.len());
+    for (id, runnable) in runnables.iter().enumerate() {
+        result.push_str(&format!("\n[{id}] {}\n", runnable.label));
+        if let Some(location) = &runnable.location {
+            result.push_str(&format!("    at {location}\n"));
+        }
+        result.push_str(&format!("    $ {}\n", runnable.command_line()));
+    }
+    result
+}
@@ -0,0 +1,450 @@
+// API documentation from rustdoc JSON (`--output-format json`, nightly),
+// generated offline from the exact package versions a workspace builds
+// against and cached under the target directory.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::SystemTime;
+use tokio::process::Command;
+
+use crate::analyzer::crate_index;
+use crate::analyzer::sources::{self, LibraryPackage};
+use crate::analyzer::syntax;
+
+/// Lines of a type definition shown before it is cut off.
+const MAX_DEFINITION_LINES: usize = 40;
+
+/// Most names listed per methods / trait impls section.
+const MAX_LISTED: usize = 60;
+
+fn cache_path(target_dir: &Path, package: &LibraryPackage) -> PathBuf {
+    target_dir
+        .join("rust-mcp/rustdoc")
+        .join(format!("{}-{}.json", package.name, package.version))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Registry and git sources never change for a version; local packages are
+/// regenerated when a source file or the manifest is newer than the cache.
+fn cache_is_fresh(cache: &Path, package: &LibraryPackage) -> bool {
+    let Some(cached) = modified(cache) else {
+        return false;
+    };
+    if !package.local {
+        return true;
+    }
+    let Some(package_dir) = syntax::find_manifest_dir(&package.src_path) else {
+        return false;
+    };
+    let mut files = vec![package_dir.join("Cargo.toml")];
+    crate_index::rust_files(&package_dir, &package_dir, &mut files);
+    files
+        .iter()
+        .all(|file| modified(file).is_none_or(|time| time <= cached))
+}
+
+/// Rustdoc JSON for `package`, generated with the nightly `toolchain` args
+/// on a cache miss.
+pub async fn package_docs(
+    workspace: &Path,
+    toolchain: &[String],
+    package: &LibraryPackage,
+) -> Result<Value> {
+    let target_dir = crate::analyzer::cargo::target_directory(workspace).await?;
+    let cache = cache_path(&target_dir, package);
+    if !cache_is_fresh(&cache, package) {
+        // A separate target dir keeps nightly artifacts out of the regular
+        // build's way.
+        let build_dir = target_dir.join("rust-mcp/rustdoc-build");
+        let output = Command::new("cargo")
+            .args(toolchain)
+            .args(["rustdoc", "--offline", "--lib", "-p"])
+            .arg(format!("{}@{}", package.name, package.version))
+            .arg("--target-dir")
+            .arg(&build_dir)
+            .args(["--", "-Z", "unstable-options", "--output-format", "json"])
+            .current_dir(workspace)
+            .stdin(Stdio::null())
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "rustdoc failed for {} {}:\n{}",
+                package.name,
+                package.version,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let generated = build_dir
+            .join("doc")
+            .join(format!("{}.json", package.lib_name));
+        if let Some(parent) = cache.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&generated, &cache)?;
+    }
+    Ok(serde_json::from_slice(&std::fs::read(&cache)?)?)
+}
+
+/// Rustdoc JSON for a standard library crate from the `rust-docs-json`
+/// rustup component.
+pub async fn sysroot_docs(workspace: &Path, toolchain: &[String], name: &str) -> Result<Value> {
+    let output = Command::new("rustc")
+        .args(toolchain)
+        .args(["--print", "sysroot"])
+        .current_dir(workspace)
+        .output()
+        .await?;
+    let sysroot = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let path = Path::new(&sysroot)
+        .join("share/doc/rust/json")
+        .join(format!("{name}.json"));
+    if !path.is_file() {
+        return Err(anyhow::anyhow!(
+            "No rustdoc JSON for {name}; install it with `rustup component add --toolchain nightly rust-docs-json`"
+        ));
+    }
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+fn item<'a>(docs: &'a Value, id: &Value) -> Option<&'a Value> {
+    let key = match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    };
+    docs["index"].get(key)
+}
+
+/// The `inner` variant name (`function`, `struct`, ...) and its payload.
+fn inner(item: &Value) -> (&str, &Value) {
+    item["inner"]
+        .as_object()
+        .and_then(|inner| inner.iter().next())
+        .map(|(kind, value)| (kind.as_str(), value))
+        .unwrap_or(("item", &Value::Null))
+}
+
+fn ids(value: &Value) -> Vec<Value> {
+    value.as_array().cloned().unwrap_or_default()
+}
+
+/// Items of the documented crate whose canonical path matches `segments`:
+/// same last name, and the other segments appear in order (so re-exported
+/// paths like `tokio::sync::Mutex` find `tokio::sync::mutex::Mutex`).
+fn find_paths(docs: &Value, segments: &[String]) -> Vec<(Value, Vec<String>)> {
+    let Some(paths) = docs["paths"].as_object() else {
+        return Vec::new();
+    };
+    let mut found: Vec<(Value, Vec<String>)> = paths
+        .iter()
+        .filter(|(_, summary)| summary["crate_id"].as_u64() == Some(0))
+        .filter_map(|(id, summary)| {
+            let path: Vec<String> = summary["path"]
+                .as_array()?
+                .iter()
+                .filter_map(|segment| segment.as_str().map(String::from))
+                .collect();
+            if path.last() != segments.last() {
+                return None;
+            }
+            let mut rest = path.iter();
+            segments
+                .iter()
+                .all(|segment| rest.any(|p| p == segment))
+                .then(|| (Value::String(id.clone()), path))
+        })
+        .collect();
+    found.sort_by_key(|(_, path)| (path.len(), path.clone()));
+    found
+}
+
+fn impls_of(docs: &Value, owner: &Value) -> Vec<Value> {
+    let (_, payload) = inner(owner);
+    ids(&payload["impls"])
+        .iter()
+        .filter_map(|id| item(docs, id).cloned())
+        .collect()
+}
+
+fn trait_name(impl_payload: &Value) -> Option<&str> {
+    let trait_ref = &impl_payload["trait"];
+    trait_ref["path"]
+        .as_str()
+        .or_else(|| trait_ref["name"].as_str())
+}
+
+/// A member (method, associated const or type) of a type, looked up in its
+/// inherent impls first and then in trait impls.
+fn find_member<'a>(docs: &'a Value, owner: &Value, name: &str) -> Option<&'a Value> {
+    let mut impls = impls_of(docs, owner);
+    impls.sort_by_key(|imp| trait_name(inner(imp).1).is_some());
+    let (kind, payload) = inner(owner);
+    let trait_items = if kind == "trait" {
+        ids(&payload["items"])
+    } else {
+        Vec::new()
+    };
+    impls
+        .iter()
+        .flat_map(|imp| ids(&inner(imp).1["items"]))
+        .chain(trait_items)
+        .filter_map(|id| item(docs, &id))
+        .find(|member| member["name"].as_str() == Some(name))
+}
+
+/// Source of the item's declaration from its span: a function up to its
+/// body, a type definition in full (capped).
+fn definition(item: &Value, workspace: &Path) -> Option<String> {
+    let span = &item["span"];
+    let file = Path::new(span["filename"].as_str()?);
+    let file = if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        workspace.join(file)
+    };
+    let text = std::fs::read_to_string(file).ok()?;
+    let begin = span["begin"][0].as_u64()? as usize;
+    let end = span["end"][0].as_u64()? as usize;
+    let start = text
+        .lines()
+        .take(begin.saturating_sub(1))
+        .map(|line| line.len() + 1)
+        .sum::<usize>()
+        .min(text.len());
+    let masked = syntax::mask_comments_and_strings(&text);
+
+    let (kind, _) = inner(item);
+    let source = if matches!(kind, "function" | "trait" | "impl" | "module") {
+        let body = masked[start..]
+            .find(['{', ';'])
+            .map_or(text.len(), |index| start + index);
+        text[start..body].trim_end().to_string()
+    } else {
+        let end_offset = sources::item_end(&masked, start) + 1;
+        text[start..end_offset.min(text.len())].to_string()
+    };
+    // Field and variant docs are shown separately; drop them along with
+    // the indentation of nested items.
+    let first = source.lines().next().unwrap_or_default();
+    let indent = &first[..first.len() - first.trim_start().len()];
+    let mut lines: Vec<&str> = Vec::new();
+    for line in source.lines().take(end.saturating_sub(begin) + 1) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("///") || trimmed.starts_with("//!") {
+            continue;
+        }
+        if trimmed.is_empty() && lines.last().is_none_or(|last| last.trim().is_empty()) {
+            continue;
+        }
+        lines.push(line.strip_prefix(indent).unwrap_or(line));
+    }
+    let mut result = lines[..lines.len().min(MAX_DEFINITION_LINES)].join("\n");
+    if lines.len() > MAX_DEFINITION_LINES {
+        result.push_str("\n    ...");
+    }
+    Some(result)
+}
+
+/// Split docs into prose and the Rust code examples in them.
+fn split_examples(docs: &str) -> (String, Vec<String>) {
+    let mut prose = String::new();
+    let mut examples = Vec::new();
+    let mut example: Option<String> = None;
+    let mut in_other_block = false;
+    for line in docs.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        match (&mut example, fence) {
+            (Some(code), Some(_)) => {
+                examples.push(std::mem::take(code));
+                example = None;
+            }
+            (Some(code), None) => {
+                // Lines hidden from rendered docs with `# `.
+                if line.trim_start() != "#" && !line.trim_start().starts_with("# ") {
+                    code.push_str(line);
+                    code.push('\n');
+                }
+            }
+            (None, Some(info)) if !in_other_block => {
+                let info = info.trim();
+                let is_rust = info.is_empty()
+                    || info.split(',').all(|tag| {
+                        matches!(
+                            tag.trim(),
+                            "rust"
+                                | "no_run"
+                                | "should_panic"
+                                | "ignore"
+                                | "edition2018"
+                                | "edition2021"
+                                | "edition2024"
+                        )
+                    });
+                if is_rust {
+                    example = Some(String::new());
+                } else {
+                    in_other_block = true;
+                    prose.push_str(line);
+                    prose.push('\n');
+                }
+            }
+            (None, Some(_)) => {
+                in_other_block = false;
+                prose.push_str(line);
+                prose.push('\n');
+            }
+            (None, None) => {
+                prose.push_str(line);
+                prose.push('\n');
+            }
+        }
+    }
+    (prose.trim().to_string(), examples)
+}
+
+fn list(title: &str, mut names: Vec<String>) -> String {
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return String::new();
+    }
+    let more = names.len().saturating_sub(MAX_LISTED);
+    names.truncate(MAX_LISTED);
+    let mut text = format!("\n{title}: {}", names.join(", "));
+    if more > 0 {
+        text.push_str(&format!(" (+{more} more)"));
+    }
+    text.push('\n');
+    text
+}
+
+/// Render an item: definition, docs, examples and, for types and traits,
+/// their methods and trait impls.
+fn format_item(docs: &Value, item: &Value, path: &str, workspace: &Path) -> String {
+    let (kind, payload) = inner(item);
+    let mut result = format!("{path} ({kind})\n");
+    if let Some(definition) = definition(item, workspace) {
+        result.push_str(&format!("\n```rust\n{definition}\n```\n"));
+    }
+    if let Some(deprecation) = item["deprecation"].as_object() {
+        let note = deprecation
+            .get("note")
+            .and_then(|v| v.as_str())
+            .unwrap_or("no note");
+        result.push_str(&format!("\nDeprecated: {note}\n"));
+    }
+    let (prose, examples) = split_examples(item["docs"].as_str().unwrap_or_default());
+    if !prose.is_empty() {
+        result.push_str(&format!("\n{prose}\n"));
+    }
+    for example in examples {
+        result.push_str(&format!("\nExample:\n```rust\n{}```\n", example));
+    }
+
+    match kind {
+        "struct" | "enum" | "union" => {
+            let mut methods = Vec::new();
+            let mut traits = Vec::new();
+            for imp in impls_of(docs, item) {
+                let (_, imp) = inner(&imp);
+                if imp["is_synthetic"].as_bool() == Some(true) || !imp["blanket_impl"].is_null() {
+                    continue;
+                }
+                match trait_name(imp) {
+                    Some(name) => traits.push(name.to_string()),
+                    None => methods.extend(
+                        ids(&imp["items"])
+                            .iter()
+                            .filter_map(|id| self::item(docs, id)?["name"].as_str())
+                            .map(String::from),
+                    ),
+                }
+            }
+            if kind == "enum" {
+                let variants = ids(&payload["variants"])
+                    .iter()
+                    .filter_map(|id| self::item(docs, id)?["name"].as_str())
+                    .map(String::from)
+                    .collect();
+                result.push_str(&list("Variants", variants));
+            }
+            result.push_str(&list("Methods", methods));
+            result.push_str(&list("Trait impls", traits));
+        }
+        "trait" => {
+            let members = ids(&payload["items"])
+                .iter()
+                .filter_map(|id| self::item(docs, id)?["name"].as_str())
+                .map(String::from)
+                .collect();
+            result.push_str(&list("Items", members));
+            let implementors = ids(&payload["implementations"]).len();
+            if implementors > 0 {
+                result.push_str(&format!("\n{implementors} implementations in this crate\n"));
+            }
+        }
+        "module" => {
+            let members = ids(&payload["items"])
+                .iter()
+                .filter_map(|id| self::item(docs, id)?["name"].as_str())
+                .map(String::from)
+                .collect();
+            result.push_str(&list("Items", members));
+        }
+        _ => {}
+    }
+    result
+}
+
+/// Answer a query for `segments` (without the crate name) in `docs`.
+pub fn lookup(
+    docs: &Value,
+    crate_name: &str,
+    segments: &[String],
+    workspace: &Path,
+) -> Result<String> {
+    if segments.is_empty() {
+        let root = item(docs, &docs["root"])
+            .ok_or_else(|| anyhow::anyhow!("rustdoc JSON has no root item"))?;
+        return Ok(format_item(docs, root, crate_name, workspace));
+    }
+
+    let mut query = vec![crate_name.to_string()];
+    query.extend(segments.iter().cloned());
+    let found = find_paths(docs, &query);
+    if let Some((id, path)) = found.first()
+        && let Some(found_item) = item(docs, id)
+    {
+        let mut result = format_item(docs, found_item, &path.join("::"), workspace);
+        if found.len() > 1 {
+            let others: Vec<String> = found[1..]
+                .iter()
+                .take(10)
+                .map(|(_, path)| format!("  {}", path.join("::")))
+                .collect();
+            result.push_str(&format!("\nOther matches:\n{}\n", others.join("\n")));
+        }
+        return Ok(result);
+    }
+
+    // `Type::member`: resolve the owner, then look through its impls.
+    if let Some((member, owner)) = query.split_last()
+        && owner.len() > 1
+        && let Some((id, path)) = find_paths(docs, owner).first()
+        && let Some(owner_item) = item(docs, id)
+        && let Some(member_item) = find_member(docs, owner_item, member)
+    {
+        let path = format!("{}::{member}", path.join("::"));
+        return Ok(format_item(docs, member_item, &path, workspace));
+    }
+
+    Err(anyhow::anyhow!(
+        "No documented item {} found",
+        query.join("::")
+    ))
+}
@@ -0,0 +1,149 @@
+// Decoding of `textDocument/semanticTokens` results: the server sends a
+// flat array of delta-encoded integers, five per token, interpreted with
+// the legend it announced in its `initialize` result.
+
+use serde_json::Value;
+
+/// Token types and modifiers indexed by the integers in token data.
+#[derive(Debug, Clone, Default)]
+pub struct Legend {
+    pub token_types: Vec<String>,
+    pub token_modifiers: Vec<String>,
+}
+
+impl Legend {
+    /// Read `capabilities.semanticTokensProvider.legend` from an
+    /// `initialize` result.
+    pub fn from_capabilities(capabilities: &Value) -> Option<Self> {
+        let legend = &capabilities["semanticTokensProvider"]["legend"];
+        let strings = |value: &Value| -> Vec<String> {
+            value
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let token_types = strings(&legend["tokenTypes"]);
+        if token_types.is_empty() {
+            return None;
+        }
+        Some(Self {
+            token_types,
+            token_modifiers: strings(&legend["tokenModifiers"]),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SemanticToken {
+    /// 0-based line and UTF-16 column, as in LSP positions.
+    pub line: u32,
+    pub character: u32,
+    pub length: u32,
+    pub token_type: String,
+    pub modifiers: Vec<String>,
+    pub text: String,
+}
+
+/// Decode token data against `legend`, taking token text from `text`.
+pub fn decode(data: &[u64], legend: &Legend, text: &str) -> Vec<SemanticToken> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut tokens = Vec::with_capacity(data.len() / 5);
+    let (mut line, mut character) = (0u32, 0u32);
+    for chunk in data.chunks_exact(5) {
+        let delta_line = chunk[0] as u32;
+        if delta_line > 0 {
+            line += delta_line;
+            character = chunk[1] as u32;
+        } else {
+            character += chunk[1] as u32;
+        }
+        let length = chunk[2] as u32;
+        let token_type = legend
+            .token_types
+            .get(chunk[3] as usize)
+            .cloned()
+            .unwrap_or_else(|| format!("type{}", chunk[3]));
+        let modifiers = legend
+            .token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| *bit < 64 && chunk[4] & (1 << bit) != 0)
+            .map(|(_, modifier)| modifier.clone())
+            .collect();
+        let text = lines
+            .get(line as usize)
+            .map(|source| utf16_slice(source, character, length))
+            .unwrap_or_default();
+        tokens.push(SemanticToken {
+            line,
+            character,
+            length,
+            token_type,
+            modifiers,
+            text,
+        });
+    }
+    tokens
+}
+
+fn utf16_slice(line: &str, start: u32, length: u32) -> String {
+    let mut units = 0u32;
+    let mut result = String::new();
+    for ch in line.chars() {
+        if units >= start + length {
+            break;
+        }
+        if units >= start {
+            result.push(ch);
+        }
+        units += ch.len_utf16() as u32;
+    }
+    result
+}
+
+/// Tokens of one of `token_types` (if given) carrying all of `modifiers`.
+pub fn filter(
+    tokens: Vec<SemanticToken>,
+    token_types: &[String],
+    modifiers: &[String],
+) -> Vec<SemanticToken> {
+    tokens
+        .into_iter()
+        .filter(|token| token_types.is_empty() || token_types.contains(&token.token_type))
+        .filter(|token| modifiers.iter().all(|m| token.modifiers.contains(m)))
+        .collect()
+}
+
+/// One token per line as `line:column type [modifiers] text` (1-based), and
+/// a count per token type.
+pub fn format_tokens(tokens: &[SemanticToken]) -> String {
+    if tokens.is_empty() {
+        return "No matching semantic tokens".to_string();
+    }
+    let mut counts: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for token in tokens {
+        *counts.entry(token.token_type.as_str()).or_default() += 1;
+    }
+    let summary: Vec<String> = counts
+        .iter()
+        .map(|(token_type, count)| format!("{token_type}: {count}"))
+        .collect();
+    let mut result = format!("{} tokens ({})\n\n", tokens.len(), summary.join(", "));
+    for token in tokens {
+        result.push_str(&format!(
+            "{}:{} {}",
+            token.line + 1,
+            token.character + 1,
+            token.token_type
+        ));
+        if !token.modifiers.is_empty() {
+            result.push_str(&format!(" [{}]", token.modifiers.join(", ")));
+        }
+        result.push_str(&format!(" {}\n", token.text));
+    }
+    result
+}
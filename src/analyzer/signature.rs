@@ -0,0 +1,104 @@
+// Rendering of `textDocument/signatureHelp` results with the active
+// parameter marked.
+
+use serde_json::Value;
+
+/// Byte range of a parameter inside its signature label. Labels are either
+/// a substring or a pair of UTF-16 offsets.
+fn parameter_range(signature_label: &str, parameter: &Value) -> Option<(usize, usize)> {
+    match &parameter["label"] {
+        Value::String(text) => {
+            let start = signature_label.find(text.as_str())?;
+            Some((start, start + text.len()))
+        }
+        Value::Array(offsets) => {
+            let byte_offset = |units: u64| {
+                let mut seen = 0;
+                for (index, ch) in signature_label.char_indices() {
+                    if seen >= units {
+                        return index;
+                    }
+                    seen += ch.len_utf16() as u64;
+                }
+                signature_label.len()
+            };
+            let start = byte_offset(offsets.first()?.as_u64()?);
+            let end = byte_offset(offsets.get(1)?.as_u64()?);
+            Some((start, end))
+        }
+        _ => None,
+    }
+}
+
+fn first_paragraph(documentation: &Value) -> Option<String> {
+    documentation
+        .as_str()
+        .or_else(|| documentation["value"].as_str())?
+        .split("\n\n")
+        .map(str::trim)
+        .find(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| paragraph.replace('\n', " "))
+}
+
+pub fn format_signature_help(help: &Value) -> String {
+    let Some(signatures) = help["signatures"].as_array().filter(|s| !s.is_empty()) else {
+        return "No signature help at this position".to_string();
+    };
+    let active_signature = help["activeSignature"].as_u64().unwrap_or(0) as usize;
+
+    let mut result = if signatures.len() == 1 {
+        String::new()
+    } else {
+        format!("{} signatures:\n\n", signatures.len())
+    };
+    for (index, signature) in signatures.iter().enumerate() {
+        let label = signature["label"].as_str().unwrap_or_default();
+        let parameters = signature["parameters"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let active_parameter = signature["activeParameter"]
+            .as_u64()
+            .or_else(|| help["activeParameter"].as_u64())
+            .map(|active| active as usize)
+            .filter(|&active| active < parameters.len());
+
+        let marker = if index == active_signature && signatures.len() > 1 {
+            "> "
+        } else {
+            ""
+        };
+        let highlighted = active_parameter
+            .and_then(|active| parameter_range(label, &parameters[active]))
+            .map(|(start, end)| {
+                format!(
+                    "{}**{}**{}",
+                    &label[..start],
+                    &label[start..end],
+                    &label[end..]
+                )
+            })
+            .unwrap_or_else(|| label.to_string());
+        result.push_str(&format!("{marker}{highlighted}\n"));
+
+        for (position, parameter) in parameters.iter().enumerate() {
+            let Some((start, end)) = parameter_range(label, parameter) else {
+                continue;
+            };
+            let active = if Some(position) == active_parameter {
+                " (active)"
+            } else {
+                ""
+            };
+            result.push_str(&format!("    {position}: {}{active}\n", &label[start..end]));
+            if let Some(docs) = first_paragraph(&parameter["documentation"]) {
+                result.push_str(&format!("       {docs}\n"));
+            }
+        }
+        if let Some(docs) = first_paragraph(&signature["documentation"]) {
+            result.push_str(&format!("    {docs}\n"));
+        }
+        result.push('\n');
+    }
+    result.trim_end().to_string()
+}
@@ -0,0 +1,501 @@
+// Source locations outside the workspace: crates unpacked in the cargo
+// registry, git dependencies checked out by cargo, and the standard library
+// from the rust-src component. Used to label and read navigation targets.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::analyzer::crate_index;
+use crate::analyzer::edit;
+use crate::analyzer::syntax;
+
+/// Lines of source shown for each location by default.
+pub const SNIPPET_LINES: usize = 8;
+
+/// Most lines `read_source` returns for one item or range.
+pub const MAX_SOURCE_LINES: usize = 200;
+
+/// Crates shipped with the toolchain, read from the rust-src component.
+pub const SYSROOT_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// Where a source file comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceOrigin {
+    Workspace,
+    /// `name-version` directory under `registry/src/<index>/`.
+    Registry(String),
+    /// Checkout directory under `git/checkouts/`.
+    Git(String),
+    /// Standard library crate (`core`, `alloc`, `std`, ...).
+    Sysroot(String),
+}
+
+impl SourceOrigin {
+    pub fn of(path: &Path) -> Self {
+        let components: Vec<String> = path
+            .iter()
+            .map(|component| component.to_string_lossy().into_owned())
+            .collect();
+        let after = |sequence: &[&str], skip: usize| {
+            components
+                .windows(sequence.len())
+                .position(|window| window.iter().zip(sequence).all(|(a, b)| a == b))
+                .and_then(|index| components.get(index + sequence.len() + skip))
+                .cloned()
+        };
+        if let Some(package) = after(&["registry", "src"], 1) {
+            return Self::Registry(package);
+        }
+        if let Some(checkout) = after(&["git", "checkouts"], 0) {
+            return Self::Git(checkout);
+        }
+        if let Some(name) = after(&["rustlib", "src", "rust", "library"], 0) {
+            return Self::Sysroot(name);
+        }
+        Self::Workspace
+    }
+
+    pub fn label(&self) -> Option<String> {
+        match self {
+            Self::Workspace => None,
+            Self::Registry(package) => Some(format!("registry: {package}")),
+            Self::Git(checkout) => Some(format!("git: {checkout}")),
+            Self::Sysroot(name) => Some(format!("sysroot: {name}")),
+        }
+    }
+}
+
+/// A navigation target: the file, the range to show and the 0-based line of
+/// the name itself.
+#[derive(Debug, Clone)]
+pub struct SourceLocation {
+    pub path: PathBuf,
+    pub range: Value,
+    pub line: u64,
+}
+
+/// Normalize a `Location`, `Location[]` or `LocationLink[]` response.
+pub fn locations_from_lsp(result: &Value) -> Vec<SourceLocation> {
+    let items = match result {
+        Value::Array(items) => items.clone(),
+        Value::Null => Vec::new(),
+        single => vec![single.clone()],
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let (uri, range, selection) = match item.get("targetUri") {
+                Some(uri) => (
+                    uri.as_str()?,
+                    &item["targetRange"],
+                    &item["targetSelectionRange"],
+                ),
+                None => (item["uri"].as_str()?, &item["range"], &item["range"]),
+            };
+            Some(SourceLocation {
+                path: edit::uri_to_path(uri),
+                range: range.clone(),
+                line: selection["start"]["line"].as_u64().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Numbered lines `start..=end` (0-based) of `text`, cut off after
+/// `max_lines`.
+pub fn snippet(text: &str, start: usize, end: usize, max_lines: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let end = end.min(lines.len().saturating_sub(1));
+    let shown_end = end.min(start + max_lines.saturating_sub(1));
+    let width = (shown_end + 1).to_string().len();
+    let mut result = String::new();
+    for (index, line) in lines.iter().enumerate().take(shown_end + 1).skip(start) {
+        result.push_str(&format!("{:>width$} | {line}\n", index + 1));
+    }
+    if shown_end < end {
+        result.push_str(&format!("{:>width$} | ...\n", ""));
+    }
+    result
+}
+
+/// `path:line` headers with the origin of non-workspace files and a source
+/// snippet for each location.
+pub fn format_locations(title: &str, locations: &[SourceLocation]) -> String {
+    if locations.is_empty() {
+        return format!("No {title} found");
+    }
+    let mut result = format!("{} {title}:\n", locations.len());
+    for location in locations {
+        result.push_str(&format!(
+            "\n{}:{}",
+            location.path.display(),
+            location.line + 1
+        ));
+        if let Some(label) = SourceOrigin::of(&location.path).label() {
+            result.push_str(&format!(" [{label}]"));
+        }
+        result.push('\n');
+        match std::fs::read_to_string(&location.path) {
+            Ok(text) => {
+                let start = location.range["start"]["line"]
+                    .as_u64()
+                    .unwrap_or(location.line) as usize;
+                let end = location.range["end"]["line"].as_u64().unwrap_or(0) as usize;
+                result.push_str(&snippet(&text, start, end.max(start), SNIPPET_LINES));
+            }
+            Err(e) => result.push_str(&format!("  (source unavailable: {e})\n")),
+        }
+    }
+    result
+}
+
+/// `library/` of the rust-src component of the toolchain used in `dir`.
+pub async fn sysroot_library(dir: &Path) -> Result<PathBuf> {
+    let output = Command::new("rustc")
+        .args(["--print", "sysroot"])
+        .current_dir(dir)
+        .output()
+        .await?;
+    let sysroot = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let library = Path::new(&sysroot).join("lib/rustlib/src/rust/library");
+    if !library.is_dir() {
+        return Err(anyhow::anyhow!(
+            "Standard library sources not found; install them with `rustup component add rust-src`"
+        ));
+    }
+    Ok(library)
+}
+
+/// Directories a relative source path may be given against: unpacked
+/// registry crates, git checkouts and the sysroot library.
+fn source_roots(library: Option<&Path>) -> Vec<PathBuf> {
+    let home = crate_index::cargo_home();
+    let mut roots = Vec::new();
+    let subdirectories = |dir: &Path| -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        dirs.sort();
+        dirs
+    };
+    roots.extend(subdirectories(&home.join("registry/src")));
+    for checkout in subdirectories(&home.join("git/checkouts")) {
+        roots.extend(subdirectories(&checkout));
+    }
+    roots.extend(library.map(Path::to_path_buf));
+    roots
+}
+
+/// Resolve a source file given as an absolute path, relative to the
+/// current directory, or relative to one of the dependency source roots
+/// (e.g. `tokio-1.38.0/src/sync/mutex.rs` or `core/src/option.rs`).
+pub async fn resolve_file(file_path: &str) -> Result<PathBuf> {
+    let path = edit::absolute_path(file_path)?;
+    if path.is_file() {
+        return Ok(path);
+    }
+    let library = sysroot_library(&std::env::current_dir()?).await.ok();
+    source_roots(library.as_deref())
+        .into_iter()
+        .map(|root| root.join(file_path))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "{file_path} not found in the workspace, the cargo registry, git checkouts or rust-src"
+            )
+        })
+}
+
+/// A package with a library target in the dependency graph of a workspace.
+#[derive(Debug, Clone)]
+pub struct LibraryPackage {
+    pub name: String,
+    pub version: String,
+    /// Crate name of the library target, with `-` replaced by `_`.
+    pub lib_name: String,
+    pub src_path: PathBuf,
+    /// A workspace member or path dependency rather than a registry or git
+    /// package.
+    pub local: bool,
+}
+
+/// Library packages cargo resolves for `workspace`, without network access.
+pub async fn library_packages(workspace: &Path) -> Result<Vec<LibraryPackage>> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--offline", "--format-version", "1"])
+        .current_dir(workspace)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let metadata: Value = serde_json::from_slice(&output.stdout)?;
+    let mut packages = Vec::new();
+    for package in metadata["packages"].as_array().into_iter().flatten() {
+        for target in package["targets"].as_array().into_iter().flatten() {
+            let is_lib = target["kind"].as_array().is_some_and(|kinds| {
+                kinds.iter().any(|kind| {
+                    matches!(kind.as_str(), Some("lib" | "rlib" | "dylib" | "proc-macro"))
+                })
+            });
+            if is_lib
+                && let (Some(name), Some(src_path)) =
+                    (target["name"].as_str(), target["src_path"].as_str())
+            {
+                packages.push(LibraryPackage {
+                    name: package["name"].as_str().unwrap_or_default().to_string(),
+                    version: package["version"].as_str().unwrap_or_default().to_string(),
+                    lib_name: name.replace('-', "_"),
+                    src_path: PathBuf::from(src_path),
+                    local: package["source"].is_null(),
+                });
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// The newest version of crate `name` among `packages`.
+pub fn find_library(packages: Vec<LibraryPackage>, name: &str) -> Option<LibraryPackage> {
+    let name = name.replace('-', "_");
+    packages
+        .into_iter()
+        .filter(|package| package.lib_name == name)
+        .max_by(|a, b| crate_index::compare_versions(&a.version, &b.version))
+}
+
+/// Root source file of the library target of crate `name` as compiled in
+/// `workspace`: the package cargo resolved if metadata is available, else
+/// the newest unpacked registry version; std crates come from rust-src.
+pub async fn crate_root(workspace: &Path, name: &str) -> Result<PathBuf> {
+    let name = name.replace('-', "_");
+    if SYSROOT_CRATES.contains(&name.as_str()) {
+        let root = sysroot_library(workspace)
+            .await?
+            .join(&name)
+            .join("src/lib.rs");
+        return Ok(root);
+    }
+
+    if let Ok(packages) = library_packages(workspace).await
+        && let Some(package) = find_library(packages, &name)
+    {
+        return Ok(package.src_path);
+    }
+
+    let mut newest: Option<(String, PathBuf)> = None;
+    for root in source_roots(None) {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let Some((package, version)) = file_name.rsplit_once('-') else {
+                continue;
+            };
+            let lib = entry.path().join("src/lib.rs");
+            if package.replace('-', "_") == name
+                && lib.is_file()
+                && newest
+                    .as_ref()
+                    .is_none_or(|(best, _)| crate_index::compare_versions(version, best).is_gt())
+            {
+                newest = Some((version.to_string(), lib));
+            }
+        }
+    }
+    newest.map(|(_, lib)| lib).ok_or_else(|| {
+        anyhow::anyhow!("Crate {name} is not a dependency and not in the registry cache")
+    })
+}
+
+/// Where an item definition was found.
+#[derive(Debug, Clone)]
+pub struct ItemMatch {
+    pub path: PathBuf,
+    /// 0-based lines of the item including its docs and attributes.
+    pub start_line: usize,
+    pub end_line: usize,
+    score: usize,
+}
+
+const ITEM_KEYWORDS: &[&str] = &[
+    "fn",
+    "struct",
+    "enum",
+    "union",
+    "trait",
+    "type",
+    "mod",
+    "const",
+    "static",
+    "macro_rules!",
+];
+
+/// The `{` opening the block that contains `offset`, if any.
+fn enclosing_block(masked: &str, offset: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, byte) in masked.as_bytes()[..offset].iter().enumerate().rev() {
+        match byte {
+            b'}' => depth += 1,
+            b'{' if depth == 0 => return Some(index),
+            b'{' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Start of the item whose keyword is at `keyword`, moved up over doc
+/// comments and attributes, as a 0-based line.
+fn item_start_line(text: &str, keyword: usize) -> usize {
+    let lines: Vec<&str> = text[..syntax::line_start(text, keyword)].lines().collect();
+    let mut start = lines.len();
+    while start > 0 {
+        let line = lines[start - 1].trim_start();
+        if line.starts_with("///") || line.starts_with("#[") || line.starts_with("#![doc") {
+            start -= 1;
+        } else {
+            break;
+        }
+    }
+    start
+}
+
+/// End offset of the item whose name ends at `from`: its closing brace, or
+/// the `;` of a body-less item.
+pub fn item_end(masked: &str, from: usize) -> usize {
+    let mut nesting = 0usize;
+    for (index, byte) in masked.bytes().enumerate().skip(from) {
+        match byte {
+            b'(' | b'[' => nesting += 1,
+            b')' | b']' => nesting = nesting.saturating_sub(1),
+            b';' if nesting == 0 => return index,
+            b'{' if nesting == 0 => {
+                return syntax::matching_brace(masked, index).unwrap_or(masked.len());
+            }
+            _ => {}
+        }
+    }
+    masked.len()
+}
+
+/// Find the definition of `segments` (a path below the crate root, such as
+/// `["sync", "Mutex", "lock"]`) in the package of `crate_root`. Items are
+/// matched by name; module segments in the file path and an enclosing
+/// `impl`/`trait` mentioning the parent type rank candidates. Re-exports
+/// are handled by searching the whole package rather than following `use`.
+pub fn find_item(crate_root: &Path, segments: &[String]) -> Vec<ItemMatch> {
+    let Some(name) = segments.last() else {
+        return Vec::new();
+    };
+    let parent_type = segments
+        .len()
+        .checked_sub(2)
+        .map(|index| &segments[index])
+        .filter(|parent| parent.starts_with(|c: char| c.is_ascii_uppercase()));
+    let modules: Vec<&String> = segments[..segments.len() - 1]
+        .iter()
+        .filter(|segment| Some(*segment) != parent_type)
+        .collect();
+
+    let package = syntax::find_manifest_dir(crate_root)
+        .or_else(|| crate_root.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let mut files = Vec::new();
+    crate_index::rust_files(&package, &package, &mut files);
+    files.sort();
+
+    let mut matches = Vec::new();
+    for file in files {
+        let Ok(text) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        let masked = syntax::mask_comments_and_strings(&text);
+        let module_path: Vec<String> = file
+            .strip_prefix(&package)
+            .map(|relative| {
+                relative
+                    .iter()
+                    .map(|c| c.to_string_lossy().trim_end_matches(".rs").to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let in_tests = module_path
+            .iter()
+            .any(|c| matches!(c.as_str(), "tests" | "benches" | "examples"));
+
+        for offset in syntax::find_word(&masked, name) {
+            let before = masked[..offset].trim_end();
+            let Some(keyword) = ITEM_KEYWORDS.iter().find(|keyword| {
+                before.ends_with(*keyword)
+                    && !before[..before.len() - keyword.len()]
+                        .bytes()
+                        .next_back()
+                        .is_some_and(syntax::is_ident_byte)
+            }) else {
+                continue;
+            };
+
+            let mut score = modules
+                .iter()
+                .filter(|module| module_path.contains(module))
+                .count()
+                * 2;
+            if let Some(parent) = parent_type {
+                let header = enclosing_block(&masked, offset).map(|open| {
+                    let start = masked[..open]
+                        .rfind([';', '}', '{'])
+                        .map_or(0, |index| index + 1);
+                    &masked[start..open]
+                });
+                match header {
+                    Some(header)
+                        if syntax::contains_word(header, parent)
+                            && (syntax::contains_word(header, "impl")
+                                || syntax::contains_word(header, "trait")) =>
+                    {
+                        score += 10;
+                        if !syntax::contains_word(header, "for") {
+                            score += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            } else if syntax::brace_depth(&masked, offset) == 0 {
+                score += 1;
+            }
+            if *keyword != "mod" || segments.len() == 1 {
+                score += 1;
+            }
+            if !in_tests {
+                score += 3;
+            }
+
+            let end = (item_end(&masked, offset + name.len()) + 1).min(text.len());
+            matches.push(ItemMatch {
+                path: file.clone(),
+                start_line: item_start_line(&text, before.len() - keyword.len()),
+                end_line: text[..end].lines().count().saturating_sub(1),
+                score,
+            });
+        }
+    }
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.start_line.cmp(&b.start_line))
+    });
+    matches
+}
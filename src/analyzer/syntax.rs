@@ -0,0 +1,848 @@
+// Lightweight textual helpers for Rust sources.
+// These are not a parser; they cover the shapes the refactoring tools need
+// (use trees, item headers, module paths) on top of rust-analyzer results.
+
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Return a copy of `text` where comments and string/char literal contents
+/// are replaced by spaces. Byte offsets and newlines are preserved, so the
+/// result can be searched for code tokens and mapped back to `text`.
+pub fn mask_comments_and_strings(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut masked = bytes.to_vec();
+    let mut i = 0;
+
+    let blank = |masked: &mut Vec<u8>, range: Range<usize>| {
+        for byte in &mut masked[range] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    };
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = text[i..].find('\n').map(|n| i + n).unwrap_or(bytes.len());
+                blank(&mut masked, i..end);
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let mut depth = 0;
+                let mut j = i;
+                while j < bytes.len() {
+                    if bytes[j] == b'/' && bytes.get(j + 1) == Some(&b'*') {
+                        depth += 1;
+                        j += 2;
+                    } else if bytes[j] == b'*' && bytes.get(j + 1) == Some(&b'/') {
+                        depth -= 1;
+                        j += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        j += 1;
+                    }
+                }
+                blank(&mut masked, i..j.min(bytes.len()));
+                i = j;
+            }
+            b'r' if is_raw_string_start(bytes, i) => {
+                let mut j = i + 1;
+                let mut hashes = 0;
+                while bytes.get(j) == Some(&b'#') {
+                    hashes += 1;
+                    j += 1;
+                }
+                let terminator = format!("\"{}", "#".repeat(hashes));
+                let body_start = j + 1;
+                let end = text[body_start..]
+                    .find(&terminator)
+                    .map(|n| body_start + n)
+                    .unwrap_or(bytes.len());
+                blank(&mut masked, body_start..end);
+                i = (end + terminator.len()).min(bytes.len());
+            }
+            b'"' => {
+                let mut j = i + 1;
+                while j < bytes.len() && bytes[j] != b'"' {
+                    if bytes[j] == b'\\' {
+                        j += 1;
+                    }
+                    j += 1;
+                }
+                let end = j.min(bytes.len());
+                blank(&mut masked, i + 1..end);
+                i = end + 1;
+            }
+            b'\'' => {
+                let end = char_literal_end(bytes, i);
+                if let Some(end) = end {
+                    blank(&mut masked, i + 1..end);
+                    i = end + 1;
+                } else {
+                    i += 1;
+                }
+            }
+            _ => {
+                if !bytes[i].is_ascii() {
+                    masked[i] = b' ';
+                }
+                i += 1;
+            }
+        }
+    }
+
+    // Every non-ASCII byte has been blanked, so this cannot fail.
+    String::from_utf8(masked).unwrap_or_default()
+}
+
+fn is_raw_string_start(bytes: &[u8], i: usize) -> bool {
+    if i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_') {
+        // Allow the `br"..."` prefix but not identifiers ending in `r`.
+        if !(bytes[i - 1] == b'b' && (i < 2 || !is_ident_byte(bytes[i - 2]))) {
+            return false;
+        }
+    }
+    let mut j = i + 1;
+    while bytes.get(j) == Some(&b'#') {
+        j += 1;
+    }
+    bytes.get(j) == Some(&b'"')
+}
+
+fn char_literal_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let next = *bytes.get(start + 1)?;
+    if next == b'\\' {
+        // Skip the escaped byte so that `'\''` ends at its last quote.
+        let mut j = start + 3;
+        while j < bytes.len() && bytes[j] != b'\'' && bytes[j] != b'\n' {
+            j += 1;
+        }
+        return (bytes.get(j) == Some(&b'\'')).then_some(j);
+    }
+    // A char literal holds exactly one (possibly multi-byte) character,
+    // anything else is a lifetime or label.
+    let width = utf8_width(next);
+    (bytes.get(start + 1 + width) == Some(&b'\'')).then_some(start + 1 + width)
+}
+
+fn utf8_width(first: u8) -> usize {
+    match first {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    }
+}
+
+pub fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Find whole-word occurrences of `word` in (masked) `text`.
+pub fn find_word(text: &str, word: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    text.match_indices(word)
+        .map(|(index, _)| index)
+        .filter(|&index| {
+            let before = index == 0 || !is_ident_byte(bytes[index - 1]);
+            let end = index + word.len();
+            let after = end >= bytes.len() || !is_ident_byte(bytes[end]);
+            before && after
+        })
+        .collect()
+}
+
+pub fn contains_word(text: &str, word: &str) -> bool {
+    !find_word(text, word).is_empty()
+}
+
+/// Brace nesting depth at `offset` in masked text.
+pub fn brace_depth(masked: &str, offset: usize) -> usize {
+    let mut depth: isize = 0;
+    for byte in masked.as_bytes()[..offset.min(masked.len())].iter() {
+        match byte {
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth.max(0) as usize
+}
+
+/// Offset of the `}` closing the `{` at `open` in masked text.
+pub fn matching_brace(masked: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, byte) in masked.bytes().enumerate().skip(open) {
+        match byte {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Offset of the item keyword (or visibility) inside an item's text, after
+/// leading doc comments and attributes.
+pub fn item_header_offset(item_text: &str) -> usize {
+    let bytes = item_text.as_bytes();
+    let mut i = 0;
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if item_text[i..].starts_with("//") {
+            i = item_text[i..]
+                .find('\n')
+                .map(|n| i + n + 1)
+                .unwrap_or(bytes.len());
+        } else if item_text[i..].starts_with("/*") {
+            i = item_text[i..]
+                .find("*/")
+                .map(|n| i + n + 2)
+                .unwrap_or(bytes.len());
+        } else if item_text[i..].starts_with("#[") {
+            let masked = mask_comments_and_strings(&item_text[i..]);
+            let mut depth = 0;
+            let mut end = masked.len();
+            for (index, byte) in masked.bytes().enumerate() {
+                match byte {
+                    b'[' => depth += 1,
+                    b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = index + 1;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            i += end;
+        } else {
+            return i;
+        }
+    }
+}
+
+/// Whether the item header at the start of `header` declares a visibility.
+pub fn has_visibility(header: &str) -> bool {
+    header.starts_with("pub ") || header.starts_with("pub(") || header.starts_with("pub\n")
+}
+
+/// Where new `use` declarations should be inserted: after the last
+/// top-level `use`, or after inner attributes and module docs.
+pub fn import_insertion_offset(text: &str) -> usize {
+    let masked = mask_comments_and_strings(text);
+    if let Some(last) = use_statements(text)
+        .into_iter()
+        .rfind(|statement| brace_depth(&masked, statement.range.start) == 0)
+    {
+        return line_end(text, last.range.end);
+    }
+
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("//!") || trimmed.starts_with("#![") || trimmed.trim().is_empty() {
+            offset += line.len();
+        } else {
+            break;
+        }
+    }
+    offset
+}
+
+/// Base name of the self type in an impl header, e.g. `Foo` for
+/// `<T> Trait for crate::Foo<T> where T: Send`.
+pub fn impl_self_type(header: &str) -> String {
+    let mut header = header.trim_start();
+    if header.starts_with('<') {
+        let mut depth = 0;
+        for (index, c) in header.char_indices() {
+            match c {
+                '<' => depth += 1,
+                '>' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        header = &header[index + 1..];
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(&index) = find_word(header, "where").first() {
+        header = &header[..index];
+    }
+    if let Some(&index) = find_word(header, "for").last() {
+        header = &header[index + 3..];
+    }
+    let path = header.trim().trim_start_matches(['&', '*']);
+    let path = path.split('<').next().unwrap_or(path);
+    path.rsplit("::").next().unwrap_or(path).trim().to_string()
+}
+
+/// Offset just past the newline that terminates the line containing `offset`.
+pub fn line_end(text: &str, offset: usize) -> usize {
+    text[offset..]
+        .find('\n')
+        .map(|n| offset + n + 1)
+        .unwrap_or(text.len())
+}
+
+/// Offset of the start of the line containing `offset`.
+pub fn line_start(text: &str, offset: usize) -> usize {
+    text[..offset].rfind('\n').map(|n| n + 1).unwrap_or(0)
+}
+
+/// A `use` declaration located in a file.
+#[derive(Debug, Clone)]
+pub struct UseStatement {
+    /// Byte range including the visibility and the trailing `;`.
+    pub range: Range<usize>,
+    pub visibility: String,
+    pub tree: UseTree,
+}
+
+impl UseStatement {
+    pub fn render(&self, tree: &UseTree) -> String {
+        format!("{}use {tree};", self.visibility)
+    }
+}
+
+/// Every `use` declaration in `text`, at any nesting depth.
+pub fn use_statements(text: &str) -> Vec<UseStatement> {
+    let masked = mask_comments_and_strings(text);
+    let bytes = masked.as_bytes();
+    let mut statements = Vec::new();
+
+    for index in find_word(&masked, "use") {
+        let after = index + 3;
+        if !bytes.get(after).is_some_and(|b| b.is_ascii_whitespace()) {
+            continue;
+        }
+        let Some(semicolon) = masked[after..].find(';').map(|n| after + n) else {
+            continue;
+        };
+        let start = visibility_start(&masked, index);
+        let Some(tree) = UseTree::parse(&masked[after..semicolon]) else {
+            continue;
+        };
+        statements.push(UseStatement {
+            range: start..semicolon + 1,
+            visibility: text[start..index].to_string(),
+            tree,
+        });
+    }
+
+    statements
+}
+
+fn visibility_start(masked: &str, use_index: usize) -> usize {
+    let before = masked[..use_index].trim_end();
+    let head = match before.strip_suffix(')') {
+        Some(inner) => match inner.rfind('(') {
+            Some(open) => masked[..open].trim_end(),
+            None => return use_index,
+        },
+        None => before,
+    };
+    if head.ends_with("pub") && (head.len() == 3 || !is_ident_byte(head.as_bytes()[head.len() - 4]))
+    {
+        head.len() - 3
+    } else {
+        use_index
+    }
+}
+
+/// The `use` declaration that contains `offset`, if any.
+pub fn enclosing_use_statement(text: &str, offset: usize) -> Option<UseStatement> {
+    use_statements(text)
+        .into_iter()
+        .find(|statement| statement.range.contains(&offset))
+}
+
+/// A parsed use tree, e.g. `crate::a::{b, c::D as E, f::*}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UseTree {
+    pub path: Vec<String>,
+    pub kind: UseTreeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UseTreeKind {
+    Simple(Option<String>),
+    Glob,
+    Group(Vec<UseTree>),
+}
+
+/// A single imported path produced by flattening a use tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UseLeaf {
+    pub path: Vec<String>,
+    pub alias: Option<String>,
+    pub glob: bool,
+}
+
+impl UseLeaf {
+    /// The name this leaf binds in scope, if any.
+    pub fn binding(&self) -> Option<&str> {
+        if self.glob {
+            return None;
+        }
+        if let Some(alias) = &self.alias {
+            return Some(alias);
+        }
+        match self.path.last().map(String::as_str) {
+            Some("self") => self
+                .path
+                .len()
+                .checked_sub(2)
+                .map(|index| self.path[index].as_str()),
+            other => other,
+        }
+    }
+
+    pub fn to_tree(&self) -> UseTree {
+        UseTree {
+            path: self.path.clone(),
+            kind: if self.glob {
+                UseTreeKind::Glob
+            } else {
+                UseTreeKind::Simple(self.alias.clone())
+            },
+        }
+    }
+}
+
+impl UseTree {
+    pub fn parse(input: &str) -> Option<Self> {
+        let tokens = tokenize_use_tree(input)?;
+        let mut position = 0;
+        let tree = parse_tree(&tokens, &mut position)?;
+        (position == tokens.len()).then_some(tree)
+    }
+
+    pub fn leaves(&self) -> Vec<UseLeaf> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&[], &mut leaves);
+        leaves
+    }
+
+    fn collect_leaves(&self, prefix: &[String], leaves: &mut Vec<UseLeaf>) {
+        let mut path = prefix.to_vec();
+        path.extend(self.path.iter().cloned());
+        match &self.kind {
+            UseTreeKind::Simple(alias) => leaves.push(UseLeaf {
+                path,
+                alias: alias.clone(),
+                glob: false,
+            }),
+            UseTreeKind::Glob => leaves.push(UseLeaf {
+                path,
+                alias: None,
+                glob: true,
+            }),
+            UseTreeKind::Group(children) => {
+                for child in children {
+                    child.collect_leaves(&path, leaves);
+                }
+            }
+        }
+    }
+
+    /// Remove the leaves for which `predicate` returns true. Returns false
+    /// if nothing is left of the tree.
+    pub fn retain_leaves(
+        &mut self,
+        prefix: &[String],
+        predicate: &dyn Fn(&UseLeaf) -> bool,
+    ) -> bool {
+        let mut path = prefix.to_vec();
+        path.extend(self.path.iter().cloned());
+        match &mut self.kind {
+            UseTreeKind::Simple(alias) => !predicate(&UseLeaf {
+                path,
+                alias: alias.clone(),
+                glob: false,
+            }),
+            UseTreeKind::Glob => !predicate(&UseLeaf {
+                path,
+                alias: None,
+                glob: true,
+            }),
+            UseTreeKind::Group(children) => {
+                children.retain_mut(|child| child.retain_leaves(&path, predicate));
+                !children.is_empty()
+            }
+        }
+    }
+}
+
+impl fmt::Display for UseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.join("::");
+        match &self.kind {
+            UseTreeKind::Simple(alias) => {
+                write!(f, "{path}")?;
+                if let Some(alias) = alias {
+                    write!(f, " as {alias}")?;
+                }
+                Ok(())
+            }
+            UseTreeKind::Glob if path.is_empty() => write!(f, "*"),
+            UseTreeKind::Glob => write!(f, "{path}::*"),
+            UseTreeKind::Group(children) => {
+                let children: Vec<String> = children.iter().map(|c| c.to_string()).collect();
+                if path.is_empty() {
+                    write!(f, "{{{}}}", children.join(", "))
+                } else {
+                    write!(f, "{path}::{{{}}}", children.join(", "))
+                }
+            }
+        }
+    }
+}
+
+fn tokenize_use_tree(input: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == ':' && chars.get(i + 1) == Some(&':') {
+            tokens.push("::".to_string());
+            i += 2;
+        } else if matches!(c, '{' | '}' | ',' | '*') {
+            tokens.push(c.to_string());
+            i += 1;
+        } else if c.is_alphanumeric() || c == '_' || c == '#' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '#')
+            {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            return None;
+        }
+    }
+    Some(tokens)
+}
+
+fn parse_tree(tokens: &[String], position: &mut usize) -> Option<UseTree> {
+    let mut path = Vec::new();
+    if tokens.get(*position).map(String::as_str) == Some("::") {
+        // Leading `::` marks an extern crate path; keep it on the first segment.
+        *position += 1;
+        let name = tokens.get(*position)?;
+        path.push(format!("::{name}"));
+        *position += 1;
+        if tokens.get(*position).map(String::as_str) != Some("::") {
+            return Some(finish_simple(tokens, position, path));
+        }
+        *position += 1;
+    }
+
+    loop {
+        match tokens.get(*position).map(String::as_str) {
+            Some("{") => {
+                *position += 1;
+                let mut children = Vec::new();
+                loop {
+                    if tokens.get(*position).map(String::as_str) == Some("}") {
+                        *position += 1;
+                        break;
+                    }
+                    children.push(parse_tree(tokens, position)?);
+                    match tokens.get(*position).map(String::as_str) {
+                        Some(",") => *position += 1,
+                        Some("}") => {}
+                        _ => return None,
+                    }
+                }
+                return Some(UseTree {
+                    path,
+                    kind: UseTreeKind::Group(children),
+                });
+            }
+            Some("*") => {
+                *position += 1;
+                return Some(UseTree {
+                    path,
+                    kind: UseTreeKind::Glob,
+                });
+            }
+            Some(token) if token != "," && token != "}" && token != "::" => {
+                path.push(token.to_string());
+                *position += 1;
+                if tokens.get(*position).map(String::as_str) == Some("::") {
+                    *position += 1;
+                } else {
+                    return Some(finish_simple(tokens, position, path));
+                }
+            }
+            _ => return None,
+        }
+    }
+}
+
+fn finish_simple(tokens: &[String], position: &mut usize, path: Vec<String>) -> UseTree {
+    let mut alias = None;
+    if tokens.get(*position).map(String::as_str) == Some("as")
+        && let Some(name) = tokens.get(*position + 1)
+    {
+        alias = Some(name.clone());
+        *position += 2;
+    }
+    UseTree {
+        path,
+        kind: UseTreeKind::Simple(alias),
+    }
+}
+
+/// Where a source file sits in its crate's module tree.
+#[derive(Debug, Clone)]
+pub struct ModuleLocation {
+    pub crate_dir: PathBuf,
+    /// Crate name as written in paths (dashes replaced by underscores).
+    pub crate_name: String,
+    pub segments: Vec<String>,
+}
+
+impl ModuleLocation {
+    /// Render the module path, e.g. `crate::tools::cargo`.
+    pub fn path_from(&self, other: &ModuleLocation) -> String {
+        let root = if self.crate_dir == other.crate_dir {
+            "crate"
+        } else {
+            self.crate_name.as_str()
+        };
+        std::iter::once(root)
+            .chain(self.segments.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// Resolve a path prefix written in this module into absolute module
+    /// segments. Returns `None` for paths that do not start in this crate.
+    pub fn resolve(&self, path: &[String]) -> Option<Vec<String>> {
+        let first = path.first()?;
+        let first = first.trim_start_matches("::");
+        if first == "crate" || first == self.crate_name {
+            return Some(path[1..].to_vec());
+        }
+        if path[0].starts_with("::") {
+            return None;
+        }
+        let mut resolved = self.segments.clone();
+        let mut rest = path;
+        if first == "self" {
+            rest = &path[1..];
+        } else {
+            while rest.first().map(String::as_str) == Some("super") {
+                resolved.pop()?;
+                rest = &rest[1..];
+            }
+        }
+        resolved.extend(rest.iter().cloned());
+        Some(resolved)
+    }
+}
+
+/// Nearest directory above `path` containing a `Cargo.toml`.
+pub fn find_manifest_dir(path: &Path) -> Option<PathBuf> {
+    let mut current = if path.is_dir() {
+        Some(path)
+    } else {
+        path.parent()
+    };
+    while let Some(dir) = current {
+        if dir.join("Cargo.toml").is_file() {
+            return Some(dir.to_path_buf());
+        }
+        current = dir.parent();
+    }
+    None
+}
+
+/// The `[package] name` of the manifest in `crate_dir`.
+pub fn package_name(crate_dir: &Path) -> Option<String> {
+    let manifest = std::fs::read_to_string(crate_dir.join("Cargo.toml")).ok()?;
+    let mut in_package = false;
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package
+            && let Some(value) = line.strip_prefix("name")
+            && let Some(value) = value.trim_start().strip_prefix('=')
+        {
+            return Some(value.trim().trim_matches('"').to_string());
+        }
+    }
+    None
+}
+
+pub fn module_location(path: &Path) -> Option<ModuleLocation> {
+    let crate_dir = find_manifest_dir(path)?;
+    let crate_name = package_name(&crate_dir)
+        .unwrap_or_else(|| {
+            crate_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+        .replace('-', "_");
+
+    let relative = path.strip_prefix(crate_dir.join("src")).ok()?;
+    let mut segments: Vec<String> = relative
+        .iter()
+        .map(|component| component.to_string_lossy().into_owned())
+        .collect();
+    if let Some(last) = segments.last_mut()
+        && let Some(stem) = last.strip_suffix(".rs")
+    {
+        *last = stem.to_string();
+    }
+
+    let is_root = matches!(segments.as_slice(), [name] if name == "lib" || name == "main")
+        || matches!(segments.first().map(String::as_str), Some("bin")) && segments.len() == 2;
+    if is_root {
+        segments.clear();
+    } else if segments.last().map(String::as_str) == Some("mod") {
+        segments.pop();
+    }
+
+    Some(ModuleLocation {
+        crate_dir,
+        crate_name,
+        segments,
+    })
+}
+
+/// Collect a `a::b::` path prefix that ends right before `offset` in masked
+/// text. Returns the byte range of the prefix (including the final `::`)
+/// and its segments.
+pub fn path_prefix_before(masked: &str, offset: usize) -> Option<(Range<usize>, Vec<String>)> {
+    let bytes = masked.as_bytes();
+    let mut cursor = offset;
+    let mut segments = Vec::new();
+    let mut start = offset;
+
+    loop {
+        let mut i = cursor;
+        while i > 0 && bytes[i - 1].is_ascii_whitespace() {
+            i -= 1;
+        }
+        if i < 2 || &masked[i - 2..i] != "::" {
+            break;
+        }
+        i -= 2;
+        while i > 0 && bytes[i - 1].is_ascii_whitespace() {
+            i -= 1;
+        }
+        let end = i;
+        while i > 0 && is_ident_byte(bytes[i - 1]) {
+            i -= 1;
+        }
+        if i == end {
+            // A leading `::` with no segment before it.
+            if let Some(first) = segments.first_mut() {
+                *first = format!("::{first}");
+            }
+            start = end;
+            break;
+        }
+        segments.insert(0, masked[i..end].to_string());
+        start = i;
+        cursor = i;
+    }
+
+    (!segments.is_empty()).then_some((start..offset, segments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_comments_strings_and_chars() {
+        let text = "let a = \"x // y\"; // note\nlet b = '\\''; /* c /* d */ */ let c = '{';";
+        let masked = mask_comments_and_strings(text);
+        assert_eq!(masked.len(), text.len());
+        assert!(!masked.contains("note"));
+        assert!(!masked.contains("x //"));
+        assert!(!masked.contains('{'));
+        assert!(masked.contains("let b = '  ';"));
+        assert!(masked.contains("let c = ' ';"));
+    }
+
+    #[test]
+    fn keeps_lifetimes_and_masks_raw_strings() {
+        let text = "fn f<'a>(x: &'a str) -> &'a str { r#\"{ \"quoted\" }\"# ; x }";
+        let masked = mask_comments_and_strings(text);
+        assert!(masked.starts_with("fn f<'a>(x: &'a str) -> &'a str {"));
+        assert_eq!(masked.matches('{').count(), 1);
+        assert!(!masked.contains("quoted"));
+    }
+
+    #[test]
+    fn parses_and_flattens_use_trees() {
+        let tree = UseTree::parse("crate::a::{b, c::D as E, f::*, self}").unwrap();
+        let leaves: Vec<(String, Option<String>, bool)> = tree
+            .leaves()
+            .into_iter()
+            .map(|leaf| (leaf.path.join("::"), leaf.alias, leaf.glob))
+            .collect();
+        assert_eq!(
+            leaves,
+            vec![
+                ("crate::a::b".to_string(), None, false),
+                ("crate::a::c::D".to_string(), Some("E".to_string()), false),
+                ("crate::a::f".to_string(), None, true),
+                ("crate::a::self".to_string(), None, false),
+            ]
+        );
+        assert_eq!(tree.leaves()[3].binding(), Some("a"));
+        assert!(UseTree::parse("a::{b").is_none());
+    }
+
+    #[test]
+    fn finds_use_statements_with_visibility() {
+        let text = "pub(crate) use std::fmt;\nmod m {\n    use super::X;\n}\n";
+        let statements = use_statements(text);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].visibility, "pub(crate) ");
+        assert_eq!(
+            &text[statements[0].range.clone()],
+            "pub(crate) use std::fmt;"
+        );
+        assert_eq!(statements[1].tree.leaves()[0].path, ["super", "X"]);
+    }
+
+    #[test]
+    fn extracts_impl_self_type() {
+        assert_eq!(impl_self_type(" Foo"), "Foo");
+        assert_eq!(
+            impl_self_type("<T> Trait for crate::Foo<T> where T: Send"),
+            "Foo"
+        );
+        assert_eq!(impl_self_type("<T: Clone> Wrapper<T>"), "Wrapper");
+        assert_eq!(impl_self_type(" Display for &Bar"), "Bar");
+    }
+}
@@ -0,0 +1,430 @@
+// Inventory of `unsafe` code: a textual scan for unsafe blocks, functions,
+// impls, traits, attributes and extern blocks, refined with the unsafe
+// operations rust-analyzer's semantic tokens report inside them.
+
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::crate_index;
+use crate::analyzer::edit;
+use crate::analyzer::semantic::SemanticToken;
+use crate::analyzer::sources::{self, LibraryPackage};
+use crate::analyzer::syntax;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UnsafeKind {
+    Block,
+    Fn,
+    Impl,
+    Trait,
+    ExternBlock,
+    Attribute,
+}
+
+impl UnsafeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Block => "unsafe block",
+            Self::Fn => "unsafe fn",
+            Self::Impl => "unsafe impl",
+            Self::Trait => "unsafe trait",
+            Self::ExternBlock => "extern block",
+            Self::Attribute => "unsafe attribute",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnsafeSite {
+    pub kind: UnsafeKind,
+    /// Byte range from the keyword to the end of the site's body.
+    pub range: Range<usize>,
+    /// 1-based line of the keyword.
+    pub line: usize,
+    /// The unsafe fn, impl or trait itself, or the function enclosing a
+    /// block or extern block.
+    pub item: Option<String>,
+    /// Whether a `// SAFETY:` comment or `# Safety` doc section precedes it.
+    pub documented: bool,
+    /// Unsafe operations inside the site, from semantic tokens.
+    pub operations: Vec<String>,
+}
+
+/// Find every unsafe site in a file.
+pub fn scan(text: &str) -> Vec<UnsafeSite> {
+    let masked = syntax::mask_comments_and_strings(text);
+    let containers = containers(&masked);
+    let functions = functions(&masked, &containers);
+    let enclosing_fn = |offset: usize| {
+        functions
+            .iter()
+            .filter(|(_, body)| body.contains(&offset))
+            .min_by_key(|(_, body)| body.len())
+            .map(|(name, _)| name.clone())
+    };
+
+    let mut sites = Vec::new();
+    let mut push = |kind: UnsafeKind, range: Range<usize>, item: Option<String>| {
+        sites.push(UnsafeSite {
+            kind,
+            line: text[..range.start].matches('\n').count() + 1,
+            documented: documented(text, range.start),
+            range,
+            item,
+            operations: Vec::new(),
+        });
+    };
+
+    for index in syntax::find_word(&masked, "unsafe") {
+        let after = skip_whitespace(&masked, index + 6);
+        let rest = &masked[after..];
+        if rest.starts_with('{') {
+            let end = syntax::matching_brace(&masked, after).unwrap_or(masked.len());
+            push(UnsafeKind::Block, index..end + 1, enclosing_fn(index));
+        } else if rest.starts_with('(') && masked[..index].trim_end().ends_with('[') {
+            let end = syntax::line_end(&masked, index);
+            push(UnsafeKind::Attribute, index..end, None);
+        } else if word_after(&masked, after, "impl").is_some() {
+            let open = masked[after..].find('{').map(|n| after + n);
+            let header = open.map(|open| collapse(&masked[after + 4..open]));
+            let end = sources::item_end(&masked, after);
+            push(UnsafeKind::Impl, index..end + 1, header);
+        } else if let Some(name) = word_after(&masked, after, "trait") {
+            let end = sources::item_end(&masked, after);
+            push(UnsafeKind::Trait, index..end + 1, Some(name));
+        } else {
+            let after_abi = skip_abi(&masked, after);
+            if let Some(name) = word_after(&masked, after_abi, "fn") {
+                let end = sources::item_end(&masked, after_abi);
+                let qualified = functions
+                    .iter()
+                    .find(|(_, body)| body.start > after_abi && body.start <= end)
+                    .map(|(name, _)| name.clone())
+                    .unwrap_or(name);
+                push(UnsafeKind::Fn, index..end + 1, Some(qualified));
+            } else if after_abi > after && masked[after_abi..].starts_with('{') {
+                let end = syntax::matching_brace(&masked, after_abi).unwrap_or(masked.len());
+                push(UnsafeKind::ExternBlock, index..end + 1, enclosing_fn(index));
+            }
+        }
+    }
+
+    // Extern blocks without the (edition 2024) `unsafe` prefix.
+    for index in syntax::find_word(&masked, "extern") {
+        if masked[..index].trim_end().ends_with("unsafe") {
+            continue;
+        }
+        let after_abi = skip_abi(&masked, index);
+        if masked[after_abi..].starts_with('{') {
+            let end = syntax::matching_brace(&masked, after_abi).unwrap_or(masked.len());
+            push(UnsafeKind::ExternBlock, index..end + 1, enclosing_fn(index));
+        }
+    }
+
+    sites.sort_by_key(|site| site.range.start);
+    sites
+}
+
+fn skip_whitespace(masked: &str, from: usize) -> usize {
+    from + (masked[from..].len() - masked[from..].trim_start().len())
+}
+
+/// Skip `extern` and an optional ABI string starting at `from`.
+fn skip_abi(masked: &str, from: usize) -> usize {
+    if word_after(masked, from, "extern").is_none() {
+        return from;
+    }
+    let mut after = skip_whitespace(masked, from + 6);
+    if masked[after..].starts_with('"')
+        && let Some(close) = masked[after + 1..].find('"')
+    {
+        after = skip_whitespace(masked, after + close + 2);
+    }
+    after
+}
+
+/// If `keyword` starts at `from`, the identifier following it.
+fn word_after(masked: &str, from: usize, keyword: &str) -> Option<String> {
+    let rest = masked[from..].strip_prefix(keyword)?;
+    if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let name: String = rest
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    (!name.is_empty() || keyword == "impl" || keyword == "extern").then_some(name)
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether the keyword at `index` starts an item rather than a type
+/// (`-> impl Trait`, `: fn()`).
+fn at_item_position(masked: &str, index: usize) -> bool {
+    let before = masked[..index].trim_end();
+    before.is_empty()
+        || before.ends_with(['}', ';', '{', ']'])
+        || ["unsafe", "default", "pub", ")"]
+            .iter()
+            .any(|word| before.ends_with(word))
+}
+
+/// `impl` and `trait` bodies with the name functions inside them are
+/// qualified with.
+fn containers(masked: &str) -> Vec<(String, Range<usize>)> {
+    let mut containers = Vec::new();
+    for keyword in ["impl", "trait"] {
+        for index in syntax::find_word(masked, keyword) {
+            if !at_item_position(masked, index) {
+                continue;
+            }
+            let end = sources::item_end(masked, index);
+            let Some(open) = masked[index..end].find('{').map(|n| index + n) else {
+                continue;
+            };
+            let header = &masked[index + keyword.len()..open];
+            let name = if keyword == "trait" {
+                header
+                    .trim_start()
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect()
+            } else {
+                syntax::impl_self_type(header)
+            };
+            containers.push((name, open..end));
+        }
+    }
+    containers
+}
+
+/// Named functions with a body, qualified by their impl or trait.
+fn functions(masked: &str, containers: &[(String, Range<usize>)]) -> Vec<(String, Range<usize>)> {
+    let mut functions = Vec::new();
+    for index in syntax::find_word(masked, "fn") {
+        let Some(name) = word_after(masked, index, "fn").filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        let end = sources::item_end(masked, index);
+        if masked.as_bytes().get(end) != Some(&b'}') {
+            continue;
+        }
+        let Some(open) = masked[index..end].find('{').map(|n| index + n) else {
+            continue;
+        };
+        let qualified = containers
+            .iter()
+            .filter(|(_, body)| body.contains(&index))
+            .min_by_key(|(_, body)| body.len())
+            .map(|(container, _)| format!("{container}::{name}"))
+            .unwrap_or(name);
+        functions.push((qualified, open..end + 1));
+    }
+    functions
+}
+
+/// Whether the site at `offset` has a safety comment on its line or in
+/// the comment and attribute lines directly above.
+fn documented(text: &str, offset: usize) -> bool {
+    let mentions_safety = |line: &str| {
+        let lower = line.to_lowercase();
+        lower.contains("safety:") || lower.contains("# safety")
+    };
+    let start = syntax::line_start(text, offset);
+    if mentions_safety(&text[start..offset]) {
+        return true;
+    }
+    for line in text[..start].lines().rev() {
+        let line = line.trim_start();
+        let is_comment_or_attribute = ["//", "/*", "*", "#["]
+            .iter()
+            .any(|prefix| line.starts_with(prefix));
+        if !is_comment_or_attribute {
+            return false;
+        }
+        if mentions_safety(line) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Attribute tokens carrying the `unsafe` modifier to the innermost site
+/// containing them. The `unsafe` keywords themselves are skipped.
+pub fn attach_operations(text: &str, sites: &mut [UnsafeSite], tokens: &[SemanticToken]) {
+    for token in tokens {
+        if token.token_type == "keyword" || !token.modifiers.iter().any(|m| m == "unsafe") {
+            continue;
+        }
+        let offset = edit::position_to_offset(
+            text,
+            &json!({ "line": token.line, "character": token.character }),
+        );
+        if let Some(site) = sites
+            .iter_mut()
+            .filter(|site| site.range.contains(&offset))
+            .min_by_key(|site| site.range.len())
+        {
+            let operation = format!("{} ({})", token.text, token.token_type);
+            if !site.operations.contains(&operation) {
+                site.operations.push(operation);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UnsafeCounts {
+    pub kinds: BTreeMap<UnsafeKind, usize>,
+    pub undocumented: usize,
+}
+
+impl UnsafeCounts {
+    pub fn add(&mut self, site: &UnsafeSite) {
+        *self.kinds.entry(site.kind).or_default() += 1;
+        if !site.documented && site.kind != UnsafeKind::Attribute {
+            self.undocumented += 1;
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.kinds.values().sum()
+    }
+
+    pub fn summary(&self) -> String {
+        let kinds: Vec<String> = self
+            .kinds
+            .iter()
+            .map(|(kind, count)| {
+                format!(
+                    "{count} {}{}",
+                    kind.label(),
+                    if *count == 1 { "" } else { "s" }
+                )
+            })
+            .collect();
+        let mut summary = kinds.join(", ");
+        if self.undocumented > 0 {
+            summary.push_str(&format!("; {} without SAFETY comment", self.undocumented));
+        }
+        summary
+    }
+}
+
+/// Module path of a file, e.g. `rustmcp::analyzer::edit`, or its path
+/// relative to `root` for files outside `src/`.
+fn module_name(root: &Path, path: &Path) -> String {
+    match syntax::module_location(path) {
+        Some(location) => std::iter::once(location.crate_name.as_str())
+            .chain(location.segments.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("::"),
+        None => path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string(),
+    }
+}
+
+/// Textual counts for every downloaded non-workspace package, most unsafe
+/// first.
+pub fn dependency_counts(packages: &[LibraryPackage]) -> Vec<(String, UnsafeCounts)> {
+    let mut seen = BTreeSet::new();
+    let mut counts = Vec::new();
+    for package in packages.iter().filter(|package| !package.local) {
+        let Some(src) = package.src_path.parent().filter(|dir| dir.is_dir()) else {
+            continue;
+        };
+        if !seen.insert((&package.name, &package.version)) {
+            continue;
+        }
+        let mut files = Vec::new();
+        crate_index::rust_files(src, src, &mut files);
+        let mut package_counts = UnsafeCounts::default();
+        for file in files {
+            if let Ok(text) = std::fs::read_to_string(&file) {
+                scan(&text).iter().for_each(|site| package_counts.add(site));
+            }
+        }
+        counts.push((
+            format!("{} {}", package.name, package.version),
+            package_counts,
+        ));
+    }
+    counts.sort_by(|a, b| b.1.total().cmp(&a.1.total()).then(a.0.cmp(&b.0)));
+    counts
+}
+
+pub fn format_report(
+    root: &Path,
+    files: &[(PathBuf, Vec<UnsafeSite>)],
+    dependencies: Option<&[(String, UnsafeCounts)]>,
+) -> String {
+    let mut total = UnsafeCounts::default();
+    let mut modules: BTreeMap<String, UnsafeCounts> = BTreeMap::new();
+    for (path, sites) in files {
+        let module = modules.entry(module_name(root, path)).or_default();
+        for site in sites {
+            module.add(site);
+            total.add(site);
+        }
+    }
+
+    let mut result = format!("Unsafe audit of {}\n\n", root.display());
+    if total.total() == 0 {
+        result.push_str("No unsafe code in the workspace\n");
+    } else {
+        result.push_str(&format!("Workspace: {}\n\nBy module:\n", total.summary()));
+        for (module, counts) in &modules {
+            result.push_str(&format!("  {module}: {}\n", counts.summary()));
+        }
+        result.push_str("\nSites:\n");
+        for (path, sites) in files {
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            for site in sites {
+                result.push_str(&format!(
+                    "  {}:{} {}",
+                    relative.display(),
+                    site.line,
+                    site.kind.label()
+                ));
+                if let Some(item) = site.item.as_deref().filter(|item| !item.is_empty()) {
+                    match site.kind {
+                        UnsafeKind::Block | UnsafeKind::ExternBlock => {
+                            result.push_str(&format!(" in {item}"))
+                        }
+                        _ => result.push_str(&format!(" {item}")),
+                    }
+                }
+                if site.kind != UnsafeKind::Attribute && !site.documented {
+                    result.push_str(" [no SAFETY comment]");
+                }
+                if !site.operations.is_empty() {
+                    result.push_str(&format!(
+                        "\n      unsafe operations: {}",
+                        site.operations.join(", ")
+                    ));
+                }
+                result.push('\n');
+            }
+        }
+    }
+
+    if let Some(dependencies) = dependencies {
+        result.push_str("\nDependencies (textual count over each package's src):\n");
+        for (package, counts) in dependencies {
+            if counts.total() == 0 {
+                result.push_str(&format!("  {package}: no unsafe code\n"));
+            } else {
+                result.push_str(&format!("  {package}: {}\n", counts.summary()));
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,406 @@
+// Dead code detection: workspace items from `textDocument/documentSymbol`
+// whose `textDocument/references` all lie inside their own definition.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::edit::{self, FileChange};
+use crate::analyzer::outline;
+use crate::analyzer::syntax;
+
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub path: PathBuf,
+    /// Qualified name, e.g. `Parser::next` for an inherent method.
+    pub name: String,
+    pub kind: &'static str,
+    /// LSP range of the whole item.
+    pub range: Value,
+    /// LSP position of the item's name.
+    pub position: Value,
+    /// Inherent impl blocks of a type in the same file, deleted with it.
+    pub impls: Vec<Value>,
+}
+
+impl Candidate {
+    /// 1-based line of the item's name.
+    pub fn line(&self) -> u64 {
+        self.position["line"].as_u64().unwrap_or(0) + 1
+    }
+}
+
+/// Items of a file worth checking for references: functions, inherent
+/// methods, types, traits, consts and statics. Trait members and trait
+/// impls, tests and benches, `main`, exported symbols (`#[no_mangle]`) and,
+/// unless `include_public`, the public API of a library are skipped.
+pub fn candidates(
+    path: &Path,
+    text: &str,
+    symbols: &[Value],
+    include_public: bool,
+) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    let public = !include_public && public_module(path);
+    collect(path, text, symbols, None, public, true, &mut candidates);
+    candidates
+}
+
+fn collect(
+    path: &Path,
+    text: &str,
+    symbols: &[Value],
+    qualifier: Option<&str>,
+    public_module: bool,
+    top_level: bool,
+    candidates: &mut Vec<Candidate>,
+) {
+    for symbol in symbols {
+        let name = symbol["name"].as_str().unwrap_or_default();
+        let kind = outline::kind_label(symbol["kind"].as_u64().unwrap_or(0));
+        let children = symbol["children"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let range = &symbol["range"];
+        let position = &symbol["selectionRange"]["start"];
+        let item = edit::lsp_range_to_offsets(text, range);
+        let name_offset = edit::position_to_offset(text, position);
+        let prefix = &text[item_start(text, item.start)..name_offset.max(item.start)];
+        let header = text[syntax::line_start(text, name_offset)..name_offset].trim_start();
+        let public = public_module && syntax::has_visibility(header) && !header.starts_with("pub(");
+
+        match kind {
+            "impl" => {
+                let header = name.strip_prefix("impl").unwrap_or(name);
+                if !syntax::contains_word(header, "for") {
+                    let self_type = syntax::impl_self_type(header);
+                    collect(
+                        path,
+                        text,
+                        children,
+                        Some(&self_type),
+                        public_module,
+                        false,
+                        candidates,
+                    );
+                }
+            }
+            "mod" if !prefix.contains("cfg(test)") => {
+                collect(path, text, children, None, public, false, candidates);
+            }
+            "fn" | "struct" | "enum" | "trait" | "const" | "static" | "type" => {
+                let is_test = ["test]", "test(", "#[bench]"]
+                    .iter()
+                    .any(|attribute| prefix.contains(attribute));
+                let exported = prefix.contains("no_mangle") || prefix.contains("export_name");
+                let is_main = top_level && kind == "fn" && name == "main";
+                if is_test || exported || is_main || public {
+                    continue;
+                }
+                let impls = if matches!(kind, "struct" | "enum") {
+                    symbols
+                        .iter()
+                        .filter(|other| {
+                            other["name"].as_str().is_some_and(|impl_name| {
+                                let header = impl_name.strip_prefix("impl").unwrap_or(impl_name);
+                                impl_name.starts_with("impl")
+                                    && !syntax::contains_word(header, "for")
+                                    && syntax::impl_self_type(header) == name
+                            })
+                        })
+                        .map(|other| other["range"].clone())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                candidates.push(Candidate {
+                    path: path.to_path_buf(),
+                    name: match qualifier {
+                        Some(qualifier) => format!("{qualifier}::{name}"),
+                        None => name.to_string(),
+                    },
+                    kind,
+                    range: range.clone(),
+                    position: position.clone(),
+                    impls,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Start of the item at `offset`, moved up over doc comments and
+/// attributes in case the symbol range leaves them out.
+fn item_start(text: &str, offset: usize) -> usize {
+    let mut start = syntax::line_start(text, offset);
+    while start > 0 {
+        let previous = syntax::line_start(text, start - 1);
+        let line = text[previous..start].trim_start();
+        if line.starts_with("///") || line.starts_with("#[") {
+            start = previous;
+        } else {
+            break;
+        }
+    }
+    start.min(offset)
+}
+
+/// Whether items declared `pub` in `path` are reachable from the library
+/// root of its crate through `pub mod` declarations.
+fn public_module(path: &Path) -> bool {
+    let Some(location) = syntax::module_location(path) else {
+        return false;
+    };
+    let src = location.crate_dir.join("src");
+    let mut parent = src.join("lib.rs");
+    if !parent.is_file() {
+        return false;
+    }
+    if location.segments.is_empty() {
+        return path == parent;
+    }
+    for (index, segment) in location.segments.iter().enumerate() {
+        let Ok(text) = std::fs::read_to_string(&parent) else {
+            return false;
+        };
+        let masked = syntax::mask_comments_and_strings(&text);
+        let declared_pub = syntax::find_word(&masked, "mod").into_iter().any(|at| {
+            let name: String = masked[at + 3..]
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            name == *segment && masked[..at].trim_end().ends_with("pub")
+        });
+        if !declared_pub {
+            return false;
+        }
+        let module = src.join(location.segments[..=index].join("/"));
+        parent = if module.with_extension("rs").is_file() {
+            module.with_extension("rs")
+        } else {
+            module.join("mod.rs")
+        };
+    }
+    true
+}
+
+/// Whether a reference to `candidate` counts as a use: it lies outside the
+/// item itself and isn't the self type of one of its impl blocks.
+pub fn is_use(candidate: &Candidate, reference: &Value) -> bool {
+    let path = edit::uri_to_path(reference["uri"].as_str().unwrap_or_default());
+    let start = &reference["range"]["start"];
+    if path == candidate.path && contains(&candidate.range, start) {
+        return false;
+    }
+    if !matches!(candidate.kind, "struct" | "enum") {
+        return true;
+    }
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return true;
+    };
+    let offset = edit::position_to_offset(&text, start);
+    let line_start = syntax::line_start(&text, offset);
+    let line = &text[line_start..syntax::line_end(&text, offset)];
+    let Some(impl_at) = syntax::find_word(line, "impl").first().copied() else {
+        return true;
+    };
+    if !line[..impl_at].trim().is_empty() && line[..impl_at].trim() != "unsafe" {
+        return true;
+    }
+    let header_end = line.find('{').unwrap_or(line.len());
+    let self_start = syntax::find_word(&line[..header_end], "for")
+        .last()
+        .map(|at| at + 3)
+        .unwrap_or(impl_at + 4);
+    !(self_start..header_end).contains(&(offset - line_start))
+}
+
+fn contains(range: &Value, position: &Value) -> bool {
+    let key = |p: &Value| {
+        (
+            p["line"].as_u64().unwrap_or(0),
+            p["character"].as_u64().unwrap_or(0),
+        )
+    };
+    let position = key(position);
+    key(&range["start"]) <= position && position <= key(&range["end"])
+}
+
+/// Group heading of a candidate: its module path or its file.
+fn group(root: &Path, candidate: &Candidate, by_file: bool) -> String {
+    let relative = || {
+        candidate
+            .path
+            .strip_prefix(root)
+            .unwrap_or(&candidate.path)
+            .display()
+            .to_string()
+    };
+    if by_file {
+        return relative();
+    }
+    match syntax::module_location(&candidate.path) {
+        Some(location) => std::iter::once(location.crate_name.as_str())
+            .chain(location.segments.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("::"),
+        None => relative(),
+    }
+}
+
+/// Unused items grouped by module (or file), followed by the candidates
+/// whose references could not be looked up.
+pub fn format_unused(
+    root: &Path,
+    unused: &[Candidate],
+    unchecked: &[Candidate],
+    checked: usize,
+    by_file: bool,
+) -> String {
+    let location = |candidate: &Candidate| {
+        let path = candidate.path.strip_prefix(root).unwrap_or(&candidate.path);
+        format!("{}:{}", path.display(), candidate.line())
+    };
+    let mut result = if unused.is_empty() {
+        format!("No unused items among {checked} checked\n")
+    } else {
+        let mut groups: BTreeMap<String, Vec<&Candidate>> = BTreeMap::new();
+        for candidate in unused {
+            groups
+                .entry(group(root, candidate, by_file))
+                .or_default()
+                .push(candidate);
+        }
+        let mut result = format!("{} unused items ({checked} checked):\n", unused.len());
+        for (heading, candidates) in groups {
+            result.push_str(&format!("\n{heading}\n"));
+            for candidate in candidates {
+                let place = if by_file {
+                    format!("line {}", candidate.line())
+                } else {
+                    location(candidate)
+                };
+                result.push_str(&format!(
+                    "  {} {} ({place})\n",
+                    candidate.kind, candidate.name
+                ));
+            }
+        }
+        result
+    };
+    if !unchecked.is_empty() {
+        result.push_str(&format!(
+            "\n{} items could not be checked (references lookup failed):\n",
+            unchecked.len()
+        ));
+        for candidate in unchecked {
+            result.push_str(&format!(
+                "  {} {} ({})\n",
+                candidate.kind,
+                candidate.name,
+                location(candidate)
+            ));
+        }
+    }
+    result.trim_end().to_string()
+}
+
+/// File changes deleting the unused items (with their docs, attributes and,
+/// for types, inherent impls in the same file).
+pub fn deletions(unused: &[Candidate]) -> Result<Vec<FileChange>> {
+    let mut by_file: BTreeMap<&Path, Vec<&Value>> = BTreeMap::new();
+    for candidate in unused {
+        let ranges = by_file.entry(&candidate.path).or_default();
+        ranges.push(&candidate.range);
+        ranges.extend(&candidate.impls);
+    }
+
+    let mut changes = Vec::new();
+    for (path, ranges) in by_file {
+        let text = std::fs::read_to_string(path)?;
+        let mut spans: Vec<Range<usize>> = ranges
+            .into_iter()
+            .map(|range| {
+                let item = edit::lsp_range_to_offsets(&text, range);
+                let start = item_start(&text, item.start);
+                let mut end = syntax::line_end(&text, item.end);
+                // Drop one of the blank lines the item leaves behind.
+                let blank_before = start == 0 || text[..start].ends_with("\n\n");
+                if blank_before && text[end..].starts_with('\n') {
+                    end += 1;
+                }
+                start..end
+            })
+            .collect();
+        spans.sort_by_key(|span| (span.start, std::cmp::Reverse(span.end)));
+        let mut new_text = String::with_capacity(text.len());
+        let mut copied = 0;
+        for span in spans {
+            if span.start < copied {
+                continue;
+            }
+            new_text.push_str(&text[copied..span.start]);
+            copied = span.end;
+        }
+        new_text.push_str(&text[copied..]);
+        changes.push(FileChange {
+            path: path.to_path_buf(),
+            old_text: Some(text),
+            new_text: Some(new_text),
+        });
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn candidate(file: &str, name: &str, line: u64) -> Candidate {
+        Candidate {
+            path: PathBuf::from("/ws/src").join(file),
+            name: name.to_string(),
+            kind: "fn",
+            range: Value::Null,
+            position: json!({ "line": line - 1, "character": 3 }),
+            impls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn format_unused_lists_items_that_could_not_be_checked() {
+        let root = Path::new("/ws");
+        let unused = [candidate("a.rs", "dead", 4), candidate("b.rs", "gone", 9)];
+        let unchecked = [candidate("a.rs", "unknown", 12)];
+        assert_eq!(
+            format_unused(root, &unused, &unchecked, 5, true),
+            "2 unused items (5 checked):
+
+src/a.rs
+  fn dead (line 4)
+
+src/b.rs
+  fn gone (line 9)
+
+1 items could not be checked (references lookup failed):
+  fn unknown (src/a.rs:12)"
+        );
+        assert_eq!(
+            format_unused(root, &[], &unchecked, 0, true),
+            "No unused items among 0 checked
+
+1 items could not be checked (references lookup failed):
+  fn unknown (src/a.rs:12)"
+        );
+        assert_eq!(
+            format_unused(root, &[], &[], 3, false),
+            "No unused items among 3 checked"
+        );
+    }
+}
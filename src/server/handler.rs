@@ -66,12 +66,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("find_definition", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "No definition found",
@@ -101,12 +101,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("find_references", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "No references found",
@@ -118,27 +118,863 @@ impl RustMcpServer {
         }
     }
 
+    #[tool(
+        description = "Find impls of a trait or implementations of a trait method at a position, with source snippets"
+    )]
+    async fn find_implementations(
+        &self,
+        Parameters(FindImplementationsParams {
+            file_path,
+            line,
+            character,
+        }): Parameters<FindImplementationsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("find_implementations", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No implementations found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Find the definition of the type of the expression or binding at a position, with a source snippet"
+    )]
+    async fn find_type_definition(
+        &self,
+        Parameters(FindTypeDefinitionParams {
+            file_path,
+            line,
+            character,
+        }): Parameters<FindTypeDefinitionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("find_type_definition", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No type definition found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(description = "Find the declaration of the symbol at a position, with a source snippet")]
+    async fn find_declaration(
+        &self,
+        Parameters(FindDeclarationParams {
+            file_path,
+            line,
+            character,
+        }): Parameters<FindDeclarationParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("find_declaration", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No declaration found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
     #[tool(description = "Get compiler diagnostics for a file")]
     async fn get_diagnostics(
         &self,
-        Parameters(GetDiagnosticsParams { file_path }): Parameters<GetDiagnosticsParams>,
+        Parameters(GetDiagnosticsParams { file_path }): Parameters<GetDiagnosticsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("get_diagnostics", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No diagnostics found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Deduplicated diagnostics for the whole workspace from rust-analyzer and optionally cargo check, counted by severity and file, with what was introduced or fixed since the previous call"
+    )]
+    async fn workspace_diagnostics(
+        &self,
+        Parameters(WorkspaceDiagnosticsParams {
+            workspace_path,
+            check,
+            min_severity,
+        }): Parameters<WorkspaceDiagnosticsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "workspace_path": workspace_path,
+            "check": check,
+            "min_severity": min_severity
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("workspace_diagnostics", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No diagnostics found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Recursively expand the macro call at a position, optionally falling back to whole-module rustc expansion on nightly"
+    )]
+    async fn expand_macro(
+        &self,
+        Parameters(ExpandMacroParams {
+            file_path,
+            line,
+            character,
+            fallback,
+        }): Parameters<ExpandMacroParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character,
+            "fallback": fallback
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("expand_macro", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text("No expansion")]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List completions at a position with kinds, signatures and docs, optionally after inserting a temporary . or :: to discover a type's API"
+    )]
+    async fn complete_at(
+        &self,
+        Parameters(CompleteAtParams {
+            file_path,
+            line,
+            character,
+            trigger,
+            query,
+            limit,
+        }): Parameters<CompleteAtParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character,
+            "trigger": trigger,
+            "query": query,
+            "limit": limit
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("complete_at", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No completions",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Show the signatures of the call at a position with parameter names, types and docs, highlighting the active parameter"
+    )]
+    async fn signature_help(
+        &self,
+        Parameters(SignatureHelpParams {
+            file_path,
+            line,
+            character,
+        }): Parameters<SignatureHelpParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("signature_help", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No signature help at this position",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Show a file or line range annotated with inferred types, parameter names, chaining and lifetime elision hints"
+    )]
+    async fn inlay_hints(
+        &self,
+        Parameters(InlayHintsParams {
+            file_path,
+            start_line,
+            end_line,
+        }): Parameters<InlayHintsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "start_line": start_line,
+            "end_line": end_line
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("inlay_hints", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No inlay hints",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List semantic tokens of a file or line range (type, modifiers such as mutable, unsafe or trait, and text), optionally filtered by token type and modifier"
+    )]
+    async fn semantic_tokens(
+        &self,
+        Parameters(SemanticTokensParams {
+            file_path,
+            start_line,
+            end_line,
+            token_types,
+            modifiers,
+        }): Parameters<SemanticTokensParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "start_line": start_line,
+            "end_line": end_line,
+            "token_types": token_types,
+            "modifiers": modifiers
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("semantic_tokens", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No semantic tokens",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(description = "Search for symbols in the workspace")]
+    async fn workspace_symbols(
+        &self,
+        Parameters(WorkspaceSymbolsParams { query }): Parameters<WorkspaceSymbolsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "query": query
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("workspace_symbols", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No symbols found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Outline a file's modules, types, impls, functions, fields and consts with signatures and line ranges"
+    )]
+    async fn document_outline(
+        &self,
+        Parameters(DocumentOutlineParams {
+            file_path,
+            max_depth,
+            format,
+        }): Parameters<DocumentOutlineParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "max_depth": max_depth,
+            "format": format
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("document_outline", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No symbols found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Read source of dependencies and std by item path (e.g. tokio::sync::Mutex::lock) or by file and line range in the registry, git checkouts or rust-src"
+    )]
+    async fn read_source(
+        &self,
+        Parameters(ReadSourceParams {
+            path,
+            file_path,
+            start_line,
+            end_line,
+            context_lines,
+        }): Parameters<ReadSourceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "path": path,
+            "file_path": file_path,
+            "start_line": start_line,
+            "end_line": end_line,
+            "context_lines": context_lines
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("read_source", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No source found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Look up API docs for an item path (signature, docs, examples, methods, trait impls) from offline rustdoc JSON of the exact dependency versions"
+    )]
+    async fn lookup_docs(
+        &self,
+        Parameters(LookupDocsParams {
+            path,
+            workspace_path,
+        }): Parameters<LookupDocsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "path": path,
+            "workspace_path": workspace_path
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("lookup_docs", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No documentation found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List tests, binaries, benches and doctests rust-analyzer can run in a file or at a position, with their cargo commands"
+    )]
+    async fn list_runnables(
+        &self,
+        Parameters(ListRunnablesParams {
+            file_path,
+            line,
+            character,
+        }): Parameters<ListRunnablesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("list_runnables", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Runnables listed",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(description = "Execute a runnable by id from the last list_runnables result")]
+    async fn run_runnable(
+        &self,
+        Parameters(RunRunnableParams {
+            id,
+            timeout_secs,
+            max_output_bytes,
+        }): Parameters<RunRunnableParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "id": id,
+            "timeout_secs": timeout_secs,
+            "max_output_bytes": max_output_bytes
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("run_runnable", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Runnable finished",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(description = "Rename a symbol with scope awareness")]
+    async fn rename_symbol(
+        &self,
+        Parameters(RenameSymbolParams {
+            file_path,
+            line,
+            character,
+            new_name,
+        }): Parameters<RenameSymbolParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character,
+            "new_name": new_name
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("rename_symbol", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Rename operation completed",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(description = "Apply rustfmt formatting to a file")]
+    async fn format_code(
+        &self,
+        Parameters(FormatCodeParams { file_path }): Parameters<FormatCodeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("format_code", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Format operation completed",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Summarize Cargo.toml: package, workspace members, dependency tables and selectable features"
+    )]
+    async fn analyze_manifest(
+        &self,
+        Parameters(AnalyzeManifestParams { manifest_path }): Parameters<AnalyzeManifestParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "manifest_path": manifest_path
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("analyze_manifest", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Analysis completed",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Add a dependency to Cargo.toml or update an existing one, preserving formatting and validating with cargo metadata"
+    )]
+    async fn add_dependency(
+        &self,
+        Parameters(AddDependencyParams {
+            manifest_path,
+            name,
+            version,
+            features,
+            optional,
+            default_features,
+            workspace,
+            kind,
+            target,
+        }): Parameters<AddDependencyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "manifest_path": manifest_path,
+            "name": name,
+            "version": version,
+            "features": features,
+            "optional": optional,
+            "default_features": default_features,
+            "workspace": workspace,
+            "kind": kind,
+            "target": target
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("add_dependency", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Dependency added successfully",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Remove a dependency and the features referring to it from Cargo.toml, validating with cargo metadata"
+    )]
+    async fn remove_dependency(
+        &self,
+        Parameters(RemoveDependencyParams {
+            manifest_path,
+            name,
+            kind,
+            target,
+        }): Parameters<RemoveDependencyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "manifest_path": manifest_path,
+            "name": name,
+            "kind": kind,
+            "target": target
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("remove_dependency", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Dependency removed successfully",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Run cargo check on the selected targets and features under a timeout and return structured compiler diagnostics"
+    )]
+    async fn run_cargo_check(
+        &self,
+        Parameters(RunCargoCheckParams {
+            workspace_path,
+            package,
+            bin,
+            example,
+            profile,
+            target,
+            features,
+            no_default_features,
+            timeout_secs,
+        }): Parameters<RunCargoCheckParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "workspace_path": workspace_path,
+            "package": package,
+            "bin": bin,
+            "example": example,
+            "profile": profile,
+            "target": target,
+            "features": features,
+            "no_default_features": no_default_features,
+            "timeout_secs": timeout_secs
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("run_cargo_check", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Cargo check completed",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Run cargo test with optional filters and return per-test status, timing and failure output"
+    )]
+    async fn run_cargo_test(
+        &self,
+        Parameters(RunCargoTestParams {
+            workspace_path,
+            package,
+            test_target,
+            filter,
+            ignored,
+            features,
+            no_default_features,
+            timeout_secs,
+        }): Parameters<RunCargoTestParams>,
     ) -> Result<CallToolResult, McpError> {
         let args = serde_json::json!({
-            "file_path": file_path
+            "workspace_path": workspace_path,
+            "package": package,
+            "test_target": test_target,
+            "filter": filter,
+            "ignored": ignored,
+            "features": features,
+            "no_default_features": no_default_features,
+            "timeout_secs": timeout_secs
         });
 
         let mut analyzer = self.analyzer.lock().await;
-        match execute_tool("get_diagnostics", args, &mut analyzer).await {
+        match execute_tool("run_cargo_test", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
-                    "No diagnostics found",
+                    "Tests completed",
                 )]))
             }
             Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
@@ -147,27 +983,45 @@ impl RustMcpServer {
         }
     }
 
-    #[tool(description = "Search for symbols in the workspace")]
-    async fn workspace_symbols(
+    #[tool(
+        description = "Run cargo build and return produced artifacts and structured compiler diagnostics"
+    )]
+    async fn cargo_build(
         &self,
-        Parameters(WorkspaceSymbolsParams { query }): Parameters<WorkspaceSymbolsParams>,
+        Parameters(CargoBuildParams {
+            workspace_path,
+            package,
+            bin,
+            example,
+            profile,
+            target,
+            features,
+            no_default_features,
+        }): Parameters<CargoBuildParams>,
     ) -> Result<CallToolResult, McpError> {
         let args = serde_json::json!({
-            "query": query
+            "workspace_path": workspace_path,
+            "package": package,
+            "bin": bin,
+            "example": example,
+            "profile": profile,
+            "target": target,
+            "features": features,
+            "no_default_features": no_default_features
         });
 
         let mut analyzer = self.analyzer.lock().await;
-        match execute_tool("workspace_symbols", args, &mut analyzer).await {
+        match execute_tool("cargo_build", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
-                    "No symbols found",
+                    "Build completed",
                 )]))
             }
             Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
@@ -176,35 +1030,51 @@ impl RustMcpServer {
         }
     }
 
-    #[tool(description = "Rename a symbol with scope awareness")]
-    async fn rename_symbol(
+    #[tool(
+        description = "Build and run a binary or example, capturing stdout/stderr with a timeout and output size limit"
+    )]
+    async fn cargo_run(
         &self,
-        Parameters(RenameSymbolParams {
-            file_path,
-            line,
-            character,
-            new_name,
-        }): Parameters<RenameSymbolParams>,
+        Parameters(CargoRunParams {
+            workspace_path,
+            package,
+            bin,
+            example,
+            profile,
+            target,
+            features,
+            no_default_features,
+            args,
+            timeout_secs,
+            max_output_bytes,
+        }): Parameters<CargoRunParams>,
     ) -> Result<CallToolResult, McpError> {
         let args = serde_json::json!({
-            "file_path": file_path,
-            "line": line,
-            "character": character,
-            "new_name": new_name
+            "workspace_path": workspace_path,
+            "package": package,
+            "bin": bin,
+            "example": example,
+            "profile": profile,
+            "target": target,
+            "features": features,
+            "no_default_features": no_default_features,
+            "args": args,
+            "timeout_secs": timeout_secs,
+            "max_output_bytes": max_output_bytes
         });
 
         let mut analyzer = self.analyzer.lock().await;
-        match execute_tool("rename_symbol", args, &mut analyzer).await {
+        match execute_tool("cargo_run", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
-                    "Rename operation completed",
+                    "Run completed",
                 )]))
             }
             Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
@@ -213,27 +1083,49 @@ impl RustMcpServer {
         }
     }
 
-    #[tool(description = "Apply rustfmt formatting to a file")]
-    async fn format_code(
+    #[tool(
+        description = "Run cargo bench (libtest or criterion), compare timings with a saved baseline and optionally save a new one"
+    )]
+    async fn run_cargo_bench(
         &self,
-        Parameters(FormatCodeParams { file_path }): Parameters<FormatCodeParams>,
+        Parameters(RunCargoBenchParams {
+            workspace_path,
+            package,
+            bench,
+            filter,
+            features,
+            no_default_features,
+            baseline,
+            save_baseline,
+            threshold_percent,
+            timeout_secs,
+        }): Parameters<RunCargoBenchParams>,
     ) -> Result<CallToolResult, McpError> {
         let args = serde_json::json!({
-            "file_path": file_path
+            "workspace_path": workspace_path,
+            "package": package,
+            "bench": bench,
+            "filter": filter,
+            "features": features,
+            "no_default_features": no_default_features,
+            "baseline": baseline,
+            "save_baseline": save_baseline,
+            "threshold_percent": threshold_percent,
+            "timeout_secs": timeout_secs
         });
 
         let mut analyzer = self.analyzer.lock().await;
-        match execute_tool("format_code", args, &mut analyzer).await {
+        match execute_tool("run_cargo_bench", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
-                    "Format operation completed",
+                    "Benchmarks completed",
                 )]))
             }
             Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
@@ -242,27 +1134,27 @@ impl RustMcpServer {
         }
     }
 
-    #[tool(description = "Parse and analyze Cargo.toml file")]
-    async fn analyze_manifest(
+    #[tool(description = "List Cargo features with everything each one transitively enables")]
+    async fn list_features(
         &self,
-        Parameters(AnalyzeManifestParams { manifest_path }): Parameters<AnalyzeManifestParams>,
+        Parameters(ListFeaturesParams { manifest_path }): Parameters<ListFeaturesParams>,
     ) -> Result<CallToolResult, McpError> {
         let args = serde_json::json!({
             "manifest_path": manifest_path
         });
 
         let mut analyzer = self.analyzer.lock().await;
-        match execute_tool("analyze_manifest", args, &mut analyzer).await {
+        match execute_tool("list_features", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
-                    "Analysis completed",
+                    "Features listed successfully",
                 )]))
             }
             Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
@@ -271,27 +1163,140 @@ impl RustMcpServer {
         }
     }
 
-    #[tool(description = "Execute cargo check and parse errors")]
-    async fn run_cargo_check(
+    #[tool(description = "Add or replace a feature definition in Cargo.toml")]
+    async fn add_feature(
         &self,
-        Parameters(RunCargoCheckParams { workspace_path }): Parameters<RunCargoCheckParams>,
+        Parameters(AddFeatureParams {
+            manifest_path,
+            name,
+            activations,
+        }): Parameters<AddFeatureParams>,
     ) -> Result<CallToolResult, McpError> {
         let args = serde_json::json!({
-            "workspace_path": workspace_path
+            "manifest_path": manifest_path,
+            "name": name,
+            "activations": activations
         });
 
         let mut analyzer = self.analyzer.lock().await;
-        match execute_tool("run_cargo_check", args, &mut analyzer).await {
+        match execute_tool("add_feature", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
-                    "Cargo check completed",
+                    "Feature added successfully",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(description = "Remove a feature definition and its activations from Cargo.toml")]
+    async fn remove_feature(
+        &self,
+        Parameters(RemoveFeatureParams {
+            manifest_path,
+            name,
+        }): Parameters<RemoveFeatureParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "manifest_path": manifest_path,
+            "name": name
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("remove_feature", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Feature removed successfully",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Make a dependency optional behind the given features, or regular again when no features are given"
+    )]
+    async fn gate_dependency(
+        &self,
+        Parameters(GateDependencyParams {
+            manifest_path,
+            dependency,
+            features,
+        }): Parameters<GateDependencyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "manifest_path": manifest_path,
+            "dependency": dependency,
+            "features": features
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("gate_dependency", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Dependency gating updated successfully",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Run cargo check --no-default-features for every feature combination, optionally limited to a depth, with a timeout per check"
+    )]
+    async fn check_feature_combinations(
+        &self,
+        Parameters(CheckFeatureCombinationsParams {
+            manifest_path,
+            depth,
+            timeout_secs,
+        }): Parameters<CheckFeatureCombinationsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "manifest_path": manifest_path,
+            "depth": depth,
+            "timeout_secs": timeout_secs
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("check_feature_combinations", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Feature combinations checked successfully",
                 )]))
             }
             Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
@@ -324,12 +1329,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("extract_function", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Function extracted successfully",
@@ -361,12 +1366,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("generate_struct", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Struct generated successfully",
@@ -398,12 +1403,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("generate_enum", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Enum generated successfully",
@@ -415,6 +1420,53 @@ impl RustMcpServer {
         }
     }
 
+    #[tool(
+        description = "Add the missing arms of the match at a position, or of every non-exhaustive match over an enum in a file or the workspace, with todo!() or a given body, optionally expanding nested enum fields"
+    )]
+    async fn fill_match_arms(
+        &self,
+        Parameters(FillMatchArmsParams {
+            file_path,
+            line,
+            character,
+            enum_name,
+            workspace_path,
+            expand_nested,
+            arm_body,
+            dry_run,
+        }): Parameters<FillMatchArmsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character,
+            "enum_name": enum_name,
+            "workspace_path": workspace_path,
+            "expand_nested": expand_nested,
+            "arm_body": arm_body,
+            "dry_run": dry_run
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("fill_match_arms", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No match arms added",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
     #[tool(description = "Generate a trait implementation for a struct")]
     async fn generate_trait_impl(
         &self,
@@ -433,12 +1485,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("generate_trait_impl", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Trait implementation generated successfully",
@@ -468,12 +1520,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("generate_tests", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Tests generated successfully",
@@ -503,12 +1555,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("inline_function", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Function inlined successfully",
@@ -540,12 +1592,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("change_signature", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Signature changed successfully",
@@ -569,12 +1621,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("organize_imports", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Imports organized successfully",
@@ -586,6 +1638,155 @@ impl RustMcpServer {
         }
     }
 
+    #[tool(
+        description = "Import every unresolved name in a file using rust-analyzer's auto_import candidates, choosing between crates by a preference list and reporting ambiguous names"
+    )]
+    async fn add_missing_imports(
+        &self,
+        Parameters(AddMissingImportsParams {
+            file_path,
+            prefer,
+            dry_run,
+        }): Parameters<AddMissingImportsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "prefer": prefer,
+            "dry_run": dry_run
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("add_missing_imports", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No imports added",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Structural search and replace with rules like `foo($a, $b) ==>> bar($b, $a)` across files, directories or the workspace, with dry-run diffs"
+    )]
+    async fn structural_replace(
+        &self,
+        Parameters(StructuralReplaceParams {
+            rule,
+            paths,
+            dry_run,
+        }): Parameters<StructuralReplaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "rule": rule,
+            "paths": paths,
+            "dry_run": dry_run
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("structural_replace", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Structural replace completed",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List rust-analyzer assists and quick fixes for a position or range, with kinds, titles and ids"
+    )]
+    async fn list_code_actions(
+        &self,
+        Parameters(ListCodeActionsParams {
+            file_path,
+            line,
+            character,
+            end_line,
+            end_character,
+            kind,
+        }): Parameters<ListCodeActionsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "line": line,
+            "character": character,
+            "end_line": end_line,
+            "end_character": end_character,
+            "kind": kind
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("list_code_actions", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No code actions available",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Resolve and apply a code action by id from the last list_code_actions result"
+    )]
+    async fn apply_code_action(
+        &self,
+        Parameters(ApplyCodeActionParams { id, dry_run }): Parameters<ApplyCodeActionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "id": id,
+            "dry_run": dry_run
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("apply_code_action", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Code action applied",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
     #[tool(description = "Apply clippy lint suggestions to improve code quality")]
     async fn apply_clippy_suggestions(
         &self,
@@ -600,12 +1801,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("apply_clippy_suggestions", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Clippy suggestions applied successfully",
@@ -629,12 +1830,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("validate_lifetimes", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Lifetimes validated successfully",
@@ -646,6 +1847,119 @@ impl RustMcpServer {
         }
     }
 
+    #[tool(
+        description = "Apply the quick fix of every diagnostic that has exactly one (or a preferred one) in a file or the workspace, repeating until a fixpoint, and report the diagnostics that remain"
+    )]
+    async fn fix_all_diagnostics(
+        &self,
+        Parameters(FixAllDiagnosticsParams {
+            file_path,
+            workspace_path,
+            max_rounds,
+            dry_run,
+        }): Parameters<FixAllDiagnosticsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "file_path": file_path,
+            "workspace_path": workspace_path,
+            "max_rounds": max_rounds,
+            "dry_run": dry_run
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("fix_all_diagnostics", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No quick fixes applied",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Inventory unsafe blocks, unsafe fns, impls, traits and extern blocks with their enclosing function, SAFETY comments, unsafe operations and counts per module, optionally with per-dependency counts"
+    )]
+    async fn audit_unsafe(
+        &self,
+        Parameters(AuditUnsafeParams {
+            workspace_path,
+            include_dependencies,
+        }): Parameters<AuditUnsafeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "workspace_path": workspace_path,
+            "include_dependencies": include_dependencies
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("audit_unsafe", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No unsafe code found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Find functions, methods, types, traits, consts and statics with no references outside their definition, grouped by module or file, optionally deleting them"
+    )]
+    async fn find_unused(
+        &self,
+        Parameters(FindUnusedParams {
+            workspace_path,
+            include_public,
+            group_by,
+            delete,
+        }): Parameters<FindUnusedParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = serde_json::json!({
+            "workspace_path": workspace_path,
+            "include_public": include_public,
+            "group_by": group_by,
+            "delete": delete
+        });
+
+        let mut analyzer = self.analyzer.lock().await;
+        match execute_tool("find_unused", args, &mut analyzer).await {
+            Ok(result) => {
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
+                }
+                Ok(CallToolResult::success(vec![Content::text(
+                    "No unused items found",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Error: {e}"
+            ))])),
+        }
+    }
+
     #[tool(description = "Get type hierarchy for a symbol at specified position")]
     async fn get_type_hierarchy(
         &self,
@@ -664,12 +1978,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("get_type_hierarchy", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Type hierarchy retrieved successfully",
@@ -681,28 +1995,32 @@ impl RustMcpServer {
         }
     }
 
-    #[tool(description = "Suggest crate dependencies based on code patterns")]
+    #[tool(
+        description = "Suggest crates for unresolved imports and a free-text query from the local crate index, optionally adding one to Cargo.toml"
+    )]
     async fn suggest_dependencies(
         &self,
         Parameters(SuggestDependenciesParams {
             query,
             workspace_path,
+            add_crate,
         }): Parameters<SuggestDependenciesParams>,
     ) -> Result<CallToolResult, McpError> {
         let args = serde_json::json!({
             "query": query,
-            "workspace_path": workspace_path
+            "workspace_path": workspace_path,
+            "add_crate": add_crate
         });
 
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("suggest_dependencies", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Dependencies suggested successfully",
@@ -732,12 +2050,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("create_module", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Module created successfully",
@@ -749,7 +2067,9 @@ impl RustMcpServer {
         }
     }
 
-    #[tool(description = "Move code items from one file to another")]
+    #[tool(
+        description = "Move items (with their impls) to another module file and update all paths that reference them"
+    )]
     async fn move_items(
         &self,
         Parameters(MoveItemsParams {
@@ -767,12 +2087,12 @@ impl RustMcpServer {
         let mut analyzer = self.analyzer.lock().await;
         match execute_tool("move_items", args, &mut analyzer).await {
             Ok(result) => {
-                if let Some(content) = result.content.first() {
-                    if let Some(text) = content.get("text") {
-                        return Ok(CallToolResult::success(vec![Content::text(
-                            text.as_str().unwrap_or("No result"),
-                        )]));
-                    }
+                if let Some(content) = result.content.first()
+                    && let Some(text) = content.get("text")
+                {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        text.as_str().unwrap_or("No result"),
+                    )]));
                 }
                 Ok(CallToolResult::success(vec![Content::text(
                     "Items moved successfully",
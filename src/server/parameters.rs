@@ -15,16 +15,147 @@ pub struct FindReferencesParams {
     pub character: u32,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct FindImplementationsParams {
+    pub file_path: String,
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct FindTypeDefinitionParams {
+    pub file_path: String,
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct FindDeclarationParams {
+    pub file_path: String,
+    pub line: u32,
+    pub character: u32,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct GetDiagnosticsParams {
     pub file_path: String,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct WorkspaceDiagnosticsParams {
+    /// Workspace or package directory (default: the server's working directory).
+    pub workspace_path: Option<String>,
+    /// Also run `cargo check --workspace --all-targets` and merge its messages.
+    pub check: Option<bool>,
+    /// Least severe level to include: `error`, `warning`, `information` or `hint` (default).
+    pub min_severity: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExpandMacroParams {
+    pub file_path: String,
+    pub line: u32,
+    pub character: u32,
+    /// Expand the whole module with `rustc -Zunpretty=expanded` (needs nightly) when rust-analyzer finds no macro call or isn't running.
+    pub fallback: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CompleteAtParams {
+    pub file_path: String,
+    pub line: u32,
+    pub character: u32,
+    /// `.` or `::` to insert at the position (in memory only) before completing.
+    pub trigger: Option<String>,
+    /// Filter candidates by name (default: the identifier typed before the position).
+    pub query: Option<String>,
+    /// Maximum candidates returned (default 50).
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SignatureHelpParams {
+    pub file_path: String,
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct InlayHintsParams {
+    pub file_path: String,
+    /// First line to show, 0-based (default: start of file).
+    pub start_line: Option<u32>,
+    /// Last line to show, inclusive (default: end of file).
+    pub end_line: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SemanticTokensParams {
+    pub file_path: String,
+    /// First line, 0-based; with `end_line`, only the range is requested (default: whole file).
+    pub start_line: Option<u32>,
+    /// Last line, inclusive (default: end of file).
+    pub end_line: Option<u32>,
+    /// Only tokens of these types, e.g. `macro`, `method`, `function` (default: all).
+    pub token_types: Option<Vec<String>>,
+    /// Only tokens carrying all of these modifiers, e.g. `unsafe`, `mutable`, `trait`.
+    pub modifiers: Option<Vec<String>>,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct WorkspaceSymbolsParams {
     pub query: String,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct DocumentOutlineParams {
+    pub file_path: String,
+    /// Nesting levels to expand; deeper members are only counted (default: all).
+    pub max_depth: Option<u32>,
+    /// `text` (default) for an indented outline, `json` for the symbol tree.
+    pub format: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ReadSourceParams {
+    /// Item path such as `tokio::sync::Mutex::lock`, `std::vec::Vec` or `crate::module::item`.
+    pub path: Option<String>,
+    /// File, absolute or relative to the registry, git checkouts or rust-src library (e.g. `tokio-1.38.0/src/sync/mutex.rs`).
+    pub file_path: Option<String>,
+    /// First line of the range to read from file_path, 0-based.
+    pub start_line: Option<u32>,
+    /// Last line of the range, inclusive (default: start_line).
+    pub end_line: Option<u32>,
+    /// Lines shown around the range (default 5).
+    pub context_lines: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct LookupDocsParams {
+    /// Item path such as `serde_json::Value::as_str` or `crate::module::Type`.
+    pub path: String,
+    /// Workspace whose dependency versions are used (default: current directory).
+    pub workspace_path: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListRunnablesParams {
+    pub file_path: String,
+    /// Only list runnables covering this position (requires character).
+    pub line: Option<u32>,
+    pub character: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RunRunnableParams {
+    /// Id shown by list_runnables.
+    pub id: u32,
+    /// Kill cargo and the program it runs after this many seconds (default 60).
+    pub timeout_secs: Option<u64>,
+    /// Bytes kept per output stream (default 65536).
+    pub max_output_bytes: Option<u64>,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct RenameSymbolParams {
     pub file_path: String,
@@ -43,9 +174,147 @@ pub struct AnalyzeManifestParams {
     pub manifest_path: String,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct AddDependencyParams {
+    pub manifest_path: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub features: Option<Vec<String>>,
+    pub optional: Option<bool>,
+    pub default_features: Option<bool>,
+    pub workspace: Option<bool>,
+    /// One of "normal" (default), "dev" or "build".
+    pub kind: Option<String>,
+    /// Target triple or `cfg(...)` expression for a target-specific table.
+    pub target: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RemoveDependencyParams {
+    pub manifest_path: String,
+    pub name: String,
+    pub kind: Option<String>,
+    pub target: Option<String>,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct RunCargoCheckParams {
     pub workspace_path: String,
+    pub package: Option<String>,
+    pub bin: Option<String>,
+    pub example: Option<String>,
+    /// Cargo profile such as `release`.
+    pub profile: Option<String>,
+    /// Target triple to check for.
+    pub target: Option<String>,
+    pub features: Option<Vec<String>>,
+    pub no_default_features: Option<bool>,
+    /// Kill cargo after this many seconds (default 300).
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RunCargoTestParams {
+    pub workspace_path: String,
+    pub package: Option<String>,
+    /// Integration test target to run (`--test <name>`).
+    pub test_target: Option<String>,
+    /// Only run tests whose name contains this string.
+    pub filter: Option<String>,
+    /// Run only ignored tests.
+    pub ignored: Option<bool>,
+    pub features: Option<Vec<String>>,
+    pub no_default_features: Option<bool>,
+    /// Kill cargo and the test binary after this many seconds (default 600).
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoBuildParams {
+    pub workspace_path: String,
+    pub package: Option<String>,
+    pub bin: Option<String>,
+    pub example: Option<String>,
+    /// Cargo profile such as `release`.
+    pub profile: Option<String>,
+    /// Target triple to build for.
+    pub target: Option<String>,
+    pub features: Option<Vec<String>>,
+    pub no_default_features: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoRunParams {
+    pub workspace_path: String,
+    pub package: Option<String>,
+    pub bin: Option<String>,
+    pub example: Option<String>,
+    /// Cargo profile such as `release`.
+    pub profile: Option<String>,
+    /// Target triple to build for.
+    pub target: Option<String>,
+    pub features: Option<Vec<String>>,
+    pub no_default_features: Option<bool>,
+    /// Arguments passed to the program.
+    pub args: Option<Vec<String>>,
+    /// Kill the program after this many seconds (default 60).
+    pub timeout_secs: Option<u64>,
+    /// Bytes kept per output stream (default 65536).
+    pub max_output_bytes: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RunCargoBenchParams {
+    pub workspace_path: String,
+    pub package: Option<String>,
+    /// Bench target to run (`--bench <name>`).
+    pub bench: Option<String>,
+    /// Only run benchmarks whose name contains this string.
+    pub filter: Option<String>,
+    pub features: Option<Vec<String>>,
+    pub no_default_features: Option<bool>,
+    /// Name of a saved baseline to compare against.
+    pub baseline: Option<String>,
+    /// Save these results under this baseline name.
+    pub save_baseline: Option<String>,
+    /// Changes smaller than this are reported as unchanged (default 2).
+    pub threshold_percent: Option<f64>,
+    /// Kill cargo and the bench binary after this many seconds (default 1800).
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListFeaturesParams {
+    pub manifest_path: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct AddFeatureParams {
+    pub manifest_path: String,
+    pub name: String,
+    pub activations: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RemoveFeatureParams {
+    pub manifest_path: String,
+    pub name: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GateDependencyParams {
+    pub manifest_path: String,
+    pub dependency: String,
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CheckFeatureCombinationsParams {
+    pub manifest_path: String,
+    /// Largest number of features enabled together; all combinations when omitted.
+    pub depth: Option<u32>,
+    /// Kill each `cargo check` after this many seconds (default 300).
+    pub timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -74,6 +343,26 @@ pub struct GenerateEnumParams {
     pub file_path: String,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct FillMatchArmsParams {
+    /// File with the match; without a position, limits the workspace mode to this file.
+    pub file_path: Option<String>,
+    /// Position inside the `match` expression (0-based). Without one, every non-exhaustive match is filled.
+    pub line: Option<u32>,
+    /// Column of the position (0-based, default: 0).
+    pub character: Option<u32>,
+    /// Without a position, only fill matches whose missing patterns mention this enum.
+    pub enum_name: Option<String>,
+    /// Workspace or package directory for the workspace mode (default: the server's working directory).
+    pub workspace_path: Option<String>,
+    /// Expand `_` fields of tuple-variant arms into the variants of their type, one level deep.
+    pub expand_nested: Option<bool>,
+    /// Expression for every added arm (default: `todo!()`).
+    pub arm_body: Option<String>,
+    /// Report and diff the added arms without writing files.
+    pub dry_run: Option<bool>,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct GenerateTraitImplParams {
     pub trait_name: String,
@@ -108,6 +397,45 @@ pub struct OrganizeImportsParams {
     pub file_path: String,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct AddMissingImportsParams {
+    pub file_path: String,
+    /// Crates or path prefixes to import from, most preferred first, e.g. `["std", "tokio"]`; tried before the defaults (local paths, workspace crates, then std, alloc, core).
+    pub prefer: Option<Vec<String>>,
+    /// Report and diff the imports without writing the file.
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct StructuralReplaceParams {
+    /// SSR rule `pattern ==>> replacement`, placeholders written `$name`.
+    pub rule: String,
+    /// Files or directories to restrict the rule to (default: whole workspace).
+    pub paths: Option<Vec<String>>,
+    /// Only return the diff without writing files.
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListCodeActionsParams {
+    pub file_path: String,
+    pub line: u32,
+    pub character: u32,
+    /// End of the selected range (requires end_character); defaults to the position.
+    pub end_line: Option<u32>,
+    pub end_character: Option<u32>,
+    /// Only actions of this kind or a sub-kind, e.g. `quickfix` or `refactor.extract`.
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ApplyCodeActionParams {
+    /// Id shown by list_code_actions.
+    pub id: u32,
+    /// Only return the diff without writing files.
+    pub dry_run: Option<bool>,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ApplyClippySuggestionsParams {
     pub file_path: String,
@@ -118,6 +446,38 @@ pub struct ValidateLifetimesParams {
     pub file_path: String,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct FixAllDiagnosticsParams {
+    /// Fix only this file (default: every file of the workspace).
+    pub file_path: Option<String>,
+    /// Workspace or package directory when no file is given (default: the server's working directory).
+    pub workspace_path: Option<String>,
+    /// Passes over the diagnostics before giving up (default: 10).
+    pub max_rounds: Option<u32>,
+    /// Report and diff the fixes without writing files.
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct AuditUnsafeParams {
+    /// Workspace or package directory (default: the server's working directory).
+    pub workspace_path: Option<String>,
+    /// Also count unsafe code in each downloaded dependency, geiger-style.
+    pub include_dependencies: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct FindUnusedParams {
+    /// Workspace or package directory (default: the server's working directory).
+    pub workspace_path: Option<String>,
+    /// Also report `pub` items reachable from a library root, which are skipped by default as public API.
+    pub include_public: Option<bool>,
+    /// `module` (default) or `file`.
+    pub group_by: Option<String>,
+    /// Delete the unused items (with their docs, attributes and inherent impls) and return the diffs.
+    pub delete: Option<bool>,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct GetTypeHierarchyParams {
     pub file_path: String,
@@ -129,6 +489,7 @@ pub struct GetTypeHierarchyParams {
 pub struct SuggestDependenciesParams {
     pub query: String,
     pub workspace_path: String,
+    pub add_crate: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -49,8 +49,11 @@ pub async fn suggest_dependencies_impl(
         .get("workspace_path")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing workspace_path parameter"))?;
+    let add_crate = args.get("add_crate").and_then(|v| v.as_str());
 
-    let result = analyzer.suggest_dependencies(query, workspace_path).await?;
+    let result = analyzer
+        .suggest_dependencies(query, workspace_path, add_crate)
+        .await?;
 
     Ok(ToolResult {
         content: vec![
@@ -1,7 +1,10 @@
 use crate::analyzer::RustAnalyzerClient;
+use crate::analyzer::cargo::{self, BenchOptions, BuildOptions, TestOptions};
+use crate::analyzer::manifest::{DependencyKind, DependencySpec};
 use crate::tools::types::ToolResult;
 use anyhow::Result;
 use serde_json::{Value, json};
+use std::time::Duration;
 
 pub async fn analyze_manifest_impl(
     args: Value,
@@ -12,7 +15,6 @@ pub async fn analyze_manifest_impl(
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing manifest_path parameter"))?;
 
-    // Implementation will analyze Cargo.toml file
     let result = analyzer.analyze_manifest(manifest_path).await?;
 
     Ok(ToolResult {
@@ -28,6 +30,86 @@ pub async fn analyze_manifest_impl(
     })
 }
 
+pub async fn add_dependency_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let manifest_path = args
+        .get("manifest_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing manifest_path parameter"))?;
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing name parameter"))?;
+
+    let spec = DependencySpec {
+        name: name.to_string(),
+        version: args
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        features: args.get("features").and_then(|v| v.as_array()).map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        }),
+        optional: args.get("optional").and_then(|v| v.as_bool()),
+        default_features: args.get("default_features").and_then(|v| v.as_bool()),
+        workspace: args.get("workspace").and_then(|v| v.as_bool()),
+        kind: DependencyKind::parse(args.get("kind").and_then(|v| v.as_str()))?,
+        target: args
+            .get("target")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+    };
+    let result = analyzer.add_dependency(manifest_path, &spec).await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn remove_dependency_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let manifest_path = args
+        .get("manifest_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing manifest_path parameter"))?;
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing name parameter"))?;
+    let kind = DependencyKind::parse(args.get("kind").and_then(|v| v.as_str()))?;
+    let target = args.get("target").and_then(|v| v.as_str());
+
+    let result = analyzer
+        .remove_dependency(manifest_path, name, kind, target)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
 pub async fn run_cargo_check_impl(
     args: Value,
     analyzer: &mut RustAnalyzerClient,
@@ -37,8 +119,370 @@ pub async fn run_cargo_check_impl(
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing workspace_path parameter"))?;
 
-    // Implementation will run cargo check and parse results
-    let result = analyzer.run_cargo_check(workspace_path).await?;
+    let timeout = args
+        .get("timeout_secs")
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_secs)
+        .unwrap_or(cargo::DEFAULT_CHECK_TIMEOUT);
+
+    let result = analyzer
+        .run_cargo_check(workspace_path, &build_options(&args), timeout)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn run_cargo_test_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let workspace_path = args
+        .get("workspace_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing workspace_path parameter"))?;
+    let optional_string = |key: &str| args.get(key).and_then(|v| v.as_str()).map(String::from);
+
+    let options = TestOptions {
+        package: optional_string("package"),
+        test_target: optional_string("test_target"),
+        filter: optional_string("filter"),
+        ignored: args
+            .get("ignored")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        features: string_list(&args, "features"),
+        no_default_features: args
+            .get("no_default_features")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+    let timeout = args
+        .get("timeout_secs")
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_secs)
+        .unwrap_or(cargo::DEFAULT_TEST_TIMEOUT);
+    let result = analyzer
+        .run_cargo_test(workspace_path, &options, timeout)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+fn string_list(args: &Value, key: &str) -> Vec<String> {
+    args.get(key)
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn build_options(args: &Value) -> BuildOptions {
+    let optional_string = |key: &str| args.get(key).and_then(|v| v.as_str()).map(String::from);
+    BuildOptions {
+        package: optional_string("package"),
+        bin: optional_string("bin"),
+        example: optional_string("example"),
+        profile: optional_string("profile"),
+        target: optional_string("target"),
+        features: string_list(args, "features"),
+        no_default_features: args
+            .get("no_default_features")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    }
+}
+
+pub async fn cargo_build_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let workspace_path = args
+        .get("workspace_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing workspace_path parameter"))?;
+
+    let result = analyzer
+        .cargo_build(workspace_path, &build_options(&args))
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn cargo_run_impl(args: Value, analyzer: &mut RustAnalyzerClient) -> Result<ToolResult> {
+    let workspace_path = args
+        .get("workspace_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing workspace_path parameter"))?;
+    let program_args = string_list(&args, "args");
+    let timeout = args
+        .get("timeout_secs")
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_secs)
+        .unwrap_or(cargo::DEFAULT_RUN_TIMEOUT);
+    let output_limit = args
+        .get("max_output_bytes")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(cargo::DEFAULT_OUTPUT_LIMIT);
+
+    let result = analyzer
+        .cargo_run(
+            workspace_path,
+            &build_options(&args),
+            &program_args,
+            timeout,
+            output_limit,
+        )
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn run_cargo_bench_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let workspace_path = args
+        .get("workspace_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing workspace_path parameter"))?;
+    let optional_string = |key: &str| args.get(key).and_then(|v| v.as_str()).map(String::from);
+    let options = BenchOptions {
+        package: optional_string("package"),
+        bench: optional_string("bench"),
+        filter: optional_string("filter"),
+        features: string_list(&args, "features"),
+        no_default_features: args
+            .get("no_default_features")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+    let baseline = args.get("baseline").and_then(|v| v.as_str());
+    let save_baseline = args.get("save_baseline").and_then(|v| v.as_str());
+    let threshold = args
+        .get("threshold_percent")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(cargo::DEFAULT_BENCH_THRESHOLD);
+    let timeout = args
+        .get("timeout_secs")
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_secs)
+        .unwrap_or(cargo::DEFAULT_BENCH_TIMEOUT);
+
+    let result = analyzer
+        .run_cargo_bench(
+            workspace_path,
+            &options,
+            baseline,
+            save_baseline,
+            threshold,
+            timeout,
+        )
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn list_features_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let manifest_path = args
+        .get("manifest_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing manifest_path parameter"))?;
+
+    let result = analyzer.list_features(manifest_path).await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn add_feature_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let manifest_path = args
+        .get("manifest_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing manifest_path parameter"))?;
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing name parameter"))?;
+    let activations: Vec<String> = args
+        .get("activations")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("Missing activations parameter"))?
+        .iter()
+        .filter_map(|v| v.as_str().map(String::from))
+        .collect();
+
+    let result = analyzer
+        .add_feature(manifest_path, name, &activations)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn remove_feature_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let manifest_path = args
+        .get("manifest_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing manifest_path parameter"))?;
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing name parameter"))?;
+
+    let result = analyzer.remove_feature(manifest_path, name).await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn gate_dependency_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let manifest_path = args
+        .get("manifest_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing manifest_path parameter"))?;
+    let dependency = args
+        .get("dependency")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing dependency parameter"))?;
+    let features: Vec<String> = args
+        .get("features")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("Missing features parameter"))?
+        .iter()
+        .filter_map(|v| v.as_str().map(String::from))
+        .collect();
+
+    let result = analyzer
+        .gate_dependency(manifest_path, dependency, &features)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn check_feature_combinations_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let manifest_path = args
+        .get("manifest_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing manifest_path parameter"))?;
+    let depth = args
+        .get("depth")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize);
+    let timeout = args
+        .get("timeout_secs")
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_secs)
+        .unwrap_or(cargo::DEFAULT_CHECK_TIMEOUT);
+
+    let result = analyzer
+        .check_feature_combinations(manifest_path, depth, timeout)
+        .await?;
 
     Ok(ToolResult {
         content: vec![
@@ -1,4 +1,5 @@
 use crate::analyzer::RustAnalyzerClient;
+use crate::analyzer::match_arms::FillOptions;
 use crate::tools::types::ToolResult;
 use anyhow::Result;
 use serde_json::{Value, json};
@@ -81,6 +82,55 @@ pub async fn generate_enum_impl(
     })
 }
 
+pub async fn fill_match_arms_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let file_path = args.get("file_path").and_then(|v| v.as_str());
+    let line = args.get("line").and_then(|v| v.as_u64()).map(|v| v as u32);
+    let character = args
+        .get("character")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    let position = match (line, character) {
+        (Some(line), character) => Some((line, character.unwrap_or(0))),
+        (None, Some(_)) => return Err(anyhow::anyhow!("Missing line parameter")),
+        (None, None) => None,
+    };
+    let enum_name = args.get("enum_name").and_then(|v| v.as_str());
+    let workspace_path = args.get("workspace_path").and_then(|v| v.as_str());
+    let options = FillOptions {
+        expand_nested: args
+            .get("expand_nested")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        arm_body: args
+            .get("arm_body")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        dry_run: args
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+
+    let result = analyzer
+        .fill_match_arms(file_path, position, enum_name, workspace_path, &options)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
 pub async fn generate_trait_impl_impl(
     args: Value,
     analyzer: &mut RustAnalyzerClient,
@@ -1,7 +1,9 @@
 use crate::analyzer::RustAnalyzerClient;
+use crate::analyzer::cargo;
 use crate::tools::types::ToolResult;
 use anyhow::Result;
 use serde_json::{Value, json};
+use std::time::Duration;
 
 pub async fn workspace_symbols_impl(
     args: Value,
@@ -27,3 +29,171 @@ pub async fn workspace_symbols_impl(
         ],
     })
 }
+
+pub async fn document_outline_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let file_path = args
+        .get("file_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing file_path parameter"))?;
+    let max_depth = args
+        .get("max_depth")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize);
+    let as_json = match args.get("format").and_then(|v| v.as_str()) {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => {
+            return Err(anyhow::anyhow!(
+                "Unknown format '{other}', expected text or json"
+            ));
+        }
+    };
+
+    let result = analyzer
+        .document_outline(file_path, max_depth, as_json)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn read_source_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let item_path = args.get("path").and_then(|v| v.as_str());
+    let file_path = args.get("file_path").and_then(|v| v.as_str());
+    let start_line = args.get("start_line").and_then(|v| v.as_u64());
+    let end_line = args.get("end_line").and_then(|v| v.as_u64());
+    let lines = match (start_line, end_line) {
+        (Some(start), end) => Some((start as u32, end.unwrap_or(start) as u32)),
+        (None, None) => None,
+        (None, Some(_)) => return Err(anyhow::anyhow!("end_line requires start_line")),
+    };
+    let context = args
+        .get("context_lines")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(5) as usize;
+
+    let result = analyzer
+        .read_source(item_path, file_path, lines, context)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn lookup_docs_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing path parameter"))?;
+    let workspace_path = args.get("workspace_path").and_then(|v| v.as_str());
+
+    let result = analyzer.lookup_docs(path, workspace_path).await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn list_runnables_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let file_path = args
+        .get("file_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing file_path parameter"))?;
+    let line = args.get("line").and_then(|v| v.as_u64());
+    let character = args.get("character").and_then(|v| v.as_u64());
+    let position = match (line, character) {
+        (Some(line), Some(character)) => Some((line as u32, character as u32)),
+        (None, None) => None,
+        _ => {
+            return Err(anyhow::anyhow!("line and character must be given together"));
+        }
+    };
+
+    let result = analyzer.list_runnables(file_path, position).await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn run_runnable_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let id = args
+        .get("id")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| anyhow::anyhow!("Missing id parameter"))?;
+    let timeout = args
+        .get("timeout_secs")
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_secs)
+        .unwrap_or(cargo::DEFAULT_RUN_TIMEOUT);
+    let output_limit = args
+        .get("max_output_bytes")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(cargo::DEFAULT_OUTPUT_LIMIT);
+
+    let result = analyzer
+        .run_runnable(id as usize, timeout, output_limit)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
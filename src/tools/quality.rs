@@ -1,4 +1,5 @@
 use crate::analyzer::RustAnalyzerClient;
+use crate::analyzer::quick_fixes::DEFAULT_FIX_ROUNDS;
 use crate::tools::types::ToolResult;
 use anyhow::Result;
 use serde_json::{Value, json};
@@ -50,3 +51,103 @@ pub async fn validate_lifetimes_impl(
         ],
     })
 }
+
+pub async fn fix_all_diagnostics_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let file_path = args.get("file_path").and_then(|v| v.as_str());
+    let workspace_path = args.get("workspace_path").and_then(|v| v.as_str());
+    let max_rounds = args
+        .get("max_rounds")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(DEFAULT_FIX_ROUNDS);
+    let dry_run = args
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let result = analyzer
+        .fix_all_diagnostics(file_path, workspace_path, max_rounds, dry_run)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn audit_unsafe_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let workspace_path = args.get("workspace_path").and_then(|v| v.as_str());
+    let include_dependencies = args
+        .get("include_dependencies")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let result = analyzer
+        .audit_unsafe(workspace_path, include_dependencies)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
+
+pub async fn find_unused_impl(
+    args: Value,
+    analyzer: &mut RustAnalyzerClient,
+) -> Result<ToolResult> {
+    let workspace_path = args.get("workspace_path").and_then(|v| v.as_str());
+    let include_public = args
+        .get("include_public")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let group_by_file = match args.get("group_by").and_then(|v| v.as_str()) {
+        None | Some("module") => false,
+        Some("file") => true,
+        Some(other) => {
+            return Err(anyhow::anyhow!(
+                "Unknown group_by '{other}', expected 'module' or 'file'"
+            ));
+        }
+    };
+    let delete = args
+        .get("delete")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let result = analyzer
+        .find_unused(workspace_path, include_public, group_by_file, delete)
+        .await?;
+
+    Ok(ToolResult {
+        content: vec![
+            json!({
+                "type": "text",
+                "text": result
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ],
+    })
+}
@@ -38,19 +38,53 @@ pub async fn execute_tool(
     match name {
         "find_definition" => crate::tools::analysis::find_definition_impl(args, analyzer).await,
         "find_references" => crate::tools::analysis::find_references_impl(args, analyzer).await,
+        "find_implementations" => {
+            crate::tools::analysis::find_implementations_impl(args, analyzer).await
+        }
+        "find_type_definition" => {
+            crate::tools::analysis::find_type_definition_impl(args, analyzer).await
+        }
+        "find_declaration" => crate::tools::analysis::find_declaration_impl(args, analyzer).await,
         "get_diagnostics" => crate::tools::analysis::get_diagnostics_impl(args, analyzer).await,
+        "workspace_diagnostics" => {
+            crate::tools::analysis::workspace_diagnostics_impl(args, analyzer).await
+        }
+        "expand_macro" => crate::tools::analysis::expand_macro_impl(args, analyzer).await,
+        "complete_at" => crate::tools::analysis::complete_at_impl(args, analyzer).await,
+        "signature_help" => crate::tools::analysis::signature_help_impl(args, analyzer).await,
+        "inlay_hints" => crate::tools::analysis::inlay_hints_impl(args, analyzer).await,
+        "semantic_tokens" => crate::tools::analysis::semantic_tokens_impl(args, analyzer).await,
         "workspace_symbols" => {
             crate::tools::navigation::workspace_symbols_impl(args, analyzer).await
         }
+        "document_outline" => crate::tools::navigation::document_outline_impl(args, analyzer).await,
+        "read_source" => crate::tools::navigation::read_source_impl(args, analyzer).await,
+        "lookup_docs" => crate::tools::navigation::lookup_docs_impl(args, analyzer).await,
+        "list_runnables" => crate::tools::navigation::list_runnables_impl(args, analyzer).await,
+        "run_runnable" => crate::tools::navigation::run_runnable_impl(args, analyzer).await,
         "rename_symbol" => crate::tools::refactoring::rename_symbol_impl(args, analyzer).await,
         "extract_function" => {
             crate::tools::refactoring::extract_function_impl(args, analyzer).await
         }
         "format_code" => crate::tools::formatting::format_code_impl(args, analyzer).await,
         "analyze_manifest" => crate::tools::cargo::analyze_manifest_impl(args, analyzer).await,
+        "add_dependency" => crate::tools::cargo::add_dependency_impl(args, analyzer).await,
+        "remove_dependency" => crate::tools::cargo::remove_dependency_impl(args, analyzer).await,
         "run_cargo_check" => crate::tools::cargo::run_cargo_check_impl(args, analyzer).await,
+        "run_cargo_test" => crate::tools::cargo::run_cargo_test_impl(args, analyzer).await,
+        "cargo_build" => crate::tools::cargo::cargo_build_impl(args, analyzer).await,
+        "cargo_run" => crate::tools::cargo::cargo_run_impl(args, analyzer).await,
+        "run_cargo_bench" => crate::tools::cargo::run_cargo_bench_impl(args, analyzer).await,
+        "list_features" => crate::tools::cargo::list_features_impl(args, analyzer).await,
+        "add_feature" => crate::tools::cargo::add_feature_impl(args, analyzer).await,
+        "remove_feature" => crate::tools::cargo::remove_feature_impl(args, analyzer).await,
+        "gate_dependency" => crate::tools::cargo::gate_dependency_impl(args, analyzer).await,
+        "check_feature_combinations" => {
+            crate::tools::cargo::check_feature_combinations_impl(args, analyzer).await
+        }
         "generate_struct" => crate::tools::generation::generate_struct_impl(args, analyzer).await,
         "generate_enum" => crate::tools::generation::generate_enum_impl(args, analyzer).await,
+        "fill_match_arms" => crate::tools::generation::fill_match_arms_impl(args, analyzer).await,
         "generate_trait_impl" => {
             crate::tools::generation::generate_trait_impl_impl(args, analyzer).await
         }
@@ -62,12 +96,29 @@ pub async fn execute_tool(
         "organize_imports" => {
             crate::tools::refactoring::organize_imports_impl(args, analyzer).await
         }
+        "add_missing_imports" => {
+            crate::tools::refactoring::add_missing_imports_impl(args, analyzer).await
+        }
+        "structural_replace" => {
+            crate::tools::refactoring::structural_replace_impl(args, analyzer).await
+        }
+        "list_code_actions" => {
+            crate::tools::refactoring::list_code_actions_impl(args, analyzer).await
+        }
+        "apply_code_action" => {
+            crate::tools::refactoring::apply_code_action_impl(args, analyzer).await
+        }
         "apply_clippy_suggestions" => {
             crate::tools::quality::apply_clippy_suggestions_impl(args, analyzer).await
         }
         "validate_lifetimes" => {
             crate::tools::quality::validate_lifetimes_impl(args, analyzer).await
         }
+        "fix_all_diagnostics" => {
+            crate::tools::quality::fix_all_diagnostics_impl(args, analyzer).await
+        }
+        "audit_unsafe" => crate::tools::quality::audit_unsafe_impl(args, analyzer).await,
+        "find_unused" => crate::tools::quality::find_unused_impl(args, analyzer).await,
         "get_type_hierarchy" => {
             crate::tools::advanced::get_type_hierarchy_impl(args, analyzer).await
         }
@@ -109,6 +160,45 @@ pub fn get_tools() -> Vec<ToolDefinition> {
                 "required": ["file_path", "line", "character"]
             }),
         ),
+        ToolDefinition::new(
+            "find_implementations",
+            "Find impls of a trait or implementations of a trait method at a position, with source snippets",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"}
+                },
+                "required": ["file_path", "line", "character"]
+            }),
+        ),
+        ToolDefinition::new(
+            "find_type_definition",
+            "Find the definition of the type of the expression or binding at a position, with a source snippet",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"}
+                },
+                "required": ["file_path", "line", "character"]
+            }),
+        ),
+        ToolDefinition::new(
+            "find_declaration",
+            "Find the declaration of the symbol at a position, with a source snippet",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"}
+                },
+                "required": ["file_path", "line", "character"]
+            }),
+        ),
         ToolDefinition::new(
             "get_diagnostics",
             "Get compiler diagnostics for a file",
@@ -120,6 +210,89 @@ pub fn get_tools() -> Vec<ToolDefinition> {
                 "required": ["file_path"]
             }),
         ),
+        ToolDefinition::new(
+            "workspace_diagnostics",
+            "Deduplicated diagnostics for the whole workspace from rust-analyzer and optionally cargo check, counted by severity and file, with what was introduced or fixed since the previous call",
+            json!({
+                "type": "object",
+                "properties": {
+                    "workspace_path": {"type": "string"},
+                    "check": {"type": "boolean"},
+                    "min_severity": {"type": "string", "enum": ["error", "warning", "information", "hint"]}
+                }
+            }),
+        ),
+        ToolDefinition::new(
+            "expand_macro",
+            "Recursively expand the macro call at a position, optionally falling back to whole-module rustc expansion on nightly",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"},
+                    "fallback": {"type": "boolean"}
+                },
+                "required": ["file_path", "line", "character"]
+            }),
+        ),
+        ToolDefinition::new(
+            "complete_at",
+            "List completions at a position with kinds, signatures and docs, optionally after inserting a temporary . or :: to discover a type's API",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"},
+                    "trigger": {"type": "string", "enum": [".", "::"]},
+                    "query": {"type": "string"},
+                    "limit": {"type": "number"}
+                },
+                "required": ["file_path", "line", "character"]
+            }),
+        ),
+        ToolDefinition::new(
+            "signature_help",
+            "Show the signatures of the call at a position with parameter names, types and docs, highlighting the active parameter",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"}
+                },
+                "required": ["file_path", "line", "character"]
+            }),
+        ),
+        ToolDefinition::new(
+            "inlay_hints",
+            "Show a file or line range annotated with inferred types, parameter names, chaining and lifetime elision hints",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "start_line": {"type": "number"},
+                    "end_line": {"type": "number"}
+                },
+                "required": ["file_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "semantic_tokens",
+            "List semantic tokens of a file or line range (type, modifiers such as mutable, unsafe or trait, and text), optionally filtered by token type and modifier",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "start_line": {"type": "number"},
+                    "end_line": {"type": "number"},
+                    "token_types": {"type": "array", "items": {"type": "string"}},
+                    "modifiers": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["file_path"]
+            }),
+        ),
         ToolDefinition::new(
             "workspace_symbols",
             "Search for symbols in the workspace",
@@ -131,6 +304,71 @@ pub fn get_tools() -> Vec<ToolDefinition> {
                 "required": ["query"]
             }),
         ),
+        ToolDefinition::new(
+            "document_outline",
+            "Outline a file's modules, types, impls, functions, fields and consts with signatures and line ranges",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "max_depth": {"type": "number"},
+                    "format": {"type": "string", "enum": ["text", "json"]}
+                },
+                "required": ["file_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "read_source",
+            "Read source of dependencies and std by item path (e.g. tokio::sync::Mutex::lock) or by file and line range in the registry, git checkouts or rust-src",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string"},
+                    "file_path": {"type": "string"},
+                    "start_line": {"type": "number"},
+                    "end_line": {"type": "number"},
+                    "context_lines": {"type": "number"}
+                }
+            }),
+        ),
+        ToolDefinition::new(
+            "lookup_docs",
+            "Look up API docs for an item path (signature, docs, examples, methods, trait impls) from offline rustdoc JSON of the exact dependency versions",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string"},
+                    "workspace_path": {"type": "string"}
+                },
+                "required": ["path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "list_runnables",
+            "List tests, binaries, benches and doctests rust-analyzer can run in a file or at a position, with their cargo commands",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"}
+                },
+                "required": ["file_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "run_runnable",
+            "Execute a runnable by id from the last list_runnables result",
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "number"},
+                    "timeout_secs": {"type": "number"},
+                    "max_output_bytes": {"type": "number"}
+                },
+                "required": ["id"]
+            }),
+        ),
         ToolDefinition::new(
             "rename_symbol",
             "Rename a symbol with scope awareness",
@@ -158,7 +396,7 @@ pub fn get_tools() -> Vec<ToolDefinition> {
         ),
         ToolDefinition::new(
             "analyze_manifest",
-            "Parse and analyze Cargo.toml file",
+            "Summarize Cargo.toml: package, workspace members, dependency tables and selectable features",
             json!({
                 "type": "object",
                 "properties": {
@@ -167,17 +405,224 @@ pub fn get_tools() -> Vec<ToolDefinition> {
                 "required": ["manifest_path"]
             }),
         ),
+        ToolDefinition::new(
+            "add_dependency",
+            "Add a dependency to Cargo.toml or update an existing one, preserving formatting and validating with cargo metadata",
+            json!({
+                "type": "object",
+                "properties": {
+                    "manifest_path": {"type": "string"},
+                    "name": {"type": "string"},
+                    "version": {"type": "string"},
+                    "features": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    },
+                    "optional": {"type": "boolean"},
+                    "default_features": {"type": "boolean"},
+                    "workspace": {"type": "boolean"},
+                    "kind": {"type": "string", "enum": ["normal", "dev", "build"]},
+                    "target": {"type": "string"}
+                },
+                "required": ["manifest_path", "name"]
+            }),
+        ),
+        ToolDefinition::new(
+            "remove_dependency",
+            "Remove a dependency and the features referring to it from Cargo.toml, validating with cargo metadata",
+            json!({
+                "type": "object",
+                "properties": {
+                    "manifest_path": {"type": "string"},
+                    "name": {"type": "string"},
+                    "kind": {"type": "string", "enum": ["normal", "dev", "build"]},
+                    "target": {"type": "string"}
+                },
+                "required": ["manifest_path", "name"]
+            }),
+        ),
         ToolDefinition::new(
             "run_cargo_check",
-            "Execute cargo check and parse errors",
+            "Run cargo check on the selected targets and features under a timeout and return structured compiler diagnostics",
             json!({
                 "type": "object",
                 "properties": {
-                    "workspace_path": {"type": "string"}
+                    "workspace_path": {"type": "string"},
+                    "package": {"type": "string"},
+                    "bin": {"type": "string"},
+                    "example": {"type": "string"},
+                    "profile": {"type": "string"},
+                    "target": {"type": "string"},
+                    "features": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    },
+                    "no_default_features": {"type": "boolean"},
+                    "timeout_secs": {"type": "number"}
+                },
+                "required": ["workspace_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "run_cargo_test",
+            "Run cargo test with optional filters and return per-test status, timing and failure output",
+            json!({
+                "type": "object",
+                "properties": {
+                    "workspace_path": {"type": "string"},
+                    "package": {"type": "string"},
+                    "test_target": {"type": "string"},
+                    "filter": {"type": "string"},
+                    "ignored": {"type": "boolean"},
+                    "features": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    },
+                    "no_default_features": {"type": "boolean"},
+                    "timeout_secs": {"type": "number"}
+                },
+                "required": ["workspace_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "cargo_build",
+            "Run cargo build and return produced artifacts and structured compiler diagnostics",
+            json!({
+                "type": "object",
+                "properties": {
+                    "workspace_path": {"type": "string"},
+                    "package": {"type": "string"},
+                    "bin": {"type": "string"},
+                    "example": {"type": "string"},
+                    "profile": {"type": "string"},
+                    "target": {"type": "string"},
+                    "features": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    },
+                    "no_default_features": {"type": "boolean"}
+                },
+                "required": ["workspace_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "cargo_run",
+            "Build and run a binary or example, capturing stdout/stderr with a timeout and output size limit",
+            json!({
+                "type": "object",
+                "properties": {
+                    "workspace_path": {"type": "string"},
+                    "package": {"type": "string"},
+                    "bin": {"type": "string"},
+                    "example": {"type": "string"},
+                    "profile": {"type": "string"},
+                    "target": {"type": "string"},
+                    "features": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    },
+                    "no_default_features": {"type": "boolean"},
+                    "args": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    },
+                    "timeout_secs": {"type": "number"},
+                    "max_output_bytes": {"type": "number"}
+                },
+                "required": ["workspace_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "run_cargo_bench",
+            "Run cargo bench (libtest or criterion), compare timings with a saved baseline and optionally save a new one",
+            json!({
+                "type": "object",
+                "properties": {
+                    "workspace_path": {"type": "string"},
+                    "package": {"type": "string"},
+                    "bench": {"type": "string"},
+                    "filter": {"type": "string"},
+                    "features": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    },
+                    "no_default_features": {"type": "boolean"},
+                    "baseline": {"type": "string"},
+                    "save_baseline": {"type": "string"},
+                    "threshold_percent": {"type": "number"},
+                    "timeout_secs": {"type": "number"}
                 },
                 "required": ["workspace_path"]
             }),
         ),
+        ToolDefinition::new(
+            "list_features",
+            "List Cargo features with everything each one transitively enables",
+            json!({
+                "type": "object",
+                "properties": {
+                    "manifest_path": {"type": "string"}
+                },
+                "required": ["manifest_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "add_feature",
+            "Add or replace a feature definition in Cargo.toml",
+            json!({
+                "type": "object",
+                "properties": {
+                    "manifest_path": {"type": "string"},
+                    "name": {"type": "string"},
+                    "activations": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    }
+                },
+                "required": ["manifest_path", "name", "activations"]
+            }),
+        ),
+        ToolDefinition::new(
+            "remove_feature",
+            "Remove a feature definition and its activations from Cargo.toml",
+            json!({
+                "type": "object",
+                "properties": {
+                    "manifest_path": {"type": "string"},
+                    "name": {"type": "string"}
+                },
+                "required": ["manifest_path", "name"]
+            }),
+        ),
+        ToolDefinition::new(
+            "gate_dependency",
+            "Make a dependency optional behind the given features, or regular again when no features are given",
+            json!({
+                "type": "object",
+                "properties": {
+                    "manifest_path": {"type": "string"},
+                    "dependency": {"type": "string"},
+                    "features": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    }
+                },
+                "required": ["manifest_path", "dependency", "features"]
+            }),
+        ),
+        ToolDefinition::new(
+            "check_feature_combinations",
+            "Run cargo check --no-default-features for every feature combination, optionally limited to a depth, with a timeout per check",
+            json!({
+                "type": "object",
+                "properties": {
+                    "manifest_path": {"type": "string"},
+                    "depth": {"type": "number"},
+                    "timeout_secs": {"type": "number"}
+                },
+                "required": ["manifest_path"]
+            }),
+        ),
         ToolDefinition::new(
             "extract_function",
             "Extract selected code into a new function",
@@ -222,6 +667,23 @@ pub fn get_tools() -> Vec<ToolDefinition> {
                 "required": ["enum_name", "variants", "file_path"]
             }),
         ),
+        ToolDefinition::new(
+            "fill_match_arms",
+            "Add the missing arms of the match at a position, or of every non-exhaustive match over an enum in a file or the workspace, with todo!() or a given body, optionally expanding nested enum fields",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"},
+                    "enum_name": {"type": "string"},
+                    "workspace_path": {"type": "string"},
+                    "expand_nested": {"type": "boolean"},
+                    "arm_body": {"type": "string"},
+                    "dry_run": {"type": "boolean"}
+                }
+            }),
+        ),
         ToolDefinition::new(
             "generate_trait_impl",
             "Generate a trait implementation for a struct",
@@ -286,6 +748,63 @@ pub fn get_tools() -> Vec<ToolDefinition> {
                 "required": ["file_path"]
             }),
         ),
+        ToolDefinition::new(
+            "add_missing_imports",
+            "Import every unresolved name in a file using rust-analyzer's auto_import candidates, choosing between crates by a preference list and reporting ambiguous names",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "prefer": {"type": "array", "items": {"type": "string"}},
+                    "dry_run": {"type": "boolean"}
+                },
+                "required": ["file_path"]
+            }),
+        ),
+        ToolDefinition::new(
+            "structural_replace",
+            "Structural search and replace with rules like `foo($a, $b) ==>> bar($b, $a)` across files, directories or the workspace, with dry-run diffs",
+            json!({
+                "type": "object",
+                "properties": {
+                    "rule": {"type": "string"},
+                    "paths": {
+                        "type": "array",
+                        "items": {"type": "string"}
+                    },
+                    "dry_run": {"type": "boolean"}
+                },
+                "required": ["rule"]
+            }),
+        ),
+        ToolDefinition::new(
+            "list_code_actions",
+            "List rust-analyzer assists and quick fixes for a position or range, with kinds, titles and ids",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "line": {"type": "number"},
+                    "character": {"type": "number"},
+                    "end_line": {"type": "number"},
+                    "end_character": {"type": "number"},
+                    "kind": {"type": "string"}
+                },
+                "required": ["file_path", "line", "character"]
+            }),
+        ),
+        ToolDefinition::new(
+            "apply_code_action",
+            "Resolve and apply a code action by id from the last list_code_actions result",
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "number"},
+                    "dry_run": {"type": "boolean"}
+                },
+                "required": ["id"]
+            }),
+        ),
         ToolDefinition::new(
             "apply_clippy_suggestions",
             "Apply clippy lint suggestions to improve code quality",
@@ -308,6 +827,43 @@ pub fn get_tools() -> Vec<ToolDefinition> {
                 "required": ["file_path"]
             }),
         ),
+        ToolDefinition::new(
+            "fix_all_diagnostics",
+            "Apply the quick fix of every diagnostic that has exactly one (or a preferred one) in a file or the workspace, repeating until a fixpoint, and report the diagnostics that remain",
+            json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {"type": "string"},
+                    "workspace_path": {"type": "string"},
+                    "max_rounds": {"type": "number"},
+                    "dry_run": {"type": "boolean"}
+                }
+            }),
+        ),
+        ToolDefinition::new(
+            "audit_unsafe",
+            "Inventory unsafe blocks, unsafe fns, impls, traits and extern blocks with their enclosing function, SAFETY comments, unsafe operations and counts per module, optionally with per-dependency counts",
+            json!({
+                "type": "object",
+                "properties": {
+                    "workspace_path": {"type": "string"},
+                    "include_dependencies": {"type": "boolean"}
+                }
+            }),
+        ),
+        ToolDefinition::new(
+            "find_unused",
+            "Find functions, methods, types, traits, consts and statics with no references outside their definition, grouped by module or file, optionally deleting them",
+            json!({
+                "type": "object",
+                "properties": {
+                    "workspace_path": {"type": "string"},
+                    "include_public": {"type": "boolean"},
+                    "group_by": {"type": "string", "enum": ["module", "file"]},
+                    "delete": {"type": "boolean"}
+                }
+            }),
+        ),
         ToolDefinition::new(
             "get_type_hierarchy",
             "Get type hierarchy for a symbol at specified position",
@@ -323,12 +879,13 @@ pub fn get_tools() -> Vec<ToolDefinition> {
         ),
         ToolDefinition::new(
             "suggest_dependencies",
-            "Suggest crate dependencies based on code patterns",
+            "Suggest crates for unresolved imports and a free-text query from the local crate index, optionally adding one to Cargo.toml",
             json!({
                 "type": "object",
                 "properties": {
                     "query": {"type": "string"},
-                    "workspace_path": {"type": "string"}
+                    "workspace_path": {"type": "string"},
+                    "add_crate": {"type": "string"}
                 },
                 "required": ["query", "workspace_path"]
             }),
@@ -348,7 +905,7 @@ pub fn get_tools() -> Vec<ToolDefinition> {
         ),
         ToolDefinition::new(
             "move_items",
-            "Move code items from one file to another",
+            "Move items (with their impls) to another module file and update all paths that reference them",
             json!({
                 "type": "object",
                 "properties": {